@@ -4,6 +4,7 @@
 idle-welcome = Willkommen
 idle-scan-prompt = Bitte Ausweis scannen
 idle-employees-present = { $count } Mitarbeiter anwesend
+idle-qr-hint = Mit der App einstempeln
 
 # Clock-in screen
 clock-in-title = Eingestempelt
@@ -21,6 +22,47 @@ clock-out-weekly = Wochenstunden: { $worked } / { $target }
 clock-out-overtime = Überstunden: { $minutes } Minuten
 clock-out-vacation = Verbleibender Urlaub: { $days } Tage
 
+# Project selection
+project-select-title = Projekt wählen
+project-select-search = Projekt suchen…
+project-select-empty = Keine Projekte gefunden
+project-select-cancel = Abbrechen
+project-confirm-title = Projekt gebucht
+
+# Info mode
+info-button = Kontostand anzeigen
+info-prompt = Bitte scannen Sie Ihren Ausweis
+info-no-booking = Es wird keine Buchung erstellt.
+info-title = Kontostand
+info-clocked-in = Aktuell eingestempelt
+info-clocked-out = Aktuell nicht eingestempelt
+info-today = Heute
+info-upcoming-absences = Geplante Abwesenheiten
+
+# PIN keypad
+pin-button = Ausweis vergessen? PIN eingeben
+pin-employee-number = Personalnummer eingeben
+pin-enter-pin = PIN für Personalnummer { $number }
+pin-wrong = Personalnummer oder PIN falsch
+pin-too-short = Die PIN hat mindestens { $digits } Ziffern
+pin-locked = Zu viele Fehlversuche – Personalnummer für { $minutes } min gesperrt
+pin-badge-pin = Bitte PIN eingeben
+pin-wrong-badge-pin = PIN falsch
+pin-offline-unverifiable = PIN kann offline nicht geprüft werden
+pin-badge-locked = Zu viele Fehlversuche – Ausweis für { $minutes } min gesperrt
+
+# Maintenance screen
+maintenance-title = Wartungsmodus
+maintenance-backend-reachable = Backend erreichbar
+maintenance-backend-unreachable = Backend nicht erreichbar
+maintenance-recent-scans = Letzte Scans
+maintenance-sync-now = Jetzt synchronisieren
+maintenance-test-sound = Ton testen
+maintenance-test-reader = Leser testen
+maintenance-reload-config = Konfiguration neu laden
+maintenance-exit = Beenden
+maintenance-locks-in = Sperrt in { $seconds }s
+
 # Error screen
 error-badge-not-found = Ausweis nicht erkannt
 error-server-unavailable = Server nicht erreichbar
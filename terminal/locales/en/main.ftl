@@ -4,6 +4,7 @@
 idle-welcome = Welcome
 idle-scan-prompt = Please scan your badge
 idle-employees-present = { $count } employees present
+idle-qr-hint = Clock in with the app
 
 # Clock-in screen
 clock-in-title = Clocked In
@@ -21,6 +22,47 @@ clock-out-weekly = Weekly hours: { $worked } / { $target }
 clock-out-overtime = Overtime: { $minutes } minutes
 clock-out-vacation = Remaining vacation: { $days } days
 
+# Project selection
+project-select-title = Select project
+project-select-search = Search project…
+project-select-empty = No projects found
+project-select-cancel = Cancel
+project-confirm-title = Project booked
+
+# Info mode
+info-button = Show balance
+info-prompt = Please scan your badge
+info-no-booking = No booking will be created.
+info-title = Balance
+info-clocked-in = Currently clocked in
+info-clocked-out = Currently not clocked in
+info-today = Today
+info-upcoming-absences = Upcoming absences
+
+# PIN keypad
+pin-button = Forgot your badge? Enter PIN
+pin-employee-number = Enter employee number
+pin-enter-pin = PIN for employee number { $number }
+pin-wrong = Employee number or PIN wrong
+pin-too-short = The PIN has at least { $digits } digits
+pin-locked = Too many attempts – employee number locked for { $minutes } min
+pin-badge-pin = Please enter your PIN
+pin-wrong-badge-pin = Wrong PIN
+pin-offline-unverifiable = PIN cannot be checked offline
+pin-badge-locked = Too many attempts – badge locked for { $minutes } min
+
+# Maintenance screen
+maintenance-title = Maintenance mode
+maintenance-backend-reachable = Backend reachable
+maintenance-backend-unreachable = Backend unreachable
+maintenance-recent-scans = Recent scans
+maintenance-sync-now = Sync now
+maintenance-test-sound = Test sound
+maintenance-test-reader = Test reader
+maintenance-reload-config = Reload configuration
+maintenance-exit = Exit
+maintenance-locks-in = Locks in { $seconds }s
+
 # Error screen
 error-badge-not-found = Badge not recognized
 error-server-unavailable = Server unavailable
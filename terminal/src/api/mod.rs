@@ -1,14 +1,17 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use log::{error, warn};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 use crate::config::ApiConfig;
 
+/// Images (photos, logo) larger than this are rejected instead of being loaded into memory.
+/// Matches the backend's upload limit for logos.
+const MAX_IMAGE_BYTES: usize = 2 * 1024 * 1024;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)] // `id` and `photo_url` are deserialized from the server response for future use
 pub struct EmployeeInfo {
     pub id: String,
     pub first_name: String,
@@ -26,6 +29,45 @@ pub struct ClockResponse {
     pub today_break_minutes: u32,
     pub overtime_minutes: i32,
     pub remaining_vacation_days: f32,
+    /// ID of the time entry just created, for taking it back; not sent by older backends.
+    #[serde(default)]
+    pub entry_id: Option<String>,
+}
+
+/// An approved absence (vacation, sick leave, business trip) in the near future.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingAbsence {
+    /// `VACATION`, `SICK_LEAVE` or `BUSINESS_TRIP`.
+    pub absence_type: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+/// Read-only balance summary returned by the status endpoint; no time entry is created.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployeeStatus {
+    pub employee: EmployeeInfo,
+    pub clocked_in: bool,
+    pub today_work_minutes: u32,
+    pub overtime_minutes: i32,
+    pub remaining_vacation_days: f32,
+    #[serde(default)]
+    pub upcoming_absences: Vec<UpcomingAbsence>,
+}
+
+/// Public branding information from `GET /branding/info`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrandingInfo {
+    pub company_name: String,
+    pub has_logo: bool,
+    /// Hex colour such as `#1F4E9A`; not set by older backends.
+    #[serde(default)]
+    pub primary_color: Option<String>,
+    #[serde(default)]
+    pub accent_color: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,6 +77,108 @@ struct ClockRequest {
     terminal_id: String,
 }
 
+/// Clock request for an employee who forgot their badge.  Deliberately not `Debug`, so the
+/// PIN cannot end up in a log line.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PinClockRequest {
+    employee_number: String,
+    pin: String,
+    terminal_id: String,
+}
+
+/// Replay of a PIN clocking that was buffered offline.  The PIN is never stored, so the
+/// backend records the entry as unverified for HR to review.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OfflinePinClockRequest {
+    employee_number: String,
+    terminal_id: String,
+    timestamp: DateTime<Utc>,
+}
+
+/// Badge scan with the PIN asked for in two-factor mode.  Not `Debug`, like
+/// `PinClockRequest`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BadgePinClockRequest {
+    rfid_tag_id: String,
+    pin: String,
+    terminal_id: String,
+}
+
+/// Replay of a two-factor scan whose PIN was checked against the roster while offline.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifiedScanRequest {
+    rfid_tag_id: String,
+    terminal_id: String,
+    timestamp: DateTime<Utc>,
+}
+
+/// Per-badge two-factor data from `GET /terminal/roster`, cached for offline PIN checks.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterEntry {
+    pub rfid_tag_id: String,
+    /// Hex SHA-256 of `pin_salt` followed by the PIN; `None` if the employee has no PIN.
+    #[serde(default)]
+    pub pin_hash: Option<String>,
+    #[serde(default)]
+    pub pin_salt: Option<String>,
+    /// The employee clocks with the badge alone, even in two-factor mode.
+    #[serde(default)]
+    pub pin_exempt: bool,
+}
+
+/// A revoked badge from `GET /terminal/blocklist`, cached so it stays refused offline.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedBadge {
+    pub rfid_tag_id: String,
+}
+
+/// A project / cost center that time can be booked against at the terminal.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    #[serde(default)]
+    pub cost_center: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectSwitchRequest {
+    rfid_tag_id: String,
+    terminal_id: String,
+    project_id: String,
+    /// When the switch happened at the terminal — differs from the upload time for
+    /// switches replayed from the offline buffer.
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UndoRequest {
+    entry_id: String,
+    terminal_id: String,
+}
+
+/// Reported when the reader locks itself after a burst of unknown or revoked badges.  Carries
+/// no tag IDs.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanAlertRequest {
+    terminal_id: String,
+    rejected_scans: u32,
+    window_seconds: u64,
+    lockout_seconds: u64,
+    timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiClient {
     client: Client,
@@ -48,6 +192,8 @@ pub enum ApiError {
     NotFound(String),
     /// 401 — terminal is not authorised.
     Unauthorized,
+    /// 403 — the PIN asked for in two-factor mode was wrong.
+    Forbidden,
     /// 409 — another terminal processed a scan for this employee at the same moment.
     /// The terminal should ask the user to scan again.
     Conflict,
@@ -64,6 +210,7 @@ impl std::fmt::Display for ApiError {
         match self {
             ApiError::NotFound(msg) => write!(f, "Not found: {}", msg),
             ApiError::Unauthorized => write!(f, "Unauthorized"),
+            ApiError::Forbidden => write!(f, "PIN rejected"),
             ApiError::Conflict => write!(f, "Scan conflict — please scan again"),
             ApiError::ServerError(msg) => write!(f, "Server error: {}", msg),
             ApiError::NetworkError(msg) => write!(f, "Network error: {}", msg),
@@ -97,20 +244,347 @@ impl ApiClient {
             terminal_id: terminal_id.to_string(),
         };
 
+        let response = self
+            .send_with_retry(
+                || self.client.post(&url).json(&request),
+                "RFID tag not registered",
+            )
+            .await?;
+        response
+            .json::<ClockResponse>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Looks up the employee's balances without changing their clock state.
+    ///
+    /// Uses POST so the tag ID travels in the body and stays out of access logs.
+    pub async fn get_status(
+        &self,
+        rfid_tag_id: &str,
+        terminal_id: &str,
+    ) -> Result<EmployeeStatus, ApiError> {
+        let url = format!("{}/terminal/status", self.base_url);
+        let request = ClockRequest {
+            rfid_tag_id: rfid_tag_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+        };
+
+        let response = self
+            .send_with_retry(
+                || self.client.post(&url).json(&request),
+                "RFID tag not registered",
+            )
+            .await?;
+        response
+            .json::<EmployeeStatus>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Clocks in or out with employee number and PIN instead of a badge.
+    ///
+    /// The backend answers 404 both for unknown employee numbers and for wrong PINs, so
+    /// numbers cannot be probed from the terminal.
+    pub async fn clock_with_pin(
+        &self,
+        employee_number: &str,
+        pin: &str,
+        terminal_id: &str,
+    ) -> Result<ClockResponse, ApiError> {
+        let url = format!("{}/terminal/pin", self.base_url);
+        let request = PinClockRequest {
+            employee_number: employee_number.to_string(),
+            pin: pin.to_string(),
+            terminal_id: terminal_id.to_string(),
+        };
+
+        let response = self
+            .send_with_retry(
+                || self.client.post(&url).json(&request),
+                "Employee number or PIN wrong",
+            )
+            .await?;
+        response
+            .json::<ClockResponse>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Badge scan in two-factor mode: the backend checks the PIN before toggling the state.
+    pub async fn clock_with_badge_pin(
+        &self,
+        rfid_tag_id: &str,
+        pin: &str,
+        terminal_id: &str,
+    ) -> Result<ClockResponse, ApiError> {
+        let url = format!("{}/terminal/scan", self.base_url);
+        let request = BadgePinClockRequest {
+            rfid_tag_id: rfid_tag_id.to_string(),
+            pin: pin.to_string(),
+            terminal_id: terminal_id.to_string(),
+        };
+
+        let response = self
+            .send_with_retry(
+                || self.client.post(&url).json(&request),
+                "RFID tag not registered",
+            )
+            .await?;
+        response
+            .json::<ClockResponse>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Replays a two-factor scan whose PIN was verified offline against the cached roster.
+    pub async fn replay_verified_scan(
+        &self,
+        rfid_tag_id: &str,
+        terminal_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let url = format!("{}/terminal/scan/offline-verified", self.base_url);
+        let request = VerifiedScanRequest {
+            rfid_tag_id: rfid_tag_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+            timestamp,
+        };
+
+        self.send_with_retry(
+            || self.client.post(&url).json(&request),
+            "RFID tag not registered",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Fetches the two-factor roster (PIN hashes and exemptions) for this terminal.
+    pub async fn get_roster(&self, terminal_id: &str) -> Result<Vec<RosterEntry>, ApiError> {
+        let url = format!("{}/terminal/roster", self.base_url);
+        let response = self
+            .send_with_retry(
+                || self.client.get(&url).query(&[("terminalId", terminal_id)]),
+                "Roster not available",
+            )
+            .await?;
+        response
+            .json::<Vec<RosterEntry>>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Fetches the badges revoked since they were issued.
+    pub async fn get_blocklist(&self, terminal_id: &str) -> Result<Vec<BlockedBadge>, ApiError> {
+        let url = format!("{}/terminal/blocklist", self.base_url);
+        let response = self
+            .send_with_retry(
+                || self.client.get(&url).query(&[("terminalId", terminal_id)]),
+                "Blocklist not available",
+            )
+            .await?;
+        response
+            .json::<Vec<BlockedBadge>>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Fetches this terminal's configuration document: a partial `terminal.toml` as JSON.
+    pub async fn get_remote_config(&self, terminal_id: &str) -> Result<toml::Table, ApiError> {
+        let url = format!("{}/terminal/config", self.base_url);
+        let response = self
+            .send_with_retry(
+                || self.client.get(&url).query(&[("terminalId", terminal_id)]),
+                "No configuration for this terminal",
+            )
+            .await?;
+        response
+            .json::<toml::Table>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Replays a PIN clocking from the offline buffer, without the PIN.
+    pub async fn replay_pin_clock(
+        &self,
+        employee_number: &str,
+        terminal_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let url = format!("{}/terminal/pin/offline", self.base_url);
+        let request = OfflinePinClockRequest {
+            employee_number: employee_number.to_string(),
+            terminal_id: terminal_id.to_string(),
+            timestamp,
+        };
+
+        self.send_with_retry(
+            || self.client.post(&url).json(&request),
+            "Employee number not registered",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Downloads an employee photo.  `photo_url` may be absolute or relative to the API base URL.
+    pub async fn download_photo(&self, photo_url: &str) -> Result<Vec<u8>, ApiError> {
+        self.download_image(&self.resolve_url(photo_url), "Photo not found")
+            .await
+    }
+
+    /// Fetches the company name, logo flag and brand colours.
+    pub async fn get_branding(&self) -> Result<BrandingInfo, ApiError> {
+        let url = format!("{}/branding/info", self.base_url);
+        let response = self
+            .send_with_retry(|| self.client.get(&url), "Branding not configured")
+            .await?;
+        response
+            .json::<BrandingInfo>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Downloads the company logo uploaded in the admin panel.
+    pub async fn download_logo(&self) -> Result<Vec<u8>, ApiError> {
+        let url = format!("{}/branding/logo", self.base_url);
+        self.download_image(&url, "No logo uploaded").await
+    }
+
+    async fn download_image(
+        &self,
+        url: &str,
+        not_found_message: &str,
+    ) -> Result<Vec<u8>, ApiError> {
+        let response = self
+            .send_with_retry(|| self.client.get(url), not_found_message)
+            .await?;
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > MAX_IMAGE_BYTES)
+        {
+            return Err(ApiError::ServerError("Image too large".to_string()));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| ApiError::NetworkError(e.to_string()))?;
+        if bytes.len() > MAX_IMAGE_BYTES {
+            return Err(ApiError::ServerError("Image too large".to_string()));
+        }
+        Ok(bytes.to_vec())
+    }
+
+    fn resolve_url(&self, url: &str) -> String {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            url.to_string()
+        } else {
+            format!("{}/{}", self.base_url, url.trim_start_matches('/'))
+        }
+    }
+
+    /// Fetches the active projects this terminal may book against.
+    pub async fn get_projects(&self, terminal_id: &str) -> Result<Vec<Project>, ApiError> {
+        let url = format!("{}/terminal/projects", self.base_url);
+        let response = self
+            .send_with_retry(
+                || self.client.get(&url).query(&[("terminalId", terminal_id)]),
+                "Project list not available",
+            )
+            .await?;
+        response
+            .json::<Vec<Project>>()
+            .await
+            .map_err(|e| ApiError::ServerError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Takes back the time entry `entry_id` just created at this terminal, e.g. after a
+    /// mistaken scan.
+    pub async fn undo_clocking(&self, entry_id: &str, terminal_id: &str) -> Result<(), ApiError> {
+        let url = format!("{}/terminal/undo", self.base_url);
+        let request = UndoRequest {
+            entry_id: entry_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+        };
+
+        self.send_with_retry(
+            || self.client.post(&url).json(&request),
+            "Time entry not found or already taken back",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Tells the backend that `rejected_scans` unknown or revoked badges were read within
+    /// `window_seconds` and the reader is locked for `lockout_seconds`.
+    pub async fn report_scan_alert(
+        &self,
+        terminal_id: &str,
+        rejected_scans: u32,
+        window_seconds: u64,
+        lockout_seconds: u64,
+    ) -> Result<(), ApiError> {
+        let url = format!("{}/terminal/alert", self.base_url);
+        let request = ScanAlertRequest {
+            terminal_id: terminal_id.to_string(),
+            rejected_scans,
+            window_seconds,
+            lockout_seconds,
+            timestamp: Utc::now(),
+        };
+
+        self.send_with_retry(
+            || self.client.post(&url).json(&request),
+            "Alert endpoint not available",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Attributes the employee's current working time to `project_id` from `timestamp` on.
+    pub async fn switch_project(
+        &self,
+        rfid_tag_id: &str,
+        terminal_id: &str,
+        project_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        let url = format!("{}/terminal/project", self.base_url);
+        let request = ProjectSwitchRequest {
+            rfid_tag_id: rfid_tag_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+            project_id: project_id.to_string(),
+            timestamp,
+        };
+
+        self.send_with_retry(
+            || self.client.post(&url).json(&request),
+            "RFID tag or project not registered",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Sends a request, retrying network failures, timeouts and unexpected server errors up to
+    /// `retry_attempts` times.  404, 401, 403 and 409 are definitive answers and returned immediately.
+    async fn send_with_retry(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+        not_found_message: &str,
+    ) -> Result<Response, ApiError> {
         let mut last_error = ApiError::NetworkError("No attempts made".to_string());
 
         for attempt in 1..=self.retry_attempts {
-            match self.client.post(&url).json(&request).send().await {
+            match build().send().await {
                 Ok(response) => {
                     let status = response.status();
                     if status.is_success() {
-                        return response.json::<ClockResponse>().await.map_err(|e| {
-                            ApiError::ServerError(format!("Failed to parse response: {}", e))
-                        });
+                        return Ok(response);
                     } else if status.as_u16() == 404 {
-                        return Err(ApiError::NotFound("RFID tag not registered".to_string()));
+                        return Err(ApiError::NotFound(not_found_message.to_string()));
                     } else if status.as_u16() == 401 {
                         return Err(ApiError::Unauthorized);
+                    } else if status.as_u16() == 403 {
+                        return Err(ApiError::Forbidden);
                     } else if status.as_u16() == 409 {
                         // Race condition: another terminal already toggled this employee's
                         // state between our status-check and our clock action.
@@ -140,8 +614,7 @@ impl ApiClient {
         Err(last_error)
     }
 
-    /// Checks whether the backend is reachable.  Used by the heartbeat subscription.
-    #[allow(dead_code)]
+    /// Checks whether the backend is reachable.  Used by the maintenance screen.
     pub async fn health_check(&self) -> bool {
         let url = format!("{}/actuator/health", self.base_url);
         match self.client.get(&url).send().await {
@@ -178,12 +651,26 @@ mod tests {
         assert_eq!(url, "http://localhost:8080/api/actuator/health");
     }
 
+    #[test]
+    fn test_resolve_photo_url() {
+        let client = make_client("http://localhost:8080/api");
+        assert_eq!(
+            client.resolve_url("/users/abc/photo"),
+            "http://localhost:8080/api/users/abc/photo"
+        );
+        assert_eq!(
+            client.resolve_url("https://cdn.example.com/p/abc.jpg"),
+            "https://cdn.example.com/p/abc.jpg"
+        );
+    }
+
     #[test]
     fn test_api_error_display() {
         assert!(ApiError::NotFound("x".to_string())
             .to_string()
             .contains("Not found"));
         assert!(ApiError::Unauthorized.to_string().contains("Unauthorized"));
+        assert!(ApiError::Forbidden.to_string().contains("PIN"));
         assert!(ApiError::Conflict.to_string().contains("scan again"));
         assert!(ApiError::ServerError("500".to_string())
             .to_string()
@@ -217,6 +704,17 @@ mod tests {
         assert_eq!(response.entry_type, "CLOCK_IN");
         assert_eq!(response.today_work_minutes, 0);
         assert_eq!(response.remaining_vacation_days, 25.0);
+        assert!(response.entry_id.is_none());
+
+        let json = json.replace(
+            r#""entryType""#,
+            r#""entryId": "7f3c9a10-5b1e-4c2d-9a4e-2f1b6c8d0e11", "entryType""#,
+        );
+        let response: ClockResponse = serde_json::from_str(&json).expect("deserialization failed");
+        assert_eq!(
+            response.entry_id.as_deref(),
+            Some("7f3c9a10-5b1e-4c2d-9a4e-2f1b6c8d0e11")
+        );
     }
 
     #[test]
@@ -229,4 +727,152 @@ mod tests {
         assert!(json.contains("rfidTagId"), "expected camelCase: {}", json);
         assert!(json.contains("terminalId"), "expected camelCase: {}", json);
     }
+
+    #[test]
+    fn test_employee_status_deserialization() {
+        let json = r#"{
+            "employee": {
+                "id": "abc-123",
+                "firstName": "Max",
+                "lastName": "Mustermann",
+                "photoUrl": null
+            },
+            "clockedIn": true,
+            "todayWorkMinutes": 245,
+            "todayBreakMinutes": 30,
+            "overtimeMinutes": -15,
+            "remainingVacationDays": 12.5,
+            "upcomingAbsences": [
+                {"absenceType": "VACATION", "startDate": "2024-02-01", "endDate": "2024-02-09"}
+            ]
+        }"#;
+
+        let status: EmployeeStatus = serde_json::from_str(json).expect("deserialization failed");
+        assert!(status.clocked_in);
+        assert_eq!(status.overtime_minutes, -15);
+        assert_eq!(status.upcoming_absences.len(), 1);
+        assert_eq!(status.upcoming_absences[0].absence_type, "VACATION");
+        assert_eq!(
+            status.upcoming_absences[0].end_date,
+            NaiveDate::from_ymd_opt(2024, 2, 9).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_branding_info_deserialization() {
+        // Current backends only send name and logo flag.
+        let json = r#"{"companyName": "Firma GmbH", "hasLogo": true}"#;
+        let info: BrandingInfo = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(info.company_name, "Firma GmbH");
+        assert!(info.has_logo);
+        assert!(info.primary_color.is_none());
+
+        let json = r##"{"companyName": "", "hasLogo": false, "primaryColor": "#1F4E9A"}"##;
+        let info: BrandingInfo = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(info.primary_color.as_deref(), Some("#1F4E9A"));
+    }
+
+    #[test]
+    fn test_project_list_deserialization() {
+        let json = r#"[
+            {"id": "p-1", "code": "WS-01", "name": "Werkstatt", "costCenter": "4711"},
+            {"id": "p-2", "code": "MNT", "name": "Montage"}
+        ]"#;
+
+        let projects: Vec<Project> = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].cost_center.as_deref(), Some("4711"));
+        assert_eq!(projects[1].code, "MNT");
+        assert!(projects[1].cost_center.is_none());
+    }
+
+    #[test]
+    fn test_project_switch_request_serialization() {
+        let req = ProjectSwitchRequest {
+            rfid_tag_id: "TAG123".to_string(),
+            terminal_id: "terminal-1".to_string(),
+            project_id: "p-1".to_string(),
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_string(&req).expect("serialization failed");
+        assert!(json.contains("projectId"), "expected camelCase: {}", json);
+        assert!(json.contains("timestamp"), "expected timestamp: {}", json);
+
+        let req = UndoRequest {
+            entry_id: "e-1".to_string(),
+            terminal_id: "terminal-1".to_string(),
+        };
+        let json = serde_json::to_string(&req).expect("serialization failed");
+        assert!(json.contains("entryId"), "expected camelCase: {}", json);
+
+        let req = ScanAlertRequest {
+            terminal_id: "terminal-1".to_string(),
+            rejected_scans: 10,
+            window_seconds: 60,
+            lockout_seconds: 120,
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_string(&req).expect("serialization failed");
+        assert!(
+            json.contains("rejectedScans"),
+            "expected camelCase: {}",
+            json
+        );
+        assert!(
+            json.contains("lockoutSeconds"),
+            "expected camelCase: {}",
+            json
+        );
+    }
+
+    #[test]
+    fn test_pin_requests_serialization() {
+        let req = PinClockRequest {
+            employee_number: "1042".to_string(),
+            pin: "4711".to_string(),
+            terminal_id: "terminal-1".to_string(),
+        };
+        let json = serde_json::to_string(&req).expect("serialization failed");
+        assert!(
+            json.contains("employeeNumber"),
+            "expected camelCase: {}",
+            json
+        );
+
+        let req = OfflinePinClockRequest {
+            employee_number: "1042".to_string(),
+            terminal_id: "terminal-1".to_string(),
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_string(&req).expect("serialization failed");
+        assert!(
+            !json.contains("pin\""),
+            "PIN must not be replayed: {}",
+            json
+        );
+        assert!(json.contains("timestamp"), "expected timestamp: {}", json);
+    }
+
+    #[test]
+    fn test_roster_deserialization() {
+        let json = r#"[
+            {"rfidTagId": "TAG1", "pinHash": "ab12", "pinSalt": "s1"},
+            {"rfidTagId": "TAG2", "pinExempt": true}
+        ]"#;
+
+        let roster: Vec<RosterEntry> = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(roster[0].pin_salt.as_deref(), Some("s1"));
+        assert!(!roster[0].pin_exempt);
+        assert!(roster[1].pin_hash.is_none());
+        assert!(roster[1].pin_exempt);
+    }
+
+    #[test]
+    fn test_blocklist_deserialization() {
+        let json = r#"[{"rfidTagId": "TAG1", "revokedAt": "2026-10-01T08:00:00Z"}]"#;
+
+        let blocklist: Vec<BlockedBadge> =
+            serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(blocklist[0].rfid_tag_id, "TAG1");
+    }
 }
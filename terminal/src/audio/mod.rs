@@ -1,59 +1,689 @@
-use log::{debug, warn};
-use rodio::{Decoder, Player};
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use log::{debug, info, warn};
+use rodio::buffer::SamplesBuffer;
+use rodio::{
+    ChannelCount, Decoder, DeviceSinkBuilder, MixerDeviceSink, Player, Sample, SampleRate, Source,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs::File;
 use std::io::BufReader;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
 
-use crate::config::AudioConfig;
+use crate::clock::{Clock, SystemClock};
+use crate::config::{AudioConfig, LocaleConfig, SoundSet, VolumeProfile};
+
+/// Scan outcomes that can have a clip of their own in the sound pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEvent {
+    ClockIn,
+    ClockOut,
+    OfflineSaved,
+    UnknownBadge,
+    Conflict,
+    ServerError,
+    /// A revoked badge was scanned.
+    BadgeBlocked,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 7] = [
+        SoundEvent::ClockIn,
+        SoundEvent::ClockOut,
+        SoundEvent::OfflineSaved,
+        SoundEvent::UnknownBadge,
+        SoundEvent::Conflict,
+        SoundEvent::ServerError,
+        SoundEvent::BadgeBlocked,
+    ];
+
+    fn is_error(self) -> bool {
+        matches!(
+            self,
+            SoundEvent::UnknownBadge
+                | SoundEvent::Conflict
+                | SoundEvent::ServerError
+                | SoundEvent::BadgeBlocked
+        )
+    }
+
+    fn clip(self, set: &SoundSet) -> Option<&str> {
+        match self {
+            SoundEvent::ClockIn => set.clock_in.as_deref(),
+            SoundEvent::ClockOut => set.clock_out.as_deref(),
+            SoundEvent::OfflineSaved => set.offline_saved.as_deref(),
+            SoundEvent::UnknownBadge => set.unknown_badge.as_deref(),
+            SoundEvent::Conflict => set.conflict.as_deref(),
+            SoundEvent::ServerError => set.server_error.as_deref(),
+            SoundEvent::BadgeBlocked => set.badge_blocked.as_deref(),
+        }
+    }
+}
+
+/// A sound decoded into memory at startup, so playback never touches the disk.
+#[derive(Debug, Clone)]
+pub struct Clip {
+    /// Source file, or `beep:success` / `beep:error` for the synthesized fallback.
+    pub name: String,
+    samples: SamplesBuffer,
+}
+
+impl Clip {
+    /// Decodes a WAV file completely.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let decoder = Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+        let samples: Vec<Sample> = decoder.take(MAX_CLIP_SAMPLES).collect();
+        if samples.is_empty() {
+            return Err("no audio data".to_string());
+        }
+        Ok(Self {
+            name: path.to_string(),
+            samples: SamplesBuffer::new(channels, sample_rate, samples),
+        })
+    }
+
+    /// Built-in tone used when a sound file is missing or unreadable: two short high beeps for
+    /// success, one long low tone for errors.
+    pub fn beep(error: bool) -> Self {
+        let tones: &[(f32, u32)] = if error {
+            &[(330.0, 450)]
+        } else {
+            &[(880.0, 120), (0.0, 60), (1320.0, 120)]
+        };
+        let mut samples = Vec::new();
+        for (frequency, duration_ms) in tones {
+            let count = BEEP_SAMPLE_RATE * duration_ms / 1000;
+            let fade = (BEEP_SAMPLE_RATE / 200).min(count / 2).max(1);
+            for i in 0..count {
+                // Short linear fade in/out avoids clicks at the tone edges.
+                let envelope = (i.min(count - 1 - i) as f32 / fade as f32).min(1.0);
+                let t = i as f32 / BEEP_SAMPLE_RATE as f32;
+                samples.push(0.5 * envelope * (2.0 * std::f32::consts::PI * frequency * t).sin());
+            }
+        }
+        Self {
+            name: if error { "beep:error" } else { "beep:success" }.to_string(),
+            samples: SamplesBuffer::new(
+                ChannelCount::MIN,
+                SampleRate::new(BEEP_SAMPLE_RATE).expect("non-zero sample rate"),
+                samples,
+            ),
+        }
+    }
+
+    #[cfg(test)]
+    fn duration(&self) -> std::time::Duration {
+        self.samples.total_duration().unwrap_or_default()
+    }
+}
+
+const BEEP_SAMPLE_RATE: u32 = 44_100;
+/// Clips are feedback sounds; anything longer than ~30 s of stereo audio is cut off.
+const MAX_CLIP_SAMPLES: usize = 48_000 * 2 * 30;
+
+/// Where clips are played.
+pub trait AudioSink: Send + Sync {
+    /// Starts `clip`, cutting off whatever is still playing.
+    fn play(&self, clip: &Clip, volume: f32);
+}
+
+/// The default output device, driven by one long-lived audio thread.
+///
+/// The thread owns the output stream and mixer.  If the device cannot be opened (e.g. busy), it
+/// is retried on the next sound instead of giving up for good.
+pub struct DeviceSink {
+    tx: mpsc::Sender<(Clip, f32)>,
+}
+
+impl DeviceSink {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel::<(Clip, f32)>();
+        let spawned = std::thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || {
+                let mut output: Option<MixerDeviceSink> = None;
+                let mut current: Option<Player> = None;
+                for (clip, volume) in rx {
+                    if output.is_none() {
+                        match DeviceSinkBuilder::open_default_sink() {
+                            Ok(mut sink) => {
+                                sink.log_on_drop(false);
+                                output = Some(sink);
+                            }
+                            Err(e) => {
+                                warn!("Failed to open audio output: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    let Some(sink) = &output else { continue };
+
+                    let player = Player::connect_new(sink.mixer());
+                    player.set_volume(volume);
+                    player.append(clip.samples);
+                    if let Some(previous) = current.replace(player) {
+                        previous.stop();
+                    }
+                    debug!("Playing sound: {}", clip.name);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to start audio thread: {}", e);
+        }
+        Self { tx }
+    }
+}
+
+impl AudioSink for DeviceSink {
+    fn play(&self, clip: &Clip, volume: f32) {
+        if self.tx.send((clip.clone(), volume)).is_err() {
+            warn!("Audio thread is not running; dropping {}", clip.name);
+        }
+    }
+}
+
+/// Discards all sound.  Remembers the most recent clips so headless runs and tests can check
+/// what would have been played.
+#[derive(Debug, Clone, Default)]
+pub struct NullSink {
+    played: Arc<Mutex<VecDeque<(String, f32)>>>,
+}
+
+const NULL_SINK_HISTORY: usize = 32;
+
+impl NullSink {
+    /// Names of the most recently "played" clips, oldest first.
+    #[cfg(test)]
+    pub fn played(&self) -> Vec<String> {
+        self.history().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Volumes the most recent clips were played at, oldest first.
+    #[cfg(test)]
+    pub fn volumes(&self) -> Vec<f32> {
+        self.history()
+            .into_iter()
+            .map(|(_, volume)| volume)
+            .collect()
+    }
+
+    #[cfg(test)]
+    fn history(&self) -> Vec<(String, f32)> {
+        self.played
+            .lock()
+            .map(|p| p.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl AudioSink for NullSink {
+    fn play(&self, clip: &Clip, volume: f32) {
+        if let Ok(mut played) = self.played.lock() {
+            if played.len() == NULL_SINK_HISTORY {
+                played.pop_front();
+            }
+            played.push_back((clip.name.clone(), volume));
+        }
+    }
+}
+
+/// Parsed `audio.schedule`: volume factor per local time-of-day range.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VolumeSchedule {
+    profiles: Vec<(NaiveTime, NaiveTime, f32)>,
+}
+
+impl VolumeSchedule {
+    /// Skips (and logs) profiles with unparsable times.
+    pub fn new(profiles: &[VolumeProfile]) -> Self {
+        let profiles = profiles
+            .iter()
+            .filter_map(|p| match (parse_time(&p.start), parse_time(&p.end)) {
+                (Some(start), Some(end)) => Some((start, end, p.volume.clamp(0.0, 2.0))),
+                _ => {
+                    warn!(
+                        "Ignoring volume profile {}–{}: times must be HH:MM",
+                        p.start, p.end
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self { profiles }
+    }
+
+    /// Volume factor at local time `time`; 1.0 outside all profiles.
+    pub fn factor_at(&self, time: NaiveTime) -> f32 {
+        self.profiles
+            .iter()
+            .find(|(start, end, _)| {
+                if start < end {
+                    *start <= time && time < *end
+                } else {
+                    // Runs over midnight; start == end covers the whole day.
+                    time >= *start || time < *end
+                }
+            })
+            .map(|(_, _, factor)| *factor)
+            .unwrap_or(1.0)
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
 
 pub struct AudioPlayer {
     config: AudioConfig,
+    /// `locale.language`, which picks the spoken clips.
+    language: String,
+    /// Decoded clip for each event, after the language and missing-file fallbacks.
+    clips: HashMap<SoundEvent, Clip>,
+    success: Clip,
+    error: Clip,
+    sink: Box<dyn AudioSink>,
+    schedule: VolumeSchedule,
+    timezone: Tz,
+    clock: Arc<dyn Clock>,
 }
 
 impl AudioPlayer {
-    pub fn new(config: AudioConfig) -> Self {
-        Self { config }
+    /// Decodes all configured clips and opens the default output device, or a [`NullSink`]
+    /// when audio is disabled.
+    pub fn new(config: AudioConfig, locale: &LocaleConfig) -> Self {
+        let sink = default_sink(&config);
+        Self::with_sink(config, locale, sink, Arc::new(SystemClock))
     }
 
-    pub fn play_success(&self) {
-        if self.config.enabled {
-            self.play_sound(&self.config.success_sound);
+    pub fn with_sink(
+        config: AudioConfig,
+        locale: &LocaleConfig,
+        sink: Box<dyn AudioSink>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let (clips, success, error) = load_clips(&config, &locale.language);
+        Self {
+            schedule: VolumeSchedule::new(&config.schedule),
+            config,
+            language: locale.language.clone(),
+            clips,
+            success,
+            error,
+            sink,
+            timezone: parse_timezone(locale),
+            clock,
         }
     }
 
+    /// Takes over a reloaded configuration.  Volumes, the schedule and the time zone change
+    /// in place; the clips are decoded again only when a sound file or the language changes,
+    /// and the output device stays open unless audio is switched off.
+    pub fn reconfigure(&mut self, config: AudioConfig, locale: &LocaleConfig) {
+        if config.enabled != self.config.enabled {
+            self.sink = default_sink(&config);
+        }
+        if clip_paths(&config) != clip_paths(&self.config) || locale.language != self.language {
+            (self.clips, self.success, self.error) = load_clips(&config, &locale.language);
+        }
+        self.schedule = VolumeSchedule::new(&config.schedule);
+        self.timezone = parse_timezone(locale);
+        self.language = locale.language.clone();
+        self.config = config;
+    }
+
+    /// Plays the clip for `event`, e.g. the spoken "Guten Morgen" on clock-in.
+    pub fn play(&self, event: SoundEvent) {
+        self.play_clip(&self.clips[&event], event.is_error());
+    }
+
+    pub fn play_success(&self) {
+        self.play_clip(&self.success, false);
+    }
+
     pub fn play_error(&self) {
-        if self.config.enabled {
-            self.play_sound(&self.config.error_sound);
+        self.play_clip(&self.error, true);
+    }
+
+    fn play_clip(&self, clip: &Clip, error: bool) {
+        let volume = self.volume(error);
+        if self.config.enabled && volume > 0.0 {
+            self.sink.play(clip, volume);
+        }
+    }
+
+    /// Current volume for success or error sounds, after the time-of-day schedule.
+    pub fn volume(&self, error: bool) -> f32 {
+        let base = if error {
+            self.config.error_volume
+        } else {
+            self.config.success_volume
         }
+        .unwrap_or(self.config.volume);
+        let local_time = self.clock.now().with_timezone(&self.timezone).time();
+        base * self.schedule.factor_at(local_time)
     }
+}
 
-    fn play_sound(&self, path: &str) {
-        let path = path.to_string();
-        let volume = self.config.volume;
+/// The settings that decide which files are decoded.
+fn clip_paths(config: &AudioConfig) -> (&str, &str, &SoundSet, &BTreeMap<String, SoundSet>) {
+    (
+        &config.success_sound,
+        &config.error_sound,
+        &config.events,
+        &config.spoken,
+    )
+}
 
-        std::thread::spawn(move || {
-            match rodio::DeviceSinkBuilder::open_default_sink() {
-                Ok(handle) => {
-                    let player = Player::connect_new(handle.mixer());
-                    player.set_volume(volume);
-                    match File::open(&path) {
-                        Ok(file) => {
-                            let reader = BufReader::new(file);
-                            match Decoder::new(reader) {
-                                Ok(source) => {
-                                    player.append(source);
-                                    player.sleep_until_end();
-                                    debug!("Played sound: {}", path);
-                                }
-                                Err(e) => warn!("Failed to decode audio {}: {}", path, e),
-                            }
-                        }
-                        Err(e) => warn!("Failed to open audio file {}: {}", path, e),
+fn default_sink(config: &AudioConfig) -> Box<dyn AudioSink> {
+    if config.enabled {
+        Box::new(DeviceSink::spawn())
+    } else {
+        Box::new(NullSink::default())
+    }
+}
+
+fn parse_timezone(locale: &LocaleConfig) -> Tz {
+    locale.timezone.parse::<Tz>().unwrap_or_else(|_| {
+        warn!(
+            "Unknown time zone {:?}; volume schedules use UTC",
+            locale.timezone
+        );
+        Tz::UTC
+    })
+}
+
+/// Decodes the clip for every event and the generic success and error tones.
+fn load_clips(config: &AudioConfig, language: &str) -> (HashMap<SoundEvent, Clip>, Clip, Clip) {
+    if config.enabled {
+        for path in missing_files(config) {
+            warn!("Sound file {} not found; using the fallback sound", path);
+        }
+    }
+
+    // Clips shared by several events are decoded only once.
+    let mut decoded: HashMap<String, Clip> = HashMap::new();
+    let mut load = |path: &str, error: bool| -> Clip {
+        decoded
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                Clip::load(path).unwrap_or_else(|e| {
+                    if Path::new(path).exists() {
+                        warn!("Failed to decode sound {}: {}", path, e);
                     }
-                    // Keep handle alive until playback is done (drops here)
-                    drop(handle);
-                }
-                Err(e) => warn!("Failed to initialize audio output: {}", e),
+                    Clip::beep(error)
+                })
+            })
+            .clone()
+    };
+
+    let success = load(&config.success_sound, false);
+    let error = load(&config.error_sound, true);
+    let clips = SoundEvent::ALL
+        .iter()
+        .map(|event| {
+            let path = resolve_clip(config, language, *event);
+            (*event, load(&path, event.is_error()))
+        })
+        .collect();
+    info!("Loaded {} sound clips", decoded.len());
+    (clips, success, error)
+}
+
+/// Picks the most specific existing clip: the spoken clip for `language`, then the per-event
+/// clip, then the generic success or error tone.
+fn resolve_clip(config: &AudioConfig, language: &str, event: SoundEvent) -> String {
+    let spoken = config.spoken.get(language).and_then(|set| event.clip(set));
+    spoken
+        .into_iter()
+        .chain(event.clip(&config.events))
+        .find(|path| Path::new(path).exists())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            if event.is_error() {
+                config.error_sound.clone()
+            } else {
+                config.success_sound.clone()
             }
-        });
+        })
+}
+
+/// Every configured sound file that does not exist, in all languages.
+pub fn missing_files(config: &AudioConfig) -> Vec<String> {
+    let sets = std::iter::once(&config.events).chain(config.spoken.values());
+    let configured: BTreeSet<&str> = sets
+        .flat_map(|set| SoundEvent::ALL.iter().filter_map(|event| event.clip(set)))
+        .chain([config.success_sound.as_str(), config.error_sound.as_str()])
+        .collect();
+    configured
+        .into_iter()
+        .filter(|path| !Path::new(path).exists())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use std::path::PathBuf;
+
+    fn make_dir(test_name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("zt-audio-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn touch(dir: &Path, name: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, b"RIFF").unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn config(dir: &Path) -> AudioConfig {
+        AudioConfig {
+            enabled: true,
+            success_sound: touch(dir, "success.wav"),
+            error_sound: touch(dir, "error.wav"),
+            volume: 0.7,
+            success_volume: None,
+            error_volume: None,
+            schedule: Vec::new(),
+            events: SoundSet::default(),
+            spoken: Default::default(),
+        }
+    }
+
+    fn make_player(config: AudioConfig, sink: NullSink, utc_now: &str) -> AudioPlayer {
+        let locale = LocaleConfig {
+            language: "de".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+        };
+        let now = utc_now.parse().expect("valid RFC 3339 timestamp");
+        AudioPlayer::with_sink(config, &locale, Box::new(sink), Arc::new(FixedClock(now)))
+    }
+
+    /// A short silent 16-bit mono WAV file that decodes.
+    fn wav(dir: &Path, name: &str) -> String {
+        let samples = 800u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + samples * 2).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(samples * 2).to_le_bytes());
+        bytes.resize(bytes.len() + samples as usize * 2, 0);
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn profile(start: &str, end: &str, volume: f32) -> VolumeProfile {
+        VolumeProfile {
+            start: start.to_string(),
+            end: end.to_string(),
+            volume,
+        }
+    }
+
+    #[test]
+    fn test_spoken_clip_is_preferred_for_its_language() {
+        let dir = make_dir("spoken");
+        let mut config = config(&dir);
+        config.events.clock_in = Some(touch(&dir, "clock-in.wav"));
+        config.spoken.insert(
+            "de".to_string(),
+            SoundSet {
+                clock_in: Some(touch(&dir, "guten-morgen.wav")),
+                ..SoundSet::default()
+            },
+        );
+
+        assert!(resolve_clip(&config, "de", SoundEvent::ClockIn).ends_with("guten-morgen.wav"));
+        assert!(resolve_clip(&config, "en", SoundEvent::ClockIn).ends_with("clock-in.wav"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_missing_clips_fall_back_to_generic_tones() {
+        let dir = make_dir("fallback");
+        let mut config = config(&dir);
+        config.events.conflict = Some(dir.join("missing.wav").to_string_lossy().to_string());
+
+        assert_eq!(
+            resolve_clip(&config, "de", SoundEvent::Conflict),
+            config.error_sound
+        );
+        assert_eq!(
+            resolve_clip(&config, "de", SoundEvent::OfflineSaved),
+            config.success_sound
+        );
+        assert_eq!(missing_files(&config).len(), 1);
+        assert!(missing_files(&config)[0].ends_with("missing.wav"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unreadable_files_play_the_synthesized_beep() {
+        let dir = make_dir("beep");
+        // `touch` writes a truncated header that no decoder accepts.
+        let config = config(&dir);
+        let sink = NullSink::default();
+        let player = make_player(config, sink.clone(), "2026-01-15T12:00:00Z");
+
+        player.play(SoundEvent::ClockIn);
+        player.play(SoundEvent::UnknownBadge);
+        player.play_success();
+        assert_eq!(
+            sink.played(),
+            vec!["beep:success", "beep:error", "beep:success"]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disabled_audio_plays_nothing() {
+        let dir = make_dir("disabled");
+        let mut config = config(&dir);
+        config.enabled = false;
+        let sink = NullSink::default();
+        let player = make_player(config, sink.clone(), "2026-01-15T12:00:00Z");
+
+        player.play(SoundEvent::ClockOut);
+        assert!(sink.played().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reconfigure_keeps_the_sink_and_picks_up_new_clips() {
+        let dir = make_dir("reconfigure");
+        let mut config = config(&dir);
+        let sink = NullSink::default();
+        let mut player = make_player(config.clone(), sink.clone(), "2026-01-15T12:00:00Z");
+        let locale = LocaleConfig {
+            language: "de".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+        };
+
+        config.volume = 0.5;
+        player.reconfigure(config.clone(), &locale);
+        player.play(SoundEvent::ClockIn);
+
+        let clock_in = wav(&dir, "clock-in.wav");
+        config.events.clock_in = Some(clock_in.clone());
+        player.reconfigure(config.clone(), &locale);
+        player.play(SoundEvent::ClockIn);
+
+        config.enabled = false;
+        player.reconfigure(config, &locale);
+        player.play(SoundEvent::ClockIn);
+
+        // The sink the player was built with heard both clips; disabled audio plays nothing.
+        assert_eq!(sink.played(), vec!["beep:success".to_string(), clock_in]);
+        assert_eq!(sink.volumes(), vec![0.5, 0.5]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_beep_durations() {
+        assert_eq!(Clip::beep(false).duration().as_millis(), 300);
+        assert_eq!(Clip::beep(true).duration().as_millis(), 450);
+    }
+
+    #[test]
+    fn test_schedule_wraps_midnight() {
+        let schedule = VolumeSchedule::new(&[
+            profile("22:00", "06:00", 0.3),
+            profile("12:00", "13:00", 0.0),
+            profile("25:00", "26:00", 0.5),
+        ]);
+        let at = |t: &str| schedule.factor_at(parse_time(t).unwrap());
+        assert_eq!(at("21:59"), 1.0);
+        assert_eq!(at("22:00"), 0.3);
+        assert_eq!(at("03:00"), 0.3);
+        assert_eq!(at("06:00"), 1.0);
+        assert_eq!(at("12:30"), 0.0);
+    }
+
+    #[test]
+    fn test_volumes_follow_schedule_in_terminal_time_zone() {
+        let dir = make_dir("schedule");
+        let mut config = config(&dir);
+        config.error_volume = Some(1.0);
+        config.schedule = vec![
+            profile("22:00", "06:00", 0.5),
+            profile("12:00", "13:00", 0.0),
+        ];
+
+        // 21:30 UTC is 22:30 in Berlin in winter.
+        let sink = NullSink::default();
+        let player = make_player(config.clone(), sink.clone(), "2026-01-15T21:30:00Z");
+        player.play_success();
+        player.play_error();
+        assert_eq!(sink.volumes(), vec![0.35, 0.5]);
+
+        // 11:30 UTC is 13:30 in Berlin in summer: outside the muted lunch break.
+        let sink = NullSink::default();
+        let player = make_player(config.clone(), sink.clone(), "2026-07-15T11:30:00Z");
+        player.play_success();
+        assert_eq!(sink.volumes(), vec![0.7]);
+
+        // Muted profiles play nothing at all.
+        let sink = NullSink::default();
+        let player = make_player(config, sink.clone(), "2026-01-15T11:30:00Z");
+        player.play_error();
+        assert!(sink.played().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
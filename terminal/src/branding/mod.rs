@@ -0,0 +1,210 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::api::{ApiClient, ApiError, BrandingInfo};
+
+const BRANDING_FILE: &str = "branding.json";
+const LOGO_FILE_STEM: &str = "company-logo";
+
+/// Branding as last synced from the backend.  Cached on disk so the terminal starts with the
+/// right logo and colours even while the backend is unreachable.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Branding {
+    pub company_name: Option<String>,
+    pub primary_color: Option<String>,
+    pub accent_color: Option<String>,
+    /// Downloaded logo, if the backend has one.
+    pub logo_path: Option<PathBuf>,
+}
+
+/// Local cache directory holding `branding.json` and the downloaded logo.
+#[derive(Debug, Clone)]
+pub struct BrandingStore {
+    dir: PathBuf,
+}
+
+impl BrandingStore {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    /// Returns the cached branding, or the default (no overrides) if nothing was synced yet.
+    pub fn load(&self) -> Branding {
+        let path = self.dir.join(BRANDING_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Branding::default();
+        };
+        match serde_json::from_str::<Branding>(&content) {
+            // A logo deleted behind our back must not leave a dangling path.
+            Ok(mut branding) => {
+                if branding.logo_path.as_deref().is_some_and(|p| !p.exists()) {
+                    branding.logo_path = None;
+                }
+                branding
+            }
+            Err(e) => {
+                warn!("Ignoring corrupt branding cache {}: {}", path.display(), e);
+                Branding::default()
+            }
+        }
+    }
+
+    /// Replaces the cache with `info` and the optional logo image.
+    pub fn save(&self, info: &BrandingInfo, logo: Option<&[u8]>) -> io::Result<Branding> {
+        std::fs::create_dir_all(&self.dir)?;
+        self.remove_logos()?;
+
+        let logo_path = match logo.and_then(|bytes| logo_extension(bytes).map(|ext| (bytes, ext))) {
+            Some((bytes, ext)) => {
+                let path = self.dir.join(format!("{}.{}", LOGO_FILE_STEM, ext));
+                write_atomic(&path, bytes)?;
+                Some(path)
+            }
+            None => None,
+        };
+
+        let branding = Branding {
+            company_name: Some(info.company_name.trim().to_string()).filter(|n| !n.is_empty()),
+            primary_color: info.primary_color.clone(),
+            accent_color: info.accent_color.clone(),
+            logo_path,
+        };
+        let json = serde_json::to_vec_pretty(&branding).map_err(io::Error::other)?;
+        write_atomic(&self.dir.join(BRANDING_FILE), &json)?;
+        Ok(branding)
+    }
+
+    fn remove_logos(&self) -> io::Result<()> {
+        for entry in std::fs::read_dir(&self.dir)?.flatten() {
+            let path = entry.path();
+            if path.file_stem().and_then(|s| s.to_str()) == Some(LOGO_FILE_STEM) {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Downloads the branding from the backend and stores it in `store`.
+pub async fn sync(api: ApiClient, store: BrandingStore) -> Result<Branding, ApiError> {
+    let info = api.get_branding().await?;
+    let logo = if info.has_logo {
+        match api.download_logo().await {
+            Ok(bytes) => Some(bytes),
+            Err(ApiError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+
+    let branding = store
+        .save(&info, logo.as_deref())
+        .map_err(|e| ApiError::ServerError(format!("Failed to cache branding: {}", e)))?;
+    info!(
+        "Branding synced (logo: {})",
+        branding
+            .logo_path
+            .as_deref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "none".to_string())
+    );
+    Ok(branding)
+}
+
+/// File extension for the logo formats the backend accepts (PNG, JPEG, SVG, WebP).
+pub fn logo_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else if is_svg(bytes) {
+        Some("svg")
+    } else {
+        None
+    }
+}
+
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]);
+    let head = head.trim_start();
+    head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("part");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_store(test_name: &str) -> BrandingStore {
+        let dir =
+            std::env::temp_dir().join(format!("zt-branding-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        BrandingStore::new(&dir.to_string_lossy())
+    }
+
+    fn info(company_name: &str) -> BrandingInfo {
+        BrandingInfo {
+            company_name: company_name.to_string(),
+            has_logo: true,
+            primary_color: Some("#1F4E9A".to_string()),
+            accent_color: None,
+        }
+    }
+
+    #[test]
+    fn test_load_without_cache_returns_default() {
+        let store = make_store("empty");
+        assert_eq!(store.load(), Branding::default());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let store = make_store("roundtrip");
+        let saved = store
+            .save(&info("Firma GmbH"), Some(b"\x89PNG\r\n\x1a\n"))
+            .unwrap();
+        assert!(saved
+            .logo_path
+            .as_deref()
+            .is_some_and(|p| p.ends_with("company-logo.png")));
+        assert_eq!(store.load(), saved);
+
+        // A new logo format replaces the old file; an empty name is not an override.
+        let saved = store
+            .save(
+                &info(" "),
+                Some(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+            )
+            .unwrap();
+        assert!(saved.company_name.is_none());
+        assert!(saved
+            .logo_path
+            .as_deref()
+            .is_some_and(|p| p.ends_with("company-logo.svg")));
+        assert!(!store.dir.join("company-logo.png").exists());
+        let _ = std::fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_logo_extension() {
+        assert_eq!(logo_extension(b"\x89PNG\r\n\x1a\n"), Some("png"));
+        assert_eq!(logo_extension(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("jpg"));
+        assert_eq!(
+            logo_extension(b"<?xml version=\"1.0\"?>\n<svg></svg>"),
+            Some("svg")
+        );
+        assert_eq!(logo_extension(b"<html>"), None);
+    }
+}
@@ -2,20 +2,96 @@ use chrono::{DateTime, Utc};
 use log::{info, warn};
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::api::{Project, RosterEntry};
+
+/// What a buffered event asks the backend to do when it is replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    /// Badge scan that toggles clock-in/clock-out.
+    Clock,
+    /// Attribution of the employee's working time to another project.
+    ProjectSwitch,
+    /// Clock-in/clock-out entered with employee number and PIN.  Only the employee number
+    /// is buffered; the PIN is never written to disk.
+    PinClock,
+    /// Badge scan in two-factor mode whose PIN was checked against the cached roster.
+    VerifiedClock,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::Clock => "CLOCK",
+            EventKind::ProjectSwitch => "PROJECT_SWITCH",
+            EventKind::PinClock => "PIN_CLOCK",
+            EventKind::VerifiedClock => "VERIFIED_CLOCK",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "PROJECT_SWITCH" => EventKind::ProjectSwitch,
+            "PIN_CLOCK" => EventKind::PinClock,
+            "VERIFIED_CLOCK" => EventKind::VerifiedClock,
+            _ => EventKind::Clock,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BufferedEvent {
     pub id: Option<i64>,
+    pub kind: EventKind,
+    /// Empty for `EventKind::PinClock`.
     pub rfid_tag_id: String,
     pub terminal_id: String,
+    /// Only set for `EventKind::ProjectSwitch`.
+    pub project_id: Option<String>,
+    /// Only set for `EventKind::PinClock`.
+    pub employee_number: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub synced: bool,
 }
 
+/// Result of taking back a buffered event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    Removed,
+    /// Already synced, or never buffered here.
+    AlreadySynced,
+    /// A sync holds the lock; the event may still be removable once it is done.
+    Busy,
+}
+
+/// Counts shown on the maintenance screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BufferStats {
+    pub pending: u32,
+    pub synced: u32,
+    /// Timestamp of the oldest event still waiting for the backend.
+    pub oldest_pending: Option<DateTime<Utc>>,
+}
+
+/// How long a write waits for another process (the terminal or a `buffer` command) to
+/// finish its transaction before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Held while buffered events are replayed, so the terminal and `buffer resync` never send
+/// the same event twice.  Released when dropped.
+pub struct SyncLock {
+    _file: Option<File>,
+}
+
 pub struct EventBuffer {
     conn: Connection,
     max_size: u32,
+    /// Database file; `None` for an in-memory buffer.
+    path: Option<PathBuf>,
 }
 
 impl EventBuffer {
@@ -26,11 +102,36 @@ impl EventBuffer {
         }
 
         let conn = Connection::open(db_path)?;
-        let buffer = Self { conn, max_size };
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        let buffer = Self {
+            conn,
+            max_size,
+            path: (db_path != ":memory:").then(|| path.to_path_buf()),
+        };
         buffer.initialize()?;
         Ok(buffer)
     }
 
+    /// Takes the sync lock (`<buffer_path>.sync.lock`), or returns `None` if another sync
+    /// holds it, in this or another process.
+    pub fn try_sync_lock(&self) -> io::Result<Option<SyncLock>> {
+        let Some(path) = &self.path else {
+            return Ok(Some(SyncLock { _file: None }));
+        };
+        let mut lock_path = path.clone().into_os_string();
+        lock_path.push(".sync.lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(SyncLock { _file: Some(file) })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    }
+
     fn initialize(&self) -> SqliteResult<()> {
         self.conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS buffered_events (
@@ -41,13 +142,122 @@ impl EventBuffer {
                 synced INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
-            CREATE INDEX IF NOT EXISTS idx_buffered_events_synced ON buffered_events(synced);",
+            CREATE INDEX IF NOT EXISTS idx_buffered_events_synced ON buffered_events(synced);
+            CREATE TABLE IF NOT EXISTS cached_projects (
+                id TEXT PRIMARY KEY,
+                code TEXT NOT NULL,
+                name TEXT NOT NULL,
+                cost_center TEXT,
+                position INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS project_usage (
+                rfid_tag_id TEXT NOT NULL,
+                project_id TEXT NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 0,
+                last_used TEXT NOT NULL,
+                PRIMARY KEY (rfid_tag_id, project_id)
+            );
+            CREATE TABLE IF NOT EXISTS cached_roster (
+                rfid_tag_id TEXT PRIMARY KEY,
+                pin_hash TEXT,
+                pin_salt TEXT,
+                pin_exempt INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS cached_blocklist (
+                rfid_tag_id TEXT PRIMARY KEY
+            );",
         )?;
+        self.migrate()?;
         info!("Event buffer database initialized");
         Ok(())
     }
 
+    /// Adds columns introduced after the first release to databases created by older versions.
+    fn migrate(&self) -> SqliteResult<()> {
+        let mut stmt = self.conn.prepare("PRAGMA table_info(buffered_events)")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        if !columns.iter().any(|c| c == "kind") {
+            self.conn.execute_batch(
+                "ALTER TABLE buffered_events ADD COLUMN kind TEXT NOT NULL DEFAULT 'CLOCK';",
+            )?;
+        }
+        if !columns.iter().any(|c| c == "project_id") {
+            self.conn
+                .execute_batch("ALTER TABLE buffered_events ADD COLUMN project_id TEXT;")?;
+        }
+        if !columns.iter().any(|c| c == "employee_number") {
+            self.conn
+                .execute_batch("ALTER TABLE buffered_events ADD COLUMN employee_number TEXT;")?;
+        }
+        Ok(())
+    }
+
     pub fn push(&self, rfid_tag_id: &str, terminal_id: &str) -> SqliteResult<i64> {
+        self.insert(
+            EventKind::Clock,
+            rfid_tag_id,
+            terminal_id,
+            None,
+            None,
+            Utc::now(),
+        )
+    }
+
+    /// Buffers a two-factor scan after the PIN was verified offline.
+    pub fn push_verified(&self, rfid_tag_id: &str, terminal_id: &str) -> SqliteResult<i64> {
+        self.insert(
+            EventKind::VerifiedClock,
+            rfid_tag_id,
+            terminal_id,
+            None,
+            None,
+            Utc::now(),
+        )
+    }
+
+    /// Buffers a PIN clocking.  Takes no PIN on purpose: it must never be stored.
+    pub fn push_pin_clock(&self, employee_number: &str, terminal_id: &str) -> SqliteResult<i64> {
+        self.insert(
+            EventKind::PinClock,
+            "",
+            terminal_id,
+            None,
+            Some(employee_number),
+            Utc::now(),
+        )
+    }
+
+    /// Buffers a project switch so it is replayed in order with the clock events.
+    /// `timestamp` is when the project was chosen, not when the request gave up.
+    pub fn push_project_switch(
+        &self,
+        rfid_tag_id: &str,
+        terminal_id: &str,
+        project_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> SqliteResult<i64> {
+        self.insert(
+            EventKind::ProjectSwitch,
+            rfid_tag_id,
+            terminal_id,
+            Some(project_id),
+            None,
+            timestamp,
+        )
+    }
+
+    fn insert(
+        &self,
+        kind: EventKind,
+        rfid_tag_id: &str,
+        terminal_id: &str,
+        project_id: Option<&str>,
+        employee_number: Option<&str>,
+        timestamp: DateTime<Utc>,
+    ) -> SqliteResult<i64> {
         let count: u32 = self.conn.query_row(
             "SELECT COUNT(*) FROM buffered_events WHERE synced = 0",
             [],
@@ -65,33 +275,47 @@ impl EventBuffer {
             )?;
         }
 
-        let now = Utc::now().to_rfc3339();
         self.conn.execute(
-            "INSERT INTO buffered_events (rfid_tag_id, terminal_id, timestamp) VALUES (?1, ?2, ?3)",
-            params![rfid_tag_id, terminal_id, now],
+            "INSERT INTO buffered_events (kind, rfid_tag_id, terminal_id, project_id, employee_number, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                kind.as_str(),
+                rfid_tag_id,
+                terminal_id,
+                project_id,
+                employee_number,
+                timestamp.to_rfc3339()
+            ],
         )?;
 
         Ok(self.conn.last_insert_rowid())
     }
 
     pub fn get_pending(&self) -> SqliteResult<Vec<BufferedEvent>> {
+        self.events(false)
+    }
+
+    /// Buffered events in replay order; synced ones only with `include_synced`.
+    pub fn events(&self, include_synced: bool) -> SqliteResult<Vec<BufferedEvent>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, rfid_tag_id, terminal_id, timestamp, synced FROM buffered_events WHERE synced = 0 ORDER BY id ASC",
+            "SELECT id, kind, rfid_tag_id, terminal_id, project_id, employee_number, timestamp, synced FROM buffered_events WHERE synced = 0 OR ?1 ORDER BY id ASC",
         )?;
 
         let events = stmt
-            .query_map([], |row| {
-                let ts_str: String = row.get(3)?;
+            .query_map(params![include_synced], |row| {
+                let ts_str: String = row.get(6)?;
                 let timestamp = DateTime::parse_from_rfc3339(&ts_str)
                     .map(|dt| dt.with_timezone(&Utc))
                     .unwrap_or_else(|_| Utc::now());
 
                 Ok(BufferedEvent {
                     id: Some(row.get(0)?),
-                    rfid_tag_id: row.get(1)?,
-                    terminal_id: row.get(2)?,
+                    kind: EventKind::parse(&row.get::<_, String>(1)?),
+                    rfid_tag_id: row.get(2)?,
+                    terminal_id: row.get(3)?,
+                    project_id: row.get(4)?,
+                    employee_number: row.get(5)?,
                     timestamp,
-                    synced: row.get::<_, i32>(4)? != 0,
+                    synced: row.get::<_, i32>(7)? != 0,
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
@@ -107,6 +331,23 @@ impl EventBuffer {
         Ok(())
     }
 
+    /// Deletes a pending event, e.g. a clocking taken back on the confirmation screen.
+    /// `Removal::Busy` while a sync holds the lock.
+    pub fn remove_pending(&self, id: i64) -> Result<Removal, Box<dyn std::error::Error>> {
+        let Some(_lock) = self.try_sync_lock()? else {
+            return Ok(Removal::Busy);
+        };
+        let deleted = self.conn.execute(
+            "DELETE FROM buffered_events WHERE id = ?1 AND synced = 0",
+            params![id],
+        )?;
+        Ok(if deleted > 0 {
+            Removal::Removed
+        } else {
+            Removal::AlreadySynced
+        })
+    }
+
     pub fn pending_count(&self) -> SqliteResult<u32> {
         self.conn.query_row(
             "SELECT COUNT(*) FROM buffered_events WHERE synced = 0",
@@ -114,6 +355,208 @@ impl EventBuffer {
             |row| row.get(0),
         )
     }
+
+    pub fn stats(&self) -> SqliteResult<BufferStats> {
+        let (pending, synced, oldest): (u32, u32, Option<String>) = self.conn.query_row(
+            "SELECT COALESCE(SUM(synced = 0), 0), COALESCE(SUM(synced = 1), 0),
+                    (SELECT timestamp FROM buffered_events WHERE synced = 0 ORDER BY id LIMIT 1)
+             FROM buffered_events",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(BufferStats {
+            pending,
+            synced,
+            oldest_pending: oldest
+                .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+        })
+    }
+
+    /// Adds exported events as pending, keeping their original timestamps.  Synced events
+    /// and events already in the buffer are skipped.  Returns the number imported.
+    pub fn import(&mut self, events: &[BufferedEvent]) -> Result<u32, Box<dyn std::error::Error>> {
+        let tx = self.conn.transaction()?;
+        let mut imported = 0u32;
+        for event in events.iter().filter(|e| !e.synced) {
+            let timestamp = event.timestamp.to_rfc3339();
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM buffered_events WHERE kind = ?1 AND rfid_tag_id = ?2 AND terminal_id = ?3
+                 AND IFNULL(project_id, '') = IFNULL(?4, '') AND IFNULL(employee_number, '') = IFNULL(?5, '') AND timestamp = ?6)",
+                params![
+                    event.kind.as_str(),
+                    event.rfid_tag_id,
+                    event.terminal_id,
+                    event.project_id,
+                    event.employee_number,
+                    timestamp
+                ],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
+            tx.execute(
+                "INSERT INTO buffered_events (kind, rfid_tag_id, terminal_id, project_id, employee_number, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    event.kind.as_str(),
+                    event.rfid_tag_id,
+                    event.terminal_id,
+                    event.project_id,
+                    event.employee_number,
+                    timestamp
+                ],
+            )?;
+            imported += 1;
+        }
+        // Unlike a live scan, an import never silently drops the oldest events.
+        let pending: u32 = tx.query_row(
+            "SELECT COUNT(*) FROM buffered_events WHERE synced = 0",
+            [],
+            |row| row.get(0),
+        )?;
+        if pending > self.max_size {
+            return Err(format!(
+                "import would grow the buffer to {} pending events, more than max_buffer_size {}",
+                pending, self.max_size
+            )
+            .into());
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+
+    /// Deletes synced events older than `cutoff`.  Returns the number deleted.
+    pub fn purge_synced_before(&self, cutoff: DateTime<Utc>) -> SqliteResult<usize> {
+        self.conn.execute(
+            "DELETE FROM buffered_events WHERE synced = 1 AND timestamp < ?1",
+            params![cutoff.to_rfc3339()],
+        )
+    }
+
+    // ─── Project cache ──────────────────────────────────────────────────────
+
+    /// Replaces the cached project list with the latest one from the backend.
+    pub fn replace_cached_projects(&mut self, projects: &[Project]) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM cached_projects", [])?;
+        for (position, project) in projects.iter().enumerate() {
+            tx.execute(
+                "INSERT OR REPLACE INTO cached_projects (id, code, name, cost_center, position) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    project.id,
+                    project.code,
+                    project.name,
+                    project.cost_center,
+                    position as i64
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Returns the cached project list in backend order, for use while offline.
+    pub fn cached_projects(&self) -> SqliteResult<Vec<Project>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, code, name, cost_center FROM cached_projects ORDER BY position ASC",
+        )?;
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    code: row.get(1)?,
+                    name: row.get(2)?,
+                    cost_center: row.get(3)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(projects)
+    }
+
+    // ─── Two-factor roster ──────────────────────────────────────────────────
+
+    /// Replaces the cached roster with the latest one from the backend.
+    pub fn replace_cached_roster(&mut self, roster: &[RosterEntry]) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM cached_roster", [])?;
+        for entry in roster {
+            tx.execute(
+                "INSERT OR REPLACE INTO cached_roster (rfid_tag_id, pin_hash, pin_salt, pin_exempt) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    entry.rfid_tag_id,
+                    entry.pin_hash,
+                    entry.pin_salt,
+                    entry.pin_exempt as i32
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Cached roster entry for a badge, if the backend listed it.
+    pub fn roster_entry(&self, rfid_tag_id: &str) -> SqliteResult<Option<RosterEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rfid_tag_id, pin_hash, pin_salt, pin_exempt FROM cached_roster WHERE rfid_tag_id = ?1",
+        )?;
+        let mut rows = stmt.query_map(params![rfid_tag_id], |row| {
+            Ok(RosterEntry {
+                rfid_tag_id: row.get(0)?,
+                pin_hash: row.get(1)?,
+                pin_salt: row.get(2)?,
+                pin_exempt: row.get::<_, i32>(3)? != 0,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    // ─── Blocklist ──────────────────────────────────────────────────────────
+
+    /// Replaces the cached blocklist with the latest one from the backend.
+    pub fn replace_cached_blocklist(&mut self, rfid_tag_ids: &[String]) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM cached_blocklist", [])?;
+        for rfid_tag_id in rfid_tag_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO cached_blocklist (rfid_tag_id) VALUES (?1)",
+                params![rfid_tag_id],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// The revoked badges as last received from the backend.
+    pub fn cached_blocklist(&self) -> SqliteResult<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT rfid_tag_id FROM cached_blocklist ORDER BY rfid_tag_id")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Remembers that `rfid_tag_id` booked on `project_id`, so it is offered as a favourite.
+    pub fn record_project_usage(&self, rfid_tag_id: &str, project_id: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO project_usage (rfid_tag_id, project_id, use_count, last_used) VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT(rfid_tag_id, project_id) DO UPDATE SET use_count = use_count + 1, last_used = excluded.last_used",
+            params![rfid_tag_id, project_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the IDs of the projects most recently used by `rfid_tag_id`, newest first.
+    pub fn favourite_project_ids(
+        &self,
+        rfid_tag_id: &str,
+        limit: u32,
+    ) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project_id FROM project_usage WHERE rfid_tag_id = ?1 ORDER BY last_used DESC, use_count DESC LIMIT ?2",
+        )?;
+        let ids = stmt
+            .query_map(params![rfid_tag_id, limit], |row| row.get(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(ids)
+    }
 }
 
 #[cfg(test)]
@@ -160,6 +603,37 @@ mod tests {
         assert!(pending.is_empty());
     }
 
+    #[test]
+    fn test_remove_pending_skips_synced_events() {
+        let buf = make_buffer();
+        let first = buf.push("TAG001", "terminal-1").unwrap();
+        let second = buf.push("TAG002", "terminal-1").unwrap();
+        buf.mark_synced(first).unwrap();
+
+        assert_eq!(buf.remove_pending(first).unwrap(), Removal::AlreadySynced);
+        assert_eq!(buf.remove_pending(second).unwrap(), Removal::Removed);
+        assert_eq!(buf.remove_pending(second).unwrap(), Removal::AlreadySynced);
+        assert_eq!(buf.pending_count().unwrap(), 0);
+        assert_eq!(buf.events(true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stats() {
+        let buf = make_buffer();
+        assert_eq!(buf.stats().unwrap(), BufferStats::default());
+
+        let first = buf.push("TAG001", "terminal-1").unwrap();
+        buf.push("TAG002", "terminal-1").unwrap();
+        buf.mark_synced(first).unwrap();
+
+        let stats = buf.stats().unwrap();
+        assert_eq!((stats.pending, stats.synced), (1, 1));
+        assert_eq!(
+            stats.oldest_pending,
+            Some(buf.get_pending().unwrap()[0].timestamp)
+        );
+    }
+
     #[test]
     fn test_max_size_enforcement() {
         let buf = EventBuffer::new(":memory:", 3).expect("failed to create buffer");
@@ -188,4 +662,198 @@ mod tests {
         let id2 = buf.push("TAG002", "terminal-1").unwrap();
         assert!(id2 > id1);
     }
+
+    #[test]
+    fn test_project_switch_is_buffered_in_order() {
+        let buf = make_buffer();
+        buf.push("TAG001", "terminal-1").unwrap();
+        let chosen = Utc::now() - chrono::Duration::seconds(40);
+        buf.push_project_switch("TAG001", "terminal-1", "p-1", chosen)
+            .unwrap();
+
+        let pending = buf.get_pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].kind, EventKind::Clock);
+        assert!(pending[0].project_id.is_none());
+        assert_eq!(pending[1].kind, EventKind::ProjectSwitch);
+        assert_eq!(pending[1].project_id.as_deref(), Some("p-1"));
+        // Replayed with the time the project was chosen, not the time it was buffered.
+        assert_eq!(pending[1].timestamp, chosen);
+    }
+
+    #[test]
+    fn test_pin_clock_stores_employee_number_only() {
+        let buf = make_buffer();
+        buf.push_pin_clock("1042", "terminal-1").unwrap();
+
+        let pending = buf.get_pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].kind, EventKind::PinClock);
+        assert_eq!(pending[0].employee_number.as_deref(), Some("1042"));
+        assert!(pending[0].rfid_tag_id.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_schema_is_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE buffered_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                rfid_tag_id TEXT NOT NULL,
+                terminal_id TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                synced INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO buffered_events (rfid_tag_id, terminal_id, timestamp)
+                VALUES ('TAG001', 'terminal-1', '2024-01-15T08:00:00+00:00');",
+        )
+        .unwrap();
+        let buf = EventBuffer {
+            conn,
+            max_size: 10,
+            path: None,
+        };
+        buf.initialize().unwrap();
+
+        let pending = buf.get_pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].kind, EventKind::Clock);
+        assert!(pending[0].project_id.is_none());
+    }
+
+    #[test]
+    fn test_cached_projects_roundtrip() {
+        let mut buf = make_buffer();
+        let projects = vec![
+            Project {
+                id: "p-1".to_string(),
+                code: "WS".to_string(),
+                name: "Werkstatt".to_string(),
+                cost_center: Some("4711".to_string()),
+            },
+            Project {
+                id: "p-2".to_string(),
+                code: "MNT".to_string(),
+                name: "Montage".to_string(),
+                cost_center: None,
+            },
+        ];
+        buf.replace_cached_projects(&projects).unwrap();
+        assert_eq!(buf.cached_projects().unwrap(), projects);
+
+        buf.replace_cached_projects(&projects[1..]).unwrap();
+        assert_eq!(buf.cached_projects().unwrap(), projects[1..].to_vec());
+    }
+
+    #[test]
+    fn test_cached_roster_roundtrip() {
+        let mut buf = make_buffer();
+        let roster = vec![
+            RosterEntry {
+                rfid_tag_id: "TAG1".to_string(),
+                pin_hash: Some("ab12".to_string()),
+                pin_salt: Some("s1".to_string()),
+                pin_exempt: false,
+            },
+            RosterEntry {
+                rfid_tag_id: "TAG2".to_string(),
+                pin_hash: None,
+                pin_salt: None,
+                pin_exempt: true,
+            },
+        ];
+        buf.replace_cached_roster(&roster).unwrap();
+        assert_eq!(buf.roster_entry("TAG1").unwrap().as_ref(), Some(&roster[0]));
+        assert!(buf.roster_entry("TAG2").unwrap().unwrap().pin_exempt);
+
+        buf.replace_cached_roster(&roster[1..]).unwrap();
+        assert!(buf.roster_entry("TAG1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cached_blocklist_is_replaced() {
+        let mut buf = make_buffer();
+        assert!(buf.cached_blocklist().unwrap().is_empty());
+
+        buf.replace_cached_blocklist(&["TAG2".to_string(), "TAG1".to_string()])
+            .unwrap();
+        assert_eq!(buf.cached_blocklist().unwrap(), vec!["TAG1", "TAG2"]);
+
+        buf.replace_cached_blocklist(&["TAG3".to_string()]).unwrap();
+        assert_eq!(buf.cached_blocklist().unwrap(), vec!["TAG3"]);
+    }
+
+    #[test]
+    fn test_favourite_projects_are_per_badge() {
+        let buf = make_buffer();
+        buf.record_project_usage("TAG001", "p-1").unwrap();
+        buf.record_project_usage("TAG001", "p-2").unwrap();
+        buf.record_project_usage("TAG002", "p-3").unwrap();
+
+        let favourites = buf.favourite_project_ids("TAG001", 5).unwrap();
+        assert_eq!(favourites.len(), 2);
+        assert!(!favourites.contains(&"p-3".to_string()));
+        assert_eq!(buf.favourite_project_ids("TAG001", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_keeps_timestamps_and_skips_duplicates() {
+        let source = make_buffer();
+        source.push("TAG001", "terminal-1").unwrap();
+        source.push_pin_clock("1042", "terminal-1").unwrap();
+        let synced = source.push("TAG002", "terminal-1").unwrap();
+        source.mark_synced(synced).unwrap();
+        let exported = source.events(true).unwrap();
+        assert_eq!(exported.len(), 3);
+
+        let mut target = make_buffer();
+        assert_eq!(target.import(&exported).unwrap(), 2);
+        let pending = target.get_pending().unwrap();
+        assert_eq!(pending[0].timestamp, exported[0].timestamp);
+        assert_eq!(pending[1].employee_number.as_deref(), Some("1042"));
+        // Importing the same export twice must not clock anyone twice.
+        assert_eq!(target.import(&exported).unwrap(), 0);
+        assert_eq!(target.pending_count().unwrap(), 2);
+
+        let mut small = EventBuffer::new(":memory:", 1).unwrap();
+        assert!(small.import(&exported).is_err());
+        assert_eq!(small.pending_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_purge_deletes_only_old_synced_events() {
+        let buf = make_buffer();
+        let old = buf.push("TAG001", "terminal-1").unwrap();
+        buf.push("TAG002", "terminal-1").unwrap();
+        buf.mark_synced(old).unwrap();
+
+        let past = Utc::now() - chrono::Duration::days(1);
+        assert_eq!(buf.purge_synced_before(past).unwrap(), 0);
+        let future = Utc::now() + chrono::Duration::days(1);
+        assert_eq!(buf.purge_synced_before(future).unwrap(), 1);
+        assert_eq!(buf.events(true).unwrap().len(), 1);
+        assert_eq!(buf.pending_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_sync_lock_is_exclusive_across_connections() {
+        let dir = std::env::temp_dir().join(format!("zt-buffer-lock-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("buffer.db").to_string_lossy().into_owned();
+        let terminal = EventBuffer::new(&path, 10).unwrap();
+        let cli = EventBuffer::new(&path, 10).unwrap();
+
+        let lock = terminal.try_sync_lock().unwrap();
+        assert!(lock.is_some());
+        assert!(cli.try_sync_lock().unwrap().is_none());
+        // Events cannot be taken back while an upload runs, but afterwards if it skipped them.
+        let id = cli.push("TAG001", "terminal-1").unwrap();
+        assert_eq!(cli.remove_pending(id).unwrap(), Removal::Busy);
+        drop(lock);
+        assert!(cli.try_sync_lock().unwrap().is_some());
+        assert_eq!(cli.remove_pending(id).unwrap(), Removal::Removed);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,280 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use crate::audio;
+use crate::config::AppConfig;
+
+/// Terminal IDs that are clearly not set per device.
+const PLACEHOLDER_TERMINAL_IDS: &[&str] = &["terminal", "default", "changeme", "test"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The terminal refuses to start.
+    Error,
+    /// The terminal runs, but not as configured (e.g. a beep instead of a missing clip).
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// Every problem with `config`: invalid values, missing files, unwritable directories and
+/// default values left in place.  Defaults are only warnings with `allow_defaults`, for
+/// development setups.
+pub fn check(config: &AppConfig, allow_defaults: bool) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = config
+        .value_errors()
+        .into_iter()
+        .map(Problem::error)
+        .collect();
+
+    for message in default_identity(config) {
+        problems.push(if allow_defaults {
+            Problem::warning(message)
+        } else {
+            Problem::error(format!("{} (use --allow-defaults to run anyway)", message))
+        });
+    }
+
+    if config.audio.enabled {
+        for path in audio::missing_files(&config.audio) {
+            problems.push(Problem::warning(format!(
+                "sound file {} not found, a beep is played instead",
+                path
+            )));
+        }
+    }
+    if !Path::new(&config.company.logo_path).exists() {
+        problems.push(Problem::warning(format!(
+            "company.logo_path {} not found",
+            config.company.logo_path
+        )));
+    }
+    if config.qr.enabled && config.qr.secret.is_empty() {
+        problems.push(Problem::warning(
+            "qr.enabled is set but qr.secret is empty, no code is shown",
+        ));
+    }
+
+    if let Err(e) = writable_file(Path::new(&config.offline.buffer_path)) {
+        problems.push(Problem::error(format!(
+            "offline.buffer_path {} is not writable: {}",
+            config.offline.buffer_path, e
+        )));
+    }
+    let caches = [
+        (
+            config.company.sync_branding,
+            "company.branding_cache_dir",
+            &config.company.branding_cache_dir,
+        ),
+        (
+            config.photos.enabled,
+            "photos.cache_dir",
+            &config.photos.cache_dir,
+        ),
+    ];
+    for (_, key, dir) in caches.iter().filter(|(enabled, _, _)| *enabled) {
+        if let Err(e) = writable_dir(Path::new(dir)) {
+            problems.push(Problem::warning(format!(
+                "{} {} is not writable: {}",
+                key, dir, e
+            )));
+        }
+    }
+    if config.remote.enabled {
+        if let Err(e) = writable_file(Path::new(&config.remote.cache_path)) {
+            problems.push(Problem::warning(format!(
+                "remote.cache_path {} is not writable: {}",
+                config.remote.cache_path, e
+            )));
+        }
+    }
+
+    problems
+}
+
+/// Values that would make every misconfigured terminal book under the same identity.
+fn default_identity(config: &AppConfig) -> Vec<String> {
+    let defaults = AppConfig::default();
+    let mut found = Vec::new();
+    let id = config.api.terminal_id.trim();
+    if id == defaults.api.terminal_id
+        || PLACEHOLDER_TERMINAL_IDS
+            .iter()
+            .any(|p| id.eq_ignore_ascii_case(p))
+    {
+        found.push(format!(
+            "api.terminal_id \"{}\" is a default, set a unique ID per terminal",
+            id
+        ));
+    } else if id.chars().any(char::is_whitespace) {
+        found.push(format!(
+            "api.terminal_id \"{}\" must not contain spaces",
+            id
+        ));
+    }
+    if config.api.base_url == defaults.api.base_url {
+        found.push(format!(
+            "api.base_url is the default {}",
+            defaults.api.base_url
+        ));
+    }
+    found
+}
+
+/// A file can be opened for writing, or created in its (possibly not yet existing) directory.
+fn writable_file(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+    }
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => writable_dir(parent),
+        _ => writable_dir(Path::new(".")),
+    }
+}
+
+/// Files can be created in `dir`, or in the nearest existing ancestor if it does not exist
+/// yet (the terminal creates missing directories).
+fn writable_dir(dir: &Path) -> Result<(), String> {
+    let existing = dir
+        .ancestors()
+        .find(|a| a.exists())
+        .ok_or_else(|| "no existing parent directory".to_string())?;
+    if !existing.is_dir() {
+        return Err(format!("{} is not a directory", existing.display()));
+    }
+    let probe = existing.join(format!(".zt-write-test-{}", std::process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(problems: &[Problem]) -> Vec<&str> {
+        problems
+            .iter()
+            .filter(|p| p.is_error())
+            .map(|p| p.message.as_str())
+            .collect()
+    }
+
+    fn configured(dir: &Path) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.api.terminal_id = "lager-eingang".to_string();
+        config.api.base_url = "https://zeit.example.com/api".to_string();
+        config.offline.buffer_path = dir.join("buffer.db").to_string_lossy().into_owned();
+        config.company.branding_cache_dir = dir.join("branding").to_string_lossy().into_owned();
+        config
+    }
+
+    #[test]
+    fn test_defaults_are_refused_unless_allowed() {
+        let dir = std::env::temp_dir().join(format!("zt-check-defaults-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = configured(&dir);
+        config.api.terminal_id = AppConfig::default().api.terminal_id;
+        config.api.base_url = AppConfig::default().api.base_url;
+
+        let strict = check(&config, false);
+        let found = errors(&strict);
+        assert_eq!(found.len(), 2);
+        assert!(found[0].starts_with("api.terminal_id"));
+        assert!(found[1].starts_with("api.base_url"));
+
+        let relaxed = check(&config, true);
+        assert!(relaxed.iter().all(|p| !p.is_error()));
+
+        config.api.terminal_id = "Test".to_string();
+        assert!(!errors(&check(&config, false)).is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_all_problems_are_reported() {
+        let dir = std::env::temp_dir().join(format!("zt-check-all-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = configured(&dir);
+        assert!(errors(&check(&config, false)).is_empty());
+
+        let blocker = dir.join("not-a-dir");
+        std::fs::write(&blocker, b"").unwrap();
+        let mut broken = config.clone();
+        broken.api.base_url = "zeit.example.com".to_string();
+        broken.audio.volume = 1.5;
+        broken.display.resolution = "gross".to_string();
+        broken.offline.buffer_path = blocker.join("buffer.db").to_string_lossy().into_owned();
+        broken.company.logo_path = dir.join("missing.png").to_string_lossy().into_owned();
+        broken.audio.success_sound = dir.join("missing.wav").to_string_lossy().into_owned();
+
+        let problems = check(&broken, false);
+        let errors = errors(&problems);
+        assert_eq!(errors.len(), 4, "{:?}", problems);
+        assert!(errors[3].starts_with("offline.buffer_path"));
+        let warnings: Vec<_> = problems.iter().filter(|p| !p.is_error()).collect();
+        assert!(warnings.iter().any(|p| p.message.contains("missing.png")));
+        assert!(warnings.iter().any(|p| p.message.contains("missing.wav")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_missing_directories_are_writable_if_they_can_be_created() {
+        let dir = std::env::temp_dir().join(format!("zt-check-dirs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(writable_file(&dir.join("a/b/buffer.db")).is_ok());
+        assert!(writable_dir(&dir.join("cache")).is_ok());
+        // Nothing is left behind.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
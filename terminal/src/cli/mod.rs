@@ -0,0 +1,364 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::api::ApiClient;
+use crate::buffer::{BufferedEvent, EventBuffer, EventKind};
+use crate::config::AppConfig;
+use crate::sync;
+
+pub const BUFFER_USAGE: &str = "\
+Usage: zeiterfassung-terminal buffer <COMMAND>
+
+Commands:
+  list [--all]                          List pending events (--all: synced ones too)
+  stats                                 Show pending and synced counts
+  export [--format csv|json] [--all]    Write pending events to stdout (default: json)
+  import <FILE>                         Add pending events from a JSON export
+  resync                                Replay pending events to the backend now
+  purge --synced-before <DATE>          Delete synced events older than DATE
+                                        (YYYY-MM-DD in the terminal's time zone, or RFC 3339)
+
+The buffer is opened at offline.buffer_path and may be used while the terminal runs.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferCommand {
+    List { all: bool },
+    Stats,
+    Export { format: ExportFormat, all: bool },
+    Import { path: PathBuf },
+    Resync,
+    Purge { synced_before: String },
+}
+
+impl BufferCommand {
+    /// Parses the arguments after `buffer`.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (command, rest) = args
+            .split_first()
+            .ok_or_else(|| "missing buffer command".to_string())?;
+        let mut rest = rest.iter().map(String::as_str);
+        let mut all = false;
+        let parsed = match command.as_str() {
+            "list" | "export" => {
+                let mut format = ExportFormat::Json;
+                while let Some(arg) = rest.next() {
+                    match arg {
+                        "--all" => all = true,
+                        "--format" if command == "export" => {
+                            format = match rest.next() {
+                                Some("csv") => ExportFormat::Csv,
+                                Some("json") => ExportFormat::Json,
+                                other => {
+                                    return Err(format!(
+                                        "--format must be csv or json, not {}",
+                                        other.unwrap_or("nothing")
+                                    ))
+                                }
+                            }
+                        }
+                        other => return Err(format!("unknown argument: {}", other)),
+                    }
+                }
+                if command == "list" {
+                    BufferCommand::List { all }
+                } else {
+                    BufferCommand::Export { format, all }
+                }
+            }
+            "stats" => BufferCommand::Stats,
+            "resync" => BufferCommand::Resync,
+            "import" => BufferCommand::Import {
+                path: rest
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| "import needs a file".to_string())?,
+            },
+            "purge" => match (rest.next(), rest.next()) {
+                (Some("--synced-before"), Some(date)) => BufferCommand::Purge {
+                    synced_before: date.to_string(),
+                },
+                _ => return Err("purge needs --synced-before <DATE>".to_string()),
+            },
+            other => return Err(format!("unknown buffer command: {}", other)),
+        };
+        match rest.next() {
+            Some(extra) => Err(format!("unknown argument: {}", extra)),
+            None => Ok(parsed),
+        }
+    }
+}
+
+/// Runs `command` against the buffer at `offline.buffer_path`.  Returns the exit code.
+pub fn run(command: BufferCommand, config: &AppConfig) -> i32 {
+    match execute(command, config, &mut io::stdout().lock()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn execute(
+    command: BufferCommand,
+    config: &AppConfig,
+    out: &mut dyn Write,
+) -> Result<i32, Box<dyn Error>> {
+    let mut buffer = EventBuffer::new(&config.offline.buffer_path, config.offline.max_buffer_size)?;
+    match command {
+        BufferCommand::List { all } => {
+            for event in buffer.events(all)? {
+                writeln!(out, "{}", list_line(&event))?;
+            }
+        }
+        BufferCommand::Stats => {
+            let stats = buffer.stats()?;
+            writeln!(out, "pending:        {}", stats.pending)?;
+            writeln!(out, "synced:         {}", stats.synced)?;
+            writeln!(
+                out,
+                "oldest pending: {}",
+                stats
+                    .oldest_pending
+                    .map(|ts| ts.to_rfc3339())
+                    .unwrap_or_else(|| "-".to_string())
+            )?;
+        }
+        BufferCommand::Export { format, all } => {
+            let events = buffer.events(all)?;
+            match format {
+                ExportFormat::Json => {
+                    serde_json::to_writer_pretty(&mut *out, &events)?;
+                    writeln!(out)?;
+                }
+                ExportFormat::Csv => write_csv(out, &events)?,
+            }
+        }
+        BufferCommand::Import { path } => {
+            let events: Vec<BufferedEvent> =
+                serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let imported = buffer.import(&events)?;
+            writeln!(out, "imported {} of {} events", imported, events.len())?;
+        }
+        BufferCommand::Resync => {
+            let buffer = Arc::new(Mutex::new(buffer));
+            let api = ApiClient::new(&config.api);
+            let runtime = tokio::runtime::Runtime::new()?;
+            let Some(synced) =
+                runtime.block_on(sync::sync_buffered_events(api, Arc::clone(&buffer)))
+            else {
+                writeln!(out, "another sync is running, try again later")?;
+                return Ok(1);
+            };
+            let pending = buffer
+                .lock()
+                .map_err(|_| "buffer lock poisoned")?
+                .pending_count()?;
+            writeln!(out, "synced {} events, {} still pending", synced, pending)?;
+            if pending > 0 {
+                return Ok(1);
+            }
+        }
+        BufferCommand::Purge { synced_before } => {
+            let timezone = config.locale.timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+            let cutoff = parse_cutoff(&synced_before, timezone)?;
+            let purged = buffer.purge_synced_before(cutoff)?;
+            writeln!(
+                out,
+                "purged {} synced events before {}",
+                purged,
+                cutoff.to_rfc3339()
+            )?;
+        }
+    }
+    Ok(0)
+}
+
+/// `YYYY-MM-DD` as midnight in the terminal's time zone, or an RFC 3339 timestamp.
+fn parse_cutoff(value: &str, timezone: Tz) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("not a date (YYYY-MM-DD) or RFC 3339 timestamp: {}", value))?;
+    timezone
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight exists"))
+        .earliest()
+        .map(|ts| ts.with_timezone(&Utc))
+        .ok_or_else(|| format!("midnight does not exist on {} in {}", value, timezone))
+}
+
+fn list_line(event: &BufferedEvent) -> String {
+    let subject = match event.kind {
+        EventKind::PinClock => format!(
+            "employee {}",
+            event.employee_number.as_deref().unwrap_or_default()
+        ),
+        EventKind::ProjectSwitch => format!(
+            "{} -> project {}",
+            event.rfid_tag_id,
+            event.project_id.as_deref().unwrap_or_default()
+        ),
+        _ => event.rfid_tag_id.clone(),
+    };
+    format!(
+        "{:>6}  {}  {:<15} {:<8} {}  ({})",
+        event.id.unwrap_or_default(),
+        event.timestamp.to_rfc3339(),
+        event.kind.as_str(),
+        if event.synced { "synced" } else { "pending" },
+        subject,
+        event.terminal_id
+    )
+}
+
+fn write_csv(out: &mut dyn Write, events: &[BufferedEvent]) -> io::Result<()> {
+    writeln!(
+        out,
+        "id,kind,rfid_tag_id,terminal_id,project_id,employee_number,timestamp,synced"
+    )?;
+    for event in events {
+        let fields = [
+            event.id.map(|id| id.to_string()).unwrap_or_default(),
+            event.kind.as_str().to_string(),
+            event.rfid_tag_id.clone(),
+            event.terminal_id.clone(),
+            event.project_id.clone().unwrap_or_default(),
+            event.employee_number.clone().unwrap_or_default(),
+            event.timestamp.to_rfc3339(),
+            event.synced.to_string(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn config(test_name: &str) -> (PathBuf, AppConfig) {
+        let dir = std::env::temp_dir().join(format!("zt-cli-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut config = AppConfig::default();
+        config.offline.buffer_path = dir.join("buffer.db").to_string_lossy().into_owned();
+        (dir, config)
+    }
+
+    fn output(command: &str, config: &AppConfig) -> (i32, String) {
+        let mut out = Vec::new();
+        let command = BufferCommand::parse(&args(command)).unwrap();
+        let code = execute(command, config, &mut out).unwrap();
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_parse_buffer_commands() {
+        assert_eq!(
+            BufferCommand::parse(&args("list")),
+            Ok(BufferCommand::List { all: false })
+        );
+        assert_eq!(
+            BufferCommand::parse(&args("export --format csv --all")),
+            Ok(BufferCommand::Export {
+                format: ExportFormat::Csv,
+                all: true
+            })
+        );
+        assert_eq!(
+            BufferCommand::parse(&args("purge --synced-before 2026-10-01")),
+            Ok(BufferCommand::Purge {
+                synced_before: "2026-10-01".to_string()
+            })
+        );
+        assert!(BufferCommand::parse(&args("export --format xml")).is_err());
+        assert!(BufferCommand::parse(&args("list --format csv")).is_err());
+        assert!(BufferCommand::parse(&args("purge")).is_err());
+        assert!(BufferCommand::parse(&args("stats now")).is_err());
+        assert!(BufferCommand::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cutoff_uses_terminal_time_zone() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            parse_cutoff("2026-10-01", berlin).unwrap().to_rfc3339(),
+            "2026-09-30T22:00:00+00:00"
+        );
+        assert_eq!(
+            parse_cutoff("2026-10-01T12:00:00+02:00", berlin)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-10-01T10:00:00+00:00"
+        );
+        assert!(parse_cutoff("gestern", berlin).is_err());
+    }
+
+    #[test]
+    fn test_csv_quotes_fields_when_needed() {
+        assert_eq!(csv_field("TAG001"), "TAG001");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_export_import_roundtrip_and_purge() {
+        let (dir, config) = config("roundtrip");
+        {
+            let buf = EventBuffer::new(&config.offline.buffer_path, 10).unwrap();
+            buf.push("TAG001", "terminal-1").unwrap();
+            let synced = buf.push("TAG002", "terminal-1").unwrap();
+            buf.mark_synced(synced).unwrap();
+            buf.push_pin_clock("1042", "terminal-1").unwrap();
+        }
+
+        let (_, listed) = output("list", &config);
+        assert_eq!(listed.lines().count(), 2);
+        assert!(listed.contains("employee 1042"));
+        assert_eq!(output("list --all", &config).1.lines().count(), 3);
+
+        let (_, csv) = output("export --format csv", &config);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.starts_with("id,kind,"));
+
+        let (_, json) = output("export --format json", &config);
+        let export = dir.join("export.json");
+        std::fs::write(&export, &json).unwrap();
+
+        let (other_dir, other) = self::config("roundtrip-target");
+        let (code, imported) = output(&format!("import {}", export.display()), &other);
+        assert_eq!(code, 0);
+        assert_eq!(imported.trim(), "imported 2 of 2 events");
+        assert!(output("stats", &other).1.contains("pending:        2"));
+
+        let (_, purged) = output("purge --synced-before 2999-01-01", &config);
+        assert!(purged.starts_with("purged 1 synced events"));
+        assert_eq!(output("list --all", &config).1.lines().count(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&other_dir);
+    }
+}
@@ -1,26 +1,98 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Terminal configuration.
+///
+/// Changes from the remote configuration, edits of the file, SIGHUP or "Konfiguration neu
+/// laden" apply live unless the key is listed in [`RESTART_REQUIRED`]; those only take
+/// effect after a restart.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Live: timeouts, theme, font scale.  Restart: resolution, fullscreen, orientation.
     pub display: DisplayConfig,
+    /// Live.  `terminal_id` cannot be changed remotely.
     pub api: ApiConfig,
+    /// Live: sync interval.  Restart: buffer path and size.
     pub offline: OfflineConfig,
+    /// Restart.
     pub rfid: RfidConfig,
+    /// Live.
     pub audio: AudioConfig,
+    /// Live: language (spoken sounds) and time zone (volume schedule).
     pub locale: LocaleConfig,
+    /// Live: name, logo path.  Restart: branding sync settings.
     pub company: CompanyConfig,
+    /// Live, except `enabled`.
+    pub projects: ProjectsConfig,
+    /// Live.
+    pub info: InfoConfig,
+    /// Restart.
+    pub photos: PhotosConfig,
+    /// Live, except `recent_scans`.
+    pub maintenance: MaintenanceConfig,
+    /// Live, except the wrong-PIN limits.
+    pub pin: PinConfig,
+    /// Live.
+    pub qr: QrConfig,
+    /// Only read from the local file, never from the remote document.
+    pub remote: RemoteConfig,
 }
 
+/// Keys (dotted paths; a section name covers the whole section) that are only read at startup.
+pub const RESTART_REQUIRED: &[&str] = &[
+    "display.resolution",
+    "display.fullscreen",
+    "display.orientation",
+    "offline.buffer_path",
+    "offline.max_buffer_size",
+    "rfid.input_device",
+    "company.sync_branding",
+    "company.branding_cache_dir",
+    "projects.enabled",
+    "photos",
+    "maintenance.recent_scans",
+    "pin.max_attempts",
+    "pin.lockout_seconds",
+];
+
+/// Keys whose values are never logged or printed.
+pub const SECRET_KEYS: &[&str] = &["qr.secret"];
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct DisplayConfig {
     pub resolution: String,
     pub fullscreen: bool,
+    /// `"landscape"` or `"portrait"`; the window is opened with the matching aspect.
     pub orientation: String,
+    /// `"dark"`, `"light"`, `"high-contrast"` or `"high-contrast-light"`.
     pub theme: String,
+    /// Multiplier on top of the automatic window-size scaling, clamped to 0.5–3.0.
     pub font_scale: f32,
     pub idle_timeout_seconds: u64,
     pub error_timeout_seconds: u64,
+    /// Added to every confirmation and error timeout, for employees who need longer to read.
+    pub extra_timeout_seconds: u64,
+    /// How long the "Rückgängig" button stays on a clock-in/out confirmation; 0 hides it.
+    pub undo_seconds: u64,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            resolution: "1024x600".to_string(),
+            fullscreen: true,
+            orientation: "landscape".to_string(),
+            theme: "dark".to_string(),
+            font_scale: 1.0,
+            idle_timeout_seconds: 8,
+            error_timeout_seconds: 5,
+            extra_timeout_seconds: 0,
+            undo_seconds: 10,
+        }
+    }
 }
 
 impl DisplayConfig {
@@ -32,6 +104,26 @@ impl DisplayConfig {
         self.parse_resolution().1
     }
 
+    /// How long confirmation and info screens stay up.
+    pub fn confirmation_seconds(&self) -> u64 {
+        self.idle_timeout_seconds + self.extra_timeout_seconds
+    }
+
+    /// How long error screens stay up.
+    pub fn error_seconds(&self) -> u64 {
+        self.error_timeout_seconds + self.extra_timeout_seconds
+    }
+
+    /// `true` if `resolution` is `"<width>x<height>"` with both sides non-zero.
+    pub fn resolution_is_valid(&self) -> bool {
+        match self.resolution.split_once('x') {
+            Some((w, h)) => [w, h]
+                .iter()
+                .all(|side| side.trim().parse::<u32>().is_ok_and(|n| n > 0)),
+            None => false,
+        }
+    }
+
     fn parse_resolution(&self) -> (u32, u32) {
         let parts: Vec<&str> = self.resolution.split('x').collect();
         if parts.len() == 2 {
@@ -45,6 +137,7 @@ impl DisplayConfig {
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct ApiConfig {
     pub base_url: String,
     pub timeout_seconds: u64,
@@ -53,88 +146,461 @@ pub struct ApiConfig {
     pub terminal_id: String,
 }
 
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:8080/api".to_string(),
+            timeout_seconds: 10,
+            retry_attempts: 3,
+            terminal_id: "terminal-01".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct OfflineConfig {
     pub buffer_path: String,
     pub sync_interval_seconds: u64,
     pub max_buffer_size: u32,
 }
 
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        Self {
+            buffer_path: "/var/lib/zeiterfassung/buffer.db".to_string(),
+            sync_interval_seconds: 30,
+            max_buffer_size: 10000,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct RfidConfig {
     pub input_device: String,
     pub debounce_ms: u64,
+    /// After a successful clocking, scanning the same badge again within this many seconds
+    /// asks before clocking it again; 0 never asks.
+    pub cooldown_seconds: u64,
+    /// How long that question stays on screen.
+    pub cooldown_prompt_seconds: u64,
+    /// How often the list of revoked badges is re-fetched from the backend.
+    pub blocklist_refresh_minutes: u64,
+    /// This many unknown or revoked badges within `rejected_window_seconds` lock the reader
+    /// and alert the backend; 0 never locks.
+    pub max_rejected_scans: u32,
+    pub rejected_window_seconds: u64,
+    /// How long the reader ignores every badge after such a burst.
+    pub lockout_seconds: u64,
+}
+
+impl Default for RfidConfig {
+    fn default() -> Self {
+        Self {
+            input_device: "auto".to_string(),
+            debounce_ms: 500,
+            cooldown_seconds: 60,
+            cooldown_prompt_seconds: 10,
+            blocklist_refresh_minutes: 5,
+            max_rejected_scans: 10,
+            rejected_window_seconds: 60,
+            lockout_seconds: 120,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct AudioConfig {
     pub enabled: bool,
+    /// Generic tone for every successful event without a clip of its own.
     pub success_sound: String,
+    /// Generic tone for every failure without a clip of its own.
     pub error_sound: String,
+    /// Default volume, 0.0–1.0.
+    pub volume: f32,
+    /// Volume for successful events; `volume` if unset.
+    pub success_volume: Option<f32>,
+    /// Volume for errors, e.g. louder so a rejected badge is not missed; `volume` if unset.
+    pub error_volume: Option<f32>,
+    /// Time-of-day volume profiles in the terminal's time zone (`locale.timezone`).  The first
+    /// matching profile wins; outside all profiles the volumes above apply unchanged.
+    pub schedule: Vec<VolumeProfile>,
+    /// Per-event clips, used in every language.
+    pub events: SoundSet,
+    /// Spoken clips keyed by language code (`[audio.spoken.de]`); preferred over `events`.
+    pub spoken: BTreeMap<String, SoundSet>,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            success_sound: "assets/sounds/success.wav".to_string(),
+            error_sound: "assets/sounds/error.wav".to_string(),
+            volume: 0.7,
+            success_volume: None,
+            error_volume: None,
+            schedule: Vec::new(),
+            events: SoundSet::default(),
+            spoken: BTreeMap::new(),
+        }
+    }
+}
+
+/// WAV clip per scan outcome.  Unset events fall back to the next less specific sound.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SoundSet {
+    pub clock_in: Option<String>,
+    pub clock_out: Option<String>,
+    pub offline_saved: Option<String>,
+    pub unknown_badge: Option<String>,
+    pub conflict: Option<String>,
+    pub server_error: Option<String>,
+    pub badge_blocked: Option<String>,
+}
+
+/// Scales the volume between `start` and `end` (`"HH:MM"`, local time).  A profile whose end
+/// is before its start runs over midnight, e.g. `22:00`–`06:00`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct VolumeProfile {
+    pub start: String,
+    pub end: String,
+    /// Share of the normal volume: 0.3 plays at 30 %, 0.0 mutes, values above 1.0 are louder.
     pub volume: f32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct LocaleConfig {
     pub language: String,
+    /// IANA time zone of the terminal, e.g. `"Europe/Berlin"`.
+    pub timezone: String,
+}
+
+impl Default for LocaleConfig {
+    fn default() -> Self {
+        Self {
+            language: "de".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct CompanyConfig {
     pub name: String,
     pub logo_path: String,
+    /// Pull company name, logo and brand colours from the backend.  Synced values take
+    /// precedence over `name` and `logo_path`, which remain the offline fallback.
+    pub sync_branding: bool,
+    /// Where the synced branding is cached between restarts.
+    pub branding_cache_dir: String,
+    pub branding_refresh_minutes: u64,
+}
+
+impl Default for CompanyConfig {
+    fn default() -> Self {
+        Self {
+            name: "Firma GmbH".to_string(),
+            logo_path: "assets/logo.png".to_string(),
+            sync_branding: true,
+            branding_cache_dir: "/var/lib/zeiterfassung/branding".to_string(),
+            branding_refresh_minutes: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectsConfig {
+    /// Offer the project / cost-center selection after clocking in.
+    pub enabled: bool,
+    /// How often the cached project list is refreshed from the backend.
+    pub refresh_interval_seconds: u64,
+    /// Seconds of inactivity before the selection screen returns to idle.
+    pub selection_timeout_seconds: u64,
+    /// Number of most recently used projects shown as favourites per badge.
+    pub favourites_count: u32,
+}
+
+impl Default for ProjectsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_interval_seconds: 900,
+            selection_timeout_seconds: 30,
+            favourites_count: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InfoConfig {
+    /// Show the "Kontostand" button on the idle screen.
+    pub enabled: bool,
+    /// A second read of the same badge within this window opens the info screen instead of
+    /// clocking.  Every clock scan waits for the window to pass, so 0 (the default) disables
+    /// double-scan detection.
+    pub double_scan_window_ms: u64,
+}
+
+impl Default for InfoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            double_scan_window_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PhotosConfig {
+    /// Show the employee photo on the clock-in/clock-out screens.  Off by default because
+    /// photos are personal data; enable only where the works council agreed to it.
+    pub enabled: bool,
+    /// Directory for downloaded photos.
+    pub cache_dir: String,
+    /// Size cap for the cache directory; least recently shown photos are evicted first.
+    pub max_cache_mb: u64,
+    /// Photos are downloaded again after this many hours.
+    pub max_age_hours: u64,
+}
+
+impl Default for PhotosConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cache_dir: "/var/lib/zeiterfassung/photos".to_string(),
+            max_cache_mb: 50,
+            max_age_hours: 168,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// SHA-256 hashes (hex) of the badges that open the maintenance screen.  Only hashes are
+    /// stored so a leaked config file does not reveal a usable admin badge.
+    pub admin_badge_hashes: Vec<String>,
+    /// The maintenance screen locks itself after this many seconds without interaction.
+    pub inactivity_timeout_seconds: u64,
+    /// Number of recent scans listed on the maintenance screen.
+    pub recent_scans: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            admin_badge_hashes: Vec::new(),
+            inactivity_timeout_seconds: 60,
+            recent_scans: 10,
+        }
+    }
+}
+
+/// Employee number + PIN entry for employees who forgot their badge.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PinConfig {
+    /// Show the "PIN eingeben" button on the idle screen.
+    pub enabled: bool,
+    /// Shuffle the digits of the keypad for every entry, against shoulder surfing.
+    pub shuffle_keypad: bool,
+    /// Wrong PINs for one employee number before that number is locked.
+    pub max_attempts: u32,
+    /// How long an employee number stays locked after `max_attempts` wrong PINs.
+    pub lockout_seconds: u64,
+    /// The keypad closes after this many seconds without input.
+    pub entry_timeout_seconds: u64,
+    /// Two-factor mode: every badge scan also asks for the PIN, except for employees
+    /// the roster marks as exempt.
+    pub require_with_badge: bool,
+    /// How often the roster with PIN hashes and exemptions is re-fetched in two-factor mode.
+    pub roster_refresh_minutes: u64,
+}
+
+impl Default for PinConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shuffle_keypad: true,
+            max_attempts: 3,
+            lockout_seconds: 300,
+            entry_timeout_seconds: 30,
+            require_with_badge: false,
+            roster_refresh_minutes: 60,
+        }
+    }
+}
+
+/// Rotating QR code on the idle screen, scanned by the mobile app to clock in at this terminal.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct QrConfig {
+    pub enabled: bool,
+    /// HMAC key shared with the backend.  The code is not shown while this is empty.
+    pub secret: String,
+    /// A new code is shown every this many seconds.
+    pub rotation_seconds: u64,
+}
+
+impl Default for QrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            secret: String::new(),
+            rotation_seconds: 10,
+        }
+    }
+}
+
+/// Configuration document pulled from the backend by `terminal_id` and merged over the local file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub enabled: bool,
+    /// How often the document is fetched.
+    pub refresh_minutes: u64,
+    /// Last known-good document, used at startup and while the backend is unreachable.
+    pub cache_path: String,
+    /// Keys the remote document may not change, as dotted paths (`"display.theme"`) or
+    /// whole sections (`"api"`).  `api.terminal_id`, `api.base_url`, `maintenance` and
+    /// `remote` are always pinned.
+    pub pinned: Vec<String>,
 }
 
-impl Default for AppConfig {
+impl Default for RemoteConfig {
     fn default() -> Self {
         Self {
-            display: DisplayConfig {
-                resolution: "1024x600".to_string(),
-                fullscreen: true,
-                orientation: "landscape".to_string(),
-                theme: "dark".to_string(),
-                font_scale: 1.0,
-                idle_timeout_seconds: 8,
-                error_timeout_seconds: 5,
-            },
-            api: ApiConfig {
-                base_url: "http://localhost:8080/api".to_string(),
-                timeout_seconds: 10,
-                retry_attempts: 3,
-                terminal_id: "terminal-01".to_string(),
-            },
-            offline: OfflineConfig {
-                buffer_path: "/var/lib/zeiterfassung/buffer.db".to_string(),
-                sync_interval_seconds: 30,
-                max_buffer_size: 10000,
-            },
-            rfid: RfidConfig {
-                input_device: "auto".to_string(),
-                debounce_ms: 500,
-            },
-            audio: AudioConfig {
-                enabled: true,
-                success_sound: "assets/sounds/success.wav".to_string(),
-                error_sound: "assets/sounds/error.wav".to_string(),
-                volume: 0.7,
-            },
-            locale: LocaleConfig {
-                language: "de".to_string(),
-            },
-            company: CompanyConfig {
-                name: "Firma GmbH".to_string(),
-                logo_path: "assets/logo.png".to_string(),
-            },
+            enabled: false,
+            refresh_minutes: 15,
+            cache_path: "/var/lib/zeiterfassung/remote-config.json".to_string(),
+            pinned: Vec::new(),
         }
     }
 }
 
 impl AppConfig {
-    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    /// One file as a TOML table, for merging with the other layers and the remote document.
+    pub fn load_table(path: &Path) -> Result<toml::Table, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let config: AppConfig = toml::from_str(&content)?;
-        Ok(config)
+        Ok(toml::from_str(&content)?)
+    }
+
+    pub fn from_table(table: toml::Table) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(table.try_into()?)
+    }
+
+    /// Rejects values that would break the terminal at runtime.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.value_errors().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Every value that would break the terminal at runtime.
+    pub fn value_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !(self.api.base_url.starts_with("http://") || self.api.base_url.starts_with("https://"))
+        {
+            errors.push(format!(
+                "api.base_url must start with http:// or https://: {}",
+                self.api.base_url
+            ));
+        }
+        if self.api.terminal_id.trim().is_empty() {
+            errors.push("api.terminal_id must not be empty".to_string());
+        }
+        let volumes = [
+            Some(self.audio.volume),
+            self.audio.success_volume,
+            self.audio.error_volume,
+        ];
+        if volumes
+            .into_iter()
+            .flatten()
+            .any(|v| !(0.0..=1.0).contains(&v))
+        {
+            errors.push("audio volumes must be between 0.0 and 1.0".to_string());
+        }
+        if self.display.idle_timeout_seconds == 0 || self.display.error_timeout_seconds == 0 {
+            errors.push("display timeouts must be at least 1 second".to_string());
+        }
+        if self.rfid.cooldown_seconds > 0 && self.rfid.cooldown_prompt_seconds == 0 {
+            errors.push("rfid.cooldown_prompt_seconds must be at least 1 second".to_string());
+        }
+        if self.rfid.max_rejected_scans > 0
+            && (self.rfid.rejected_window_seconds == 0 || self.rfid.lockout_seconds == 0)
+        {
+            errors.push(
+                "rfid.rejected_window_seconds and rfid.lockout_seconds must be at least 1 second"
+                    .to_string(),
+            );
+        }
+        if !self.display.resolution_is_valid() {
+            errors.push(format!(
+                "display.resolution must look like \"1024x600\": {}",
+                self.display.resolution
+            ));
+        }
+        errors
+    }
+
+    /// Keys from [`RESTART_REQUIRED`] whose value differs between `self` and `other`.
+    pub fn restart_required_changes(&self, other: &AppConfig) -> Vec<&'static str> {
+        let (Ok(a), Ok(b)) = (toml::Table::try_from(self), toml::Table::try_from(other)) else {
+            return Vec::new();
+        };
+        RESTART_REQUIRED
+            .iter()
+            .copied()
+            .filter(|key| lookup(&a, key) != lookup(&b, key))
+            .collect()
+    }
+
+    /// `self` with every [`RESTART_REQUIRED`] key reset to its value in `running`, so a
+    /// reload never half-applies a setting that is only read at startup.
+    pub fn with_startup_values(self, running: &AppConfig) -> AppConfig {
+        let (Ok(mut table), Ok(old)) =
+            (toml::Table::try_from(&self), toml::Table::try_from(running))
+        else {
+            return self;
+        };
+        for key in RESTART_REQUIRED {
+            let (parent, name) = match key.rsplit_once('.') {
+                Some((section, name)) => {
+                    match table.get_mut(section).and_then(|v| v.as_table_mut()) {
+                        Some(section) => (section, name),
+                        None => continue,
+                    }
+                }
+                None => (&mut table, *key),
+            };
+            match lookup(&old, key) {
+                Some(value) => parent.insert(name.to_string(), value.clone()),
+                None => parent.remove(name),
+            };
+        }
+        Self::from_table(table).unwrap_or(self)
+    }
+}
+
+/// Value at a dotted path such as `"display.resolution"`.
+pub fn lookup<'a>(table: &'a toml::Table, path: &str) -> Option<&'a toml::Value> {
+    let mut parts = path.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
     }
+    Some(value)
 }
 
 #[cfg(test)]
@@ -159,6 +625,8 @@ mod tests {
             font_scale: 1.0,
             idle_timeout_seconds: 8,
             error_timeout_seconds: 5,
+            extra_timeout_seconds: 0,
+            undo_seconds: 10,
         };
         assert_eq!(config.resolution_width(), 1920);
         assert_eq!(config.resolution_height(), 1080);
@@ -174,6 +642,8 @@ mod tests {
             font_scale: 1.0,
             idle_timeout_seconds: 8,
             error_timeout_seconds: 5,
+            extra_timeout_seconds: 0,
+            undo_seconds: 10,
         };
         assert_eq!(config.resolution_width(), 1024);
         assert_eq!(config.resolution_height(), 600);
@@ -225,12 +695,87 @@ logo_path = "assets/test-logo.png"
         assert_eq!(config.display.resolution_height(), 480);
         assert!(!config.display.fullscreen);
         assert_eq!(config.display.theme, "light");
+        assert_eq!(config.display.extra_timeout_seconds, 0);
+        assert_eq!(config.display.confirmation_seconds(), 10);
         assert_eq!(config.api.base_url, "https://example.com/api");
         assert_eq!(config.api.retry_attempts, 5);
         assert_eq!(config.api.terminal_id, "terminal-02");
         assert!(!config.audio.enabled);
         assert_eq!(config.locale.language, "en");
         assert_eq!(config.company.name, "Test GmbH");
+        // Optional sections fall back to their defaults when omitted.
+        assert!(!config.projects.enabled);
+        assert!(config.info.enabled);
+        assert_eq!(config.info.double_scan_window_ms, 0);
+        assert!(!config.photos.enabled, "photos must be opt-in");
+        assert!(config.company.sync_branding);
+        assert!(config.maintenance.admin_badge_hashes.is_empty());
+        assert_eq!(config.locale.timezone, "Europe/Berlin");
+        assert!(config.audio.schedule.is_empty());
+        assert!(config.audio.error_volume.is_none());
+        assert_eq!(config.company.branding_refresh_minutes, 60);
+    }
+
+    #[test]
+    fn test_partial_file_uses_field_defaults() {
+        let empty = AppConfig::from_table(toml::Table::new()).unwrap();
+        assert_eq!(
+            toml::Table::try_from(&empty).unwrap(),
+            toml::Table::try_from(AppConfig::default()).unwrap()
+        );
+
+        let toml_str = r#"
+[api]
+terminal_id = "lager-01"
+
+[display]
+theme = "light"
+"#;
+        let config: AppConfig = toml::from_str(toml_str).expect("failed to parse TOML");
+        assert_eq!(config.api.terminal_id, "lager-01");
+        assert_eq!(config.api.base_url, ApiConfig::default().base_url);
+        assert_eq!(config.display.theme, "light");
+        assert_eq!(config.display.resolution, "1024x600");
+        assert_eq!(config.audio.volume, 0.7);
+    }
+
+    #[test]
+    fn test_projects_section_partial() {
+        let toml_str = r#"
+enabled = true
+favourites_count = 3
+"#;
+        let projects: ProjectsConfig = toml::from_str(toml_str).expect("failed to parse TOML");
+        assert!(projects.enabled);
+        assert_eq!(projects.favourites_count, 3);
+        assert_eq!(projects.refresh_interval_seconds, 900);
+    }
+
+    #[test]
+    fn test_audio_volume_schedule() {
+        let toml_str = r#"
+enabled = true
+success_sound = "assets/sounds/success.wav"
+error_sound = "assets/sounds/error.wav"
+volume = 0.7
+error_volume = 1.0
+
+[[schedule]]
+start = "22:00"
+end = "06:00"
+volume = 0.3
+"#;
+        let audio: AudioConfig = toml::from_str(toml_str).expect("failed to parse TOML");
+        assert_eq!(audio.error_volume, Some(1.0));
+        assert!(audio.success_volume.is_none());
+        assert_eq!(
+            audio.schedule,
+            vec![VolumeProfile {
+                start: "22:00".to_string(),
+                end: "06:00".to_string(),
+                volume: 0.3,
+            }]
+        );
     }
 
     #[test]
@@ -243,4 +788,45 @@ logo_path = "assets/test-logo.png"
             "terminal_id must not be empty"
         );
     }
+
+    #[test]
+    fn test_validate_and_restart_required_changes() {
+        let config = AppConfig::default();
+        assert!(config.validate().is_ok());
+
+        let mut bad = config.clone();
+        bad.api.base_url = "localhost:8080".to_string();
+        assert!(bad.validate().is_err());
+        let mut bad = config.clone();
+        bad.audio.error_volume = Some(1.5);
+        assert!(bad.validate().is_err());
+        bad.display.resolution = "1024x".to_string();
+        assert_eq!(bad.value_errors().len(), 2);
+        bad.rfid.cooldown_prompt_seconds = 0;
+        assert_eq!(bad.value_errors().len(), 3);
+        bad.rfid.cooldown_seconds = 0;
+        assert_eq!(bad.value_errors().len(), 2);
+        bad.rfid.lockout_seconds = 0;
+        assert_eq!(bad.value_errors().len(), 3);
+        bad.rfid.max_rejected_scans = 0;
+        assert_eq!(bad.value_errors().len(), 2);
+
+        let mut changed = config.clone();
+        changed.display.idle_timeout_seconds = 20;
+        changed.offline.sync_interval_seconds = 10;
+        changed.rfid.debounce_ms = 1000;
+        assert!(config.restart_required_changes(&changed).is_empty());
+        changed.display.resolution = "800x480".to_string();
+        changed.rfid.input_device = "/dev/input/event3".to_string();
+        assert_eq!(
+            config.restart_required_changes(&changed),
+            vec!["display.resolution", "rfid.input_device"]
+        );
+
+        let applied = changed.with_startup_values(&config);
+        assert_eq!(applied.display.resolution, "1024x600");
+        assert_eq!(applied.rfid.input_device, config.rfid.input_device);
+        assert_eq!(applied.rfid.debounce_ms, 1000);
+        assert_eq!(applied.display.idle_timeout_seconds, 20);
+    }
 }
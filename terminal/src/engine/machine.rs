@@ -0,0 +1,1406 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::api::{ApiError, ClockResponse};
+use crate::audio::SoundEvent;
+use crate::config::AppConfig;
+use crate::maintenance::ScanOutcome;
+
+/// Ticks an outcome stays on screen at most while other badges are waiting behind it.
+const QUEUED_DISPLAY_SECONDS: u64 = 2;
+
+/// What became of a clocking.
+#[derive(Debug, Clone)]
+pub enum ClockOutcome {
+    /// The backend booked the clocking.
+    Accepted(ClockResponse),
+    /// The backend was unreachable; the clocking waits in the buffer for the next sync.
+    Buffered,
+    /// 404 — no employee is registered for this badge.
+    UnknownBadge,
+    /// 409 — another terminal booked the same employee at the same instant.
+    Conflict,
+    /// Any other answer from the backend.
+    Failed(ApiError),
+    /// The badge is on the blocklist; it was neither sent nor buffered.
+    Blocked,
+}
+
+impl From<Result<ClockResponse, ApiError>> for ClockOutcome {
+    fn from(result: Result<ClockResponse, ApiError>) -> Self {
+        match result {
+            Ok(response) => ClockOutcome::Accepted(response),
+            Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => ClockOutcome::Buffered,
+            Err(ApiError::NotFound(_)) => ClockOutcome::UnknownBadge,
+            Err(ApiError::Conflict) => ClockOutcome::Conflict,
+            Err(err) => ClockOutcome::Failed(err),
+        }
+    }
+}
+
+impl ClockOutcome {
+    fn is_success(&self) -> bool {
+        matches!(self, ClockOutcome::Accepted(_) | ClockOutcome::Buffered)
+    }
+}
+
+/// Durations the machine works with, taken from the configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    /// Repeated reads of the same tag within this window are ignored.
+    pub debounce: Duration,
+    /// A second read of the same tag within this window asks for the balances instead of
+    /// clocking; zero clocks right away.
+    pub double_scan_window: Duration,
+    /// Ticks a confirmation stays on screen.
+    pub confirmation_seconds: u64,
+    /// Ticks an error stays on screen.
+    pub error_seconds: u64,
+    /// Ticks an outcome stays on screen at most while badges are queued behind it.
+    pub queued_display_seconds: u64,
+    /// A badge read again within this window after a successful clocking asks before
+    /// clocking it again; zero never asks.
+    pub cooldown: Duration,
+    /// Ticks the question stays on screen.
+    pub cooldown_prompt_seconds: u64,
+    /// This many unknown or revoked badges within `rejected_window` lock the reader; zero
+    /// never locks.
+    pub max_rejected: u32,
+    pub rejected_window: Duration,
+    /// How long the reader stays locked.
+    pub lockout: Duration,
+}
+
+impl Timing {
+    pub fn new(config: &AppConfig) -> Self {
+        let double_scan_window = if config.info.enabled {
+            Duration::from_millis(config.info.double_scan_window_ms)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            debounce: Duration::from_millis(config.rfid.debounce_ms),
+            double_scan_window,
+            confirmation_seconds: config.display.confirmation_seconds(),
+            error_seconds: config.display.error_seconds(),
+            queued_display_seconds: QUEUED_DISPLAY_SECONDS,
+            cooldown: Duration::from_secs(config.rfid.cooldown_seconds),
+            cooldown_prompt_seconds: config.rfid.cooldown_prompt_seconds,
+            max_rejected: config.rfid.max_rejected_scans,
+            rejected_window: Duration::from_secs(config.rfid.rejected_window_seconds),
+            lockout: Duration::from_secs(config.rfid.lockout_seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Phase {
+    Idle,
+    /// First read of a badge; waiting for the double-scan window to elapse.
+    AwaitingSecondScan {
+        rfid: String,
+    },
+    /// The clocking of `rfid` was sent; waiting for the answer.
+    Loading {
+        rfid: String,
+    },
+    /// The balances of `rfid` are looked up or on screen; waiting for [`Input::Dismissed`].
+    Info {
+        rfid: String,
+    },
+    /// The screen is busy outside the clocking flow (PIN entry, project selection, taking
+    /// back a clocking, a balance lookup), for `rfid` if it started from one; badges read
+    /// meanwhile are queued.
+    Held {
+        rfid: Option<String>,
+    },
+    /// An outcome is on screen for `seconds_left` more ticks.  `rfid` is empty for results
+    /// the screen produced without a badge, such as PIN clockings.
+    Showing {
+        rfid: String,
+        seconds_left: u64,
+    },
+    /// `rfid` was clocked moments ago; asking for `seconds_left` more ticks whether to clock
+    /// it again.
+    Confirming {
+        rfid: String,
+        seconds_left: u64,
+    },
+}
+
+impl Phase {
+    /// The badge the phase is about, if any.
+    fn rfid(&self) -> Option<&String> {
+        match self {
+            Phase::Idle => None,
+            Phase::Held { rfid } => rfid.as_ref(),
+            Phase::AwaitingSecondScan { rfid }
+            | Phase::Loading { rfid }
+            | Phase::Info { rfid }
+            | Phase::Showing { rfid, .. }
+            | Phase::Confirming { rfid, .. } => Some(rfid),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Input {
+    /// A tag was read.
+    Scan(String),
+    /// The double-scan window started for this tag has elapsed.
+    WindowElapsed(String),
+    /// The backend's answer to [`Effect::Request`].
+    Response {
+        rfid: String,
+        result: Result<ClockResponse, ApiError>,
+    },
+    /// The employee confirmed clocking this badge again within the cooldown.
+    Confirmed(String),
+    /// The last clocking of this badge was taken back; it no longer counts for the cooldown.
+    Undone(String),
+    /// One second passed.
+    Tick,
+    /// The screen left the clocking flow for a while; see [`Phase::Held`].
+    Hold,
+    /// While held, the screen shows a result of its own for this many ticks; it counts like
+    /// a clocking outcome.
+    Shown(u64),
+    /// The screen went back to idle by itself: the balances were closed, the PIN entry or
+    /// project selection ended, …  The badges queued meanwhile are clocked next.
+    Dismissed,
+    /// The screen left the clocking flow for good (maintenance, lockout); forget it and the
+    /// badges queued behind it.
+    Reset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// The badge is being clocked or already waiting in the queue.
+    Busy,
+    /// The badge was queued, but the flow was reset or the reader locked before its turn.
+    Cancelled,
+}
+
+/// Work the caller does on behalf of the machine.
+#[derive(Debug, Clone)]
+pub enum Effect {
+    /// Send the clocking of this badge to the backend and answer with [`Input::Response`].
+    Request(String),
+    /// Answer with [`Input::WindowElapsed`] after this long.
+    Wait {
+        rfid: String,
+        window: Duration,
+    },
+    /// The badge was read twice in the window: show its balances instead of clocking.
+    ShowInfo(String),
+    /// Another clocking is in progress; this badge is clocked after the ones before it.
+    Queued(String),
+    /// The badge was clocked moments ago: ask before clocking it again and answer with
+    /// [`Input::Confirmed`].  `last_entry` is the entry type of that clocking, `None` if it
+    /// was buffered.
+    Confirm {
+        rfid: String,
+        last_entry: Option<String>,
+    },
+    /// Store the clocking of this badge for the next sync.
+    Buffer(String),
+    Play(SoundEvent),
+    /// Add the scan to the maintenance screen's log.
+    Record(String, ScanOutcome),
+    Show {
+        rfid: String,
+        outcome: ClockOutcome,
+    },
+    ShowIdle,
+    /// `rejected` unknown or revoked badges were read within `window`: every read is
+    /// ignored for `lockout`.  Show the lockout screen and alert the backend.
+    LockedOut {
+        rejected: u32,
+        window: Duration,
+        lockout: Duration,
+    },
+    /// The scan was not clocked.
+    Ignored {
+        rfid: String,
+        reason: IgnoreReason,
+    },
+}
+
+/// The badge clocking flow without any I/O: inputs in, effects out.  The caller supplies
+/// the time with every input, so debouncing and the cooldown can be tested without waiting.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    timing: Timing,
+    phase: Phase,
+    last_scan: Option<(String, Instant)>,
+    /// Badges read while another one was being clocked, oldest first.
+    queue: VecDeque<String>,
+    /// Successful clockings within the cooldown: when, and the entry type if known.
+    recent: HashMap<String, (Instant, Option<String>)>,
+    /// Revoked badges, as last pushed by the backend.
+    blocked: HashSet<String>,
+    /// When unknown or revoked badges were read within the rejection window, oldest first.
+    rejected: VecDeque<Instant>,
+    locked_until: Option<Instant>,
+}
+
+impl Machine {
+    pub fn new(timing: Timing) -> Self {
+        Self {
+            timing,
+            phase: Phase::Idle,
+            last_scan: None,
+            queue: VecDeque::new(),
+            recent: HashMap::new(),
+            blocked: HashSet::new(),
+            rejected: VecDeque::new(),
+            locked_until: None,
+        }
+    }
+
+    pub fn phase(&self) -> &Phase {
+        &self.phase
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    pub fn set_blocklist(&mut self, blocked: HashSet<String>) {
+        self.blocked = blocked;
+    }
+
+    pub fn is_blocked(&self, rfid: &str) -> bool {
+        self.blocked.contains(rfid)
+    }
+
+    /// Whether a burst of rejected badges has locked the reader.
+    pub fn locked(&self, now: Instant) -> bool {
+        self.locked_until.is_some_and(|until| now < until)
+    }
+
+    /// First stage for every read, before the screen routes it anywhere: `false` for a
+    /// repeated read of the same tag within the debounce window, and for every read while
+    /// the reader is locked.
+    pub fn admit(&mut self, now: Instant, rfid: &str) -> bool {
+        if self.locked(now) {
+            return false;
+        }
+        if let Some((last, at)) = &self.last_scan {
+            if last == rfid && now.duration_since(*at) < self.timing.debounce {
+                return false;
+            }
+        }
+        self.last_scan = Some((rfid.to_string(), now));
+        true
+    }
+
+    /// Takes one input; scans are expected to have passed [`Machine::admit`].
+    pub fn step(&mut self, now: Instant, input: Input) -> Vec<Effect> {
+        match input {
+            Input::Scan(rfid) => self.scan(now, rfid),
+            Input::WindowElapsed(rfid) => match &self.phase {
+                Phase::AwaitingSecondScan { rfid: waiting } if *waiting == rfid => {
+                    self.phase = Phase::Loading { rfid: rfid.clone() };
+                    vec![Effect::Request(rfid)]
+                }
+                _ => Vec::new(),
+            },
+            Input::Response { rfid, result } => self.settle(now, rfid, result),
+            Input::Undone(rfid) => {
+                self.recent.remove(&rfid);
+                Vec::new()
+            }
+            Input::Confirmed(rfid) => match &self.phase {
+                Phase::Confirming { rfid: asked, .. } if *asked == rfid => self.request(rfid),
+                _ => Vec::new(),
+            },
+            Input::Tick => match &mut self.phase {
+                Phase::Showing { seconds_left, .. } | Phase::Confirming { seconds_left, .. }
+                    if *seconds_left > 0 =>
+                {
+                    *seconds_left -= 1;
+                    Vec::new()
+                }
+                Phase::Showing { .. } | Phase::Confirming { .. } => self.next(),
+                _ => Vec::new(),
+            },
+            Input::Hold => {
+                if !matches!(self.phase, Phase::Held { .. }) {
+                    let rfid = self.phase.rfid().cloned();
+                    self.phase = Phase::Held { rfid };
+                }
+                Vec::new()
+            }
+            Input::Shown(seconds) => {
+                if let Phase::Held { rfid } = &mut self.phase {
+                    let rfid = rfid.take().unwrap_or_default();
+                    self.phase = Phase::Showing {
+                        rfid,
+                        seconds_left: self.display_seconds(seconds),
+                    };
+                }
+                Vec::new()
+            }
+            Input::Dismissed => match &self.phase {
+                Phase::Idle => Vec::new(),
+                _ => self.next(),
+            },
+            Input::Reset => {
+                self.phase = Phase::Idle;
+                self.cancel_queue()
+            }
+        }
+    }
+
+    /// Ticks an outcome stays on screen: shortened while others are waiting, so the queue
+    /// keeps moving at shift change.
+    fn display_seconds(&self, seconds: u64) -> u64 {
+        if self.queue.is_empty() {
+            seconds
+        } else {
+            seconds.min(self.timing.queued_display_seconds)
+        }
+    }
+
+    /// Queues `rfid` behind the clocking in progress, once.
+    fn enqueue(&mut self, rfid: String) -> Vec<Effect> {
+        if self.queue.contains(&rfid) {
+            return vec![Effect::Ignored {
+                rfid,
+                reason: IgnoreReason::Busy,
+            }];
+        }
+        self.queue.push_back(rfid.clone());
+        vec![Effect::Queued(rfid)]
+    }
+
+    /// Once a confirmation or the balances are off screen: the next queued badge, if any.
+    fn next(&mut self) -> Vec<Effect> {
+        match self.queue.pop_front() {
+            Some(next) => self.start(next),
+            None => {
+                self.phase = Phase::Idle;
+                vec![Effect::ShowIdle]
+            }
+        }
+    }
+
+    /// Drops the queued badges once the screen leaves the clocking flow.
+    fn cancel_queue(&mut self) -> Vec<Effect> {
+        self.queue
+            .drain(..)
+            .map(|rfid| Effect::Ignored {
+                rfid,
+                reason: IgnoreReason::Cancelled,
+            })
+            .collect()
+    }
+
+    fn scan(&mut self, now: Instant, rfid: String) -> Vec<Effect> {
+        // Revoked badges are refused at once, online or not.  A clocking in progress is not
+        // interrupted; the refusal is still heard and logged.
+        if self.blocked.contains(&rfid) {
+            let mut effects = feedback(&rfid, &ClockOutcome::Blocked);
+            let busy = matches!(
+                self.phase,
+                Phase::AwaitingSecondScan { .. }
+                    | Phase::Loading { .. }
+                    | Phase::Info { .. }
+                    | Phase::Held { .. }
+            );
+            if let Some(lockout) = self.reject(now) {
+                if !busy {
+                    self.phase = Phase::Idle;
+                }
+                effects.extend(self.cancel_queue());
+                effects.push(lockout);
+            } else if !busy {
+                self.phase = Phase::Showing {
+                    rfid: rfid.clone(),
+                    seconds_left: self.timing.error_seconds,
+                };
+                effects.push(Effect::Show {
+                    rfid,
+                    outcome: ClockOutcome::Blocked,
+                });
+            }
+            return effects;
+        }
+
+        match &self.phase {
+            Phase::Idle => {}
+            // Badge held on the reader (or scanned twice quickly): show balances instead.  The
+            // badges queued behind it wait until the balances are dismissed.
+            Phase::AwaitingSecondScan { rfid: waiting } if *waiting == rfid => {
+                self.phase = Phase::Info { rfid: rfid.clone() };
+                return vec![Effect::ShowInfo(rfid)];
+            }
+            Phase::AwaitingSecondScan { rfid: busy }
+            | Phase::Loading { rfid: busy }
+            | Phase::Info { rfid: busy }
+                if *busy == rfid =>
+            {
+                return vec![Effect::Ignored {
+                    rfid,
+                    reason: IgnoreReason::Busy,
+                }];
+            }
+            Phase::Held { rfid: Some(busy) } if *busy == rfid => {
+                return vec![Effect::Ignored {
+                    rfid,
+                    reason: IgnoreReason::Busy,
+                }];
+            }
+            Phase::AwaitingSecondScan { .. }
+            | Phase::Loading { .. }
+            | Phase::Info { .. }
+            | Phase::Held { .. } => return self.enqueue(rfid),
+            // The next person does not have to wait for the confirmation to time out.  With
+            // others already waiting, the badge joins them and the screen is cut short.
+            Phase::Showing { rfid: shown, .. } | Phase::Confirming { rfid: shown, .. }
+                if *shown != rfid && !self.queue.is_empty() =>
+            {
+                let effects = self.enqueue(rfid);
+                let cap = self.timing.queued_display_seconds;
+                if let Phase::Showing { seconds_left, .. }
+                | Phase::Confirming { seconds_left, .. } = &mut self.phase
+                {
+                    *seconds_left = (*seconds_left).min(cap);
+                }
+                return effects;
+            }
+            Phase::Showing { rfid: shown, .. } | Phase::Confirming { rfid: shown, .. }
+                if *shown != rfid => {}
+            Phase::Showing { .. } | Phase::Confirming { .. } => {
+                return vec![Effect::Ignored {
+                    rfid,
+                    reason: IgnoreReason::Busy,
+                }]
+            }
+        }
+
+        // Scanning twice within a minute would clock straight back out (or in).
+        if let Some((at, last_entry)) = self.recent.get(&rfid) {
+            if now.duration_since(*at) < self.timing.cooldown {
+                let last_entry = last_entry.clone();
+                self.phase = Phase::Confirming {
+                    rfid: rfid.clone(),
+                    seconds_left: self.timing.cooldown_prompt_seconds,
+                };
+                return vec![Effect::Confirm { rfid, last_entry }];
+            }
+        }
+        self.start(rfid)
+    }
+
+    fn start(&mut self, rfid: String) -> Vec<Effect> {
+        if !self.timing.double_scan_window.is_zero() {
+            self.phase = Phase::AwaitingSecondScan { rfid: rfid.clone() };
+            return vec![Effect::Wait {
+                rfid,
+                window: self.timing.double_scan_window,
+            }];
+        }
+        self.request(rfid)
+    }
+
+    fn request(&mut self, rfid: String) -> Vec<Effect> {
+        self.phase = Phase::Loading { rfid: rfid.clone() };
+        vec![Effect::Request(rfid)]
+    }
+
+    /// Late answers (after a reset) are still buffered and logged, just not shown.  With
+    /// badges in the queue, the confirmation is cut short and the next one is clocked
+    /// without the cooldown question holding up everyone behind it.
+    fn settle(
+        &mut self,
+        now: Instant,
+        rfid: String,
+        result: Result<ClockResponse, ApiError>,
+    ) -> Vec<Effect> {
+        let mut outcome = ClockOutcome::from(result);
+        // Revoked while the request was out: refused rather than buffered for later.
+        if matches!(outcome, ClockOutcome::Buffered) && self.blocked.contains(&rfid) {
+            outcome = ClockOutcome::Blocked;
+        }
+        let mut effects = Vec::new();
+        if let ClockOutcome::Buffered = outcome {
+            effects.push(Effect::Buffer(rfid.clone()));
+        }
+        effects.extend(feedback(&rfid, &outcome));
+
+        let lockout = match outcome {
+            ClockOutcome::UnknownBadge | ClockOutcome::Blocked => self.reject(now),
+            _ => None,
+        };
+
+        let cooldown = self.timing.cooldown;
+        self.recent
+            .retain(|_, (at, _)| now.duration_since(*at) < cooldown);
+        if outcome.is_success() && !cooldown.is_zero() {
+            let entry_type = match &outcome {
+                ClockOutcome::Accepted(response) => Some(response.entry_type.clone()),
+                _ => None,
+            };
+            self.recent.insert(rfid.clone(), (now, entry_type));
+        }
+
+        // The lockout screen replaces the refusal, and the queued badges are not clocked.  A
+        // late answer leaves the current flow to the caller.
+        if let Some(lockout) = lockout {
+            if self.phase == (Phase::Loading { rfid }) {
+                self.phase = Phase::Idle;
+            }
+            effects.extend(self.cancel_queue());
+            effects.push(lockout);
+            return effects;
+        }
+
+        if self.phase == (Phase::Loading { rfid: rfid.clone() }) {
+            let seconds_left = if outcome.is_success() {
+                self.timing.confirmation_seconds
+            } else {
+                self.timing.error_seconds
+            };
+            self.phase = Phase::Showing {
+                rfid: rfid.clone(),
+                seconds_left: self.display_seconds(seconds_left),
+            };
+            effects.push(Effect::Show { rfid, outcome });
+        }
+        effects
+    }
+
+    /// Counts an unknown or revoked badge; locks the reader once too many were read within
+    /// the window.
+    fn reject(&mut self, now: Instant) -> Option<Effect> {
+        let max = self.timing.max_rejected;
+        if max == 0 {
+            return None;
+        }
+        let window = self.timing.rejected_window;
+        self.rejected.retain(|at| now.duration_since(*at) < window);
+        self.rejected.push_back(now);
+        if self.rejected.len() < max as usize {
+            return None;
+        }
+        let rejected = self.rejected.len() as u32;
+        self.rejected.clear();
+        self.locked_until = Some(now + self.timing.lockout);
+        Some(Effect::LockedOut {
+            rejected,
+            window,
+            lockout: self.timing.lockout,
+        })
+    }
+}
+
+/// Sound and scan log entry for a settled clocking.  `log_id` is the badge, or the employee
+/// number for PIN clockings.
+pub fn feedback(log_id: &str, outcome: &ClockOutcome) -> Vec<Effect> {
+    let (sound, logged) = match outcome {
+        ClockOutcome::Accepted(response) if response.entry_type == "CLOCK_IN" => {
+            (SoundEvent::ClockIn, ScanOutcome::ClockIn)
+        }
+        ClockOutcome::Accepted(_) => (SoundEvent::ClockOut, ScanOutcome::ClockOut),
+        ClockOutcome::Buffered => (SoundEvent::OfflineSaved, ScanOutcome::Offline),
+        ClockOutcome::UnknownBadge => (SoundEvent::UnknownBadge, ScanOutcome::UnknownBadge),
+        ClockOutcome::Conflict => (SoundEvent::Conflict, ScanOutcome::Conflict),
+        ClockOutcome::Failed(_) => (SoundEvent::ServerError, ScanOutcome::Error),
+        ClockOutcome::Blocked => (SoundEvent::BadgeBlocked, ScanOutcome::Blocked),
+    };
+    vec![
+        Effect::Play(sound),
+        Effect::Record(log_id.to_string(), logged),
+    ]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::api::EmployeeInfo;
+    use chrono::Utc;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    pub(crate) fn response(entry_type: &str) -> ClockResponse {
+        ClockResponse {
+            employee: EmployeeInfo {
+                id: "e-1".to_string(),
+                first_name: "Erika".to_string(),
+                last_name: "Muster".to_string(),
+                photo_url: None,
+            },
+            entry_type: entry_type.to_string(),
+            timestamp: Utc::now(),
+            today_work_minutes: 0,
+            today_break_minutes: 0,
+            overtime_minutes: 0,
+            remaining_vacation_days: 0.0,
+            entry_id: Some("entry-1".to_string()),
+        }
+    }
+
+    fn timing() -> Timing {
+        Timing {
+            debounce: Duration::from_millis(500),
+            double_scan_window: Duration::ZERO,
+            confirmation_seconds: 3,
+            error_seconds: 2,
+            queued_display_seconds: 1,
+            cooldown: Duration::ZERO,
+            cooldown_prompt_seconds: 4,
+            max_rejected: 0,
+            rejected_window: Duration::from_secs(60),
+            lockout: Duration::from_secs(120),
+        }
+    }
+
+    fn scan(rfid: &str) -> Input {
+        Input::Scan(rfid.to_string())
+    }
+
+    fn answer(rfid: &str, result: Result<ClockResponse, ApiError>) -> Input {
+        Input::Response {
+            rfid: rfid.to_string(),
+            result,
+        }
+    }
+
+    #[test]
+    fn test_scan_clocks_and_returns_to_idle() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+
+        let effects = machine.step(now, scan("TAG-1"));
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-1"));
+
+        let effects = machine.step(now, answer("TAG-1", Ok(response("CLOCK_IN"))));
+        assert!(matches!(
+            &effects[..],
+            [
+                Effect::Play(SoundEvent::ClockIn),
+                Effect::Record(_, ScanOutcome::ClockIn),
+                Effect::Show { .. }
+            ]
+        ));
+
+        for _ in 0..3 {
+            assert!(machine.step(now, Input::Tick).is_empty());
+        }
+        assert!(matches!(
+            &machine.step(now, Input::Tick)[..],
+            [Effect::ShowIdle]
+        ));
+        assert_eq!(*machine.phase(), Phase::Idle);
+    }
+
+    #[test]
+    fn test_unreachable_backend_buffers_the_scan() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+
+        let effects = machine.step(now, answer("TAG-1", Err(ApiError::Timeout)));
+        // Buffered before the screen says so.
+        let buffered = effects
+            .iter()
+            .position(|e| matches!(e, Effect::Buffer(r) if r == "TAG-1"));
+        let shown = effects.iter().position(|e| {
+            matches!(
+                e,
+                Effect::Show {
+                    outcome: ClockOutcome::Buffered,
+                    ..
+                }
+            )
+        });
+        assert!(buffered.is_some() && shown.is_some());
+        assert!(buffered < shown);
+
+        // Refusals are not buffered and stay on screen for the error timeout.
+        let mut machine = Machine::new(timing());
+        machine.step(now, scan("TAG-2"));
+        let effects = machine.step(now, answer("TAG-2", Err(ApiError::Conflict)));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Buffer(_))));
+        assert_eq!(
+            *machine.phase(),
+            Phase::Showing {
+                rfid: "TAG-2".to_string(),
+                seconds_left: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_repeated_reads_are_debounced() {
+        let mut machine = Machine::new(timing());
+        let start = Instant::now();
+        assert!(machine.admit(start, "TAG-1"));
+        machine.step(start, scan("TAG-1"));
+        machine.step(start, answer("TAG-1", Ok(response("CLOCK_IN"))));
+        machine.step(start, Input::Reset);
+
+        assert!(!machine.admit(start + Duration::from_millis(100), "TAG-1"));
+        assert!(machine.admit(start + Duration::from_millis(600), "TAG-1"));
+        let effects = machine.step(start + Duration::from_millis(600), scan("TAG-1"));
+        assert!(matches!(&effects[..], [Effect::Request(_)]));
+
+        // The badge being clocked is not queued behind itself.
+        let effects = machine.step(start + Duration::from_millis(700), scan("TAG-1"));
+        assert!(matches!(
+            &effects[..],
+            [Effect::Ignored {
+                reason: IgnoreReason::Busy,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_second_scan_within_the_cooldown_asks_first() {
+        let mut machine = Machine::new(Timing {
+            cooldown: Duration::from_secs(60),
+            ..timing()
+        });
+        let start = Instant::now();
+        machine.step(start, scan("TAG-1"));
+        machine.step(start, answer("TAG-1", Ok(response("CLOCK_IN"))));
+        machine.step(start, Input::Reset);
+
+        let effects = machine.step(start + Duration::from_secs(20), scan("TAG-1"));
+        assert!(matches!(
+            &effects[..],
+            [Effect::Confirm { rfid, last_entry: Some(entry) }]
+                if rfid == "TAG-1" && entry == "CLOCK_IN"
+        ));
+        // Unanswered, the question times out without clocking.
+        for _ in 0..4 {
+            assert!(machine.step(start, Input::Tick).is_empty());
+        }
+        assert!(matches!(
+            &machine.step(start, Input::Tick)[..],
+            [Effect::ShowIdle]
+        ));
+        assert!(machine
+            .step(start, Input::Confirmed("TAG-1".to_string()))
+            .is_empty());
+
+        // Confirmed, the badge is clocked out.
+        machine.step(start + Duration::from_secs(30), scan("TAG-1"));
+        let effects = machine.step(start, Input::Confirmed("TAG-1".to_string()));
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-1"));
+
+        // Buffered clockings count too; after the cooldown the badge clocks right away.
+        machine.step(
+            start + Duration::from_secs(30),
+            answer("TAG-1", Err(ApiError::Timeout)),
+        );
+        machine.step(start, Input::Reset);
+        let effects = machine.step(start + Duration::from_secs(40), scan("TAG-1"));
+        assert!(matches!(
+            &effects[..],
+            [Effect::Confirm {
+                last_entry: None,
+                ..
+            }]
+        ));
+        machine.step(start, Input::Reset);
+        let effects = machine.step(start + Duration::from_secs(91), scan("TAG-1"));
+        assert!(matches!(&effects[..], [Effect::Request(_)]));
+
+        // A clocking taken back does not count.
+        let later = start + Duration::from_secs(91);
+        machine.step(later, answer("TAG-1", Ok(response("CLOCK_IN"))));
+        machine.step(later, Input::Undone("TAG-1".to_string()));
+        machine.step(later, Input::Reset);
+        let effects = machine.step(later + Duration::from_secs(5), scan("TAG-1"));
+        assert!(matches!(&effects[..], [Effect::Request(_)]));
+    }
+
+    #[test]
+    fn test_new_badge_interrupts_the_confirmation() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+        machine.step(now, answer("TAG-1", Err(ApiError::Conflict)));
+
+        // The same badge waits for its screen to time out; another one takes over at once.
+        assert!(matches!(
+            &machine.step(now, scan("TAG-1"))[..],
+            [Effect::Ignored {
+                reason: IgnoreReason::Busy,
+                ..
+            }]
+        ));
+        let effects = machine.step(now, scan("TAG-2"));
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-2"));
+        assert_eq!(
+            *machine.phase(),
+            Phase::Loading {
+                rfid: "TAG-2".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_scans_while_loading_are_queued_in_order() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+        for rfid in ["TAG-2", "TAG-3", "TAG-2"] {
+            machine.step(now, scan(rfid));
+        }
+        assert_eq!(machine.queued(), 2);
+
+        // While others wait, each answer stays on screen only briefly; then the next badge
+        // in line is sent.  A badge read meanwhile joins the end of the line.
+        let effects = machine.step(now, answer("TAG-1", Ok(response("CLOCK_IN"))));
+        assert!(matches!(effects.last(), Some(Effect::Show { rfid, .. }) if rfid == "TAG-1"));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Request(_))));
+        assert!(matches!(
+            &machine.step(now, scan("TAG-4"))[..],
+            [Effect::Queued(_)]
+        ));
+        for _ in 0..timing().queued_display_seconds {
+            assert!(machine.step(now, Input::Tick).is_empty());
+        }
+        let effects = machine.step(now, Input::Tick);
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-2"));
+
+        machine.step(now, answer("TAG-2", Err(ApiError::Timeout)));
+        for _ in 0..timing().queued_display_seconds {
+            machine.step(now, Input::Tick);
+        }
+        let effects = machine.step(now, Input::Tick);
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-3"));
+        assert_eq!(machine.queued(), 1);
+        machine.step(now, answer("TAG-3", Ok(response("CLOCK_OUT"))));
+        assert!(matches!(machine.phase(), Phase::Showing { rfid, .. } if rfid == "TAG-3"));
+
+        // Leaving the flow reports the badges still waiting.
+        let effects = machine.step(now, Input::Reset);
+        assert!(matches!(
+            &effects[..],
+            [Effect::Ignored {
+                reason: IgnoreReason::Cancelled,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_queued_badges_cut_the_confirmation_short() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+        machine.step(now, answer("TAG-1", Ok(response("CLOCK_IN"))));
+
+        // Nobody waiting: the next badge interrupts at once.  With others in line, it joins
+        // them and the confirmation on screen only stays briefly.
+        machine.step(now, scan("TAG-2"));
+        machine.step(now, scan("TAG-3"));
+        machine.step(now, answer("TAG-2", Ok(response("CLOCK_IN"))));
+        assert_eq!(
+            *machine.phase(),
+            Phase::Showing {
+                rfid: "TAG-2".to_string(),
+                seconds_left: timing().queued_display_seconds
+            }
+        );
+        machine.step(now, Input::Tick);
+        let effects = machine.step(now, Input::Tick);
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-3"));
+
+        // The last one in line gets the full confirmation.
+        machine.step(now, answer("TAG-3", Ok(response("CLOCK_OUT"))));
+        assert!(matches!(
+            machine.phase(),
+            Phase::Showing { seconds_left, .. } if *seconds_left == timing().confirmation_seconds
+        ));
+        machine.step(now, scan("TAG-4"));
+        machine.step(now, answer("TAG-4", Ok(response("CLOCK_IN"))));
+        machine.step(now, scan("TAG-5"));
+        machine.step(now, scan("TAG-6"));
+        assert!(matches!(
+            machine.phase(),
+            Phase::Loading { rfid } if rfid == "TAG-5"
+        ));
+    }
+
+    #[test]
+    fn test_screens_outside_the_flow_hold_the_queue() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+        machine.step(now, answer("TAG-1", Ok(response("CLOCK_IN"))));
+
+        // Project selection for TAG-1: other badges wait, TAG-1 itself is not clocked again.
+        assert!(machine.step(now, Input::Hold).is_empty());
+        assert!(matches!(
+            &machine.step(now, scan("TAG-1"))[..],
+            [Effect::Ignored {
+                reason: IgnoreReason::Busy,
+                ..
+            }]
+        ));
+        for rfid in ["TAG-2", "TAG-3"] {
+            assert!(matches!(
+                &machine.step(now, scan(rfid))[..],
+                [Effect::Queued(_)]
+            ));
+        }
+        for _ in 0..10 {
+            assert!(machine.step(now, Input::Tick).is_empty());
+        }
+
+        // Its result counts like a confirmation, cut short for the badges waiting.
+        machine.step(now, Input::Shown(8));
+        assert_eq!(
+            *machine.phase(),
+            Phase::Showing {
+                rfid: "TAG-1".to_string(),
+                seconds_left: timing().queued_display_seconds
+            }
+        );
+        machine.step(now, Input::Tick);
+        let effects = machine.step(now, Input::Tick);
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-2"));
+
+        // Back on the idle screen early, the rest of the line is clocked at once.
+        machine.step(now, answer("TAG-2", Ok(response("CLOCK_IN"))));
+        machine.step(now, Input::Hold);
+        let effects = machine.step(now, Input::Dismissed);
+        assert!(matches!(&effects[..], [Effect::Request(r)] if r == "TAG-3"));
+        assert_eq!(machine.queued(), 0);
+    }
+
+    #[test]
+    fn test_double_scan_shows_info_instead_of_clocking() {
+        let mut machine = Machine::new(Timing {
+            double_scan_window: Duration::from_millis(1500),
+            ..timing()
+        });
+        let start = Instant::now();
+
+        assert!(matches!(
+            &machine.step(start, scan("TAG-1"))[..],
+            [Effect::Wait { .. }]
+        ));
+        assert!(matches!(
+            &machine.step(start, scan("TAG-2"))[..],
+            [Effect::Queued(_)]
+        ));
+        let effects = machine.step(start + Duration::from_millis(800), scan("TAG-1"));
+        assert!(matches!(&effects[..], [Effect::ShowInfo(r)] if r == "TAG-1"));
+        // The window of the first read elapses without effect.
+        assert!(machine
+            .step(start, Input::WindowElapsed("TAG-1".to_string()))
+            .is_empty());
+
+        // The badge queued behind the balances is clocked once they are closed.
+        assert!(matches!(
+            &machine.step(start, scan("TAG-3"))[..],
+            [Effect::Queued(_)]
+        ));
+        assert_eq!(machine.queued(), 2);
+        let effects = machine.step(start, Input::Dismissed);
+        assert!(matches!(&effects[..], [Effect::Wait { rfid, .. }] if rfid == "TAG-2"));
+        machine.step(start, Input::Reset);
+
+        machine.step(start + Duration::from_secs(5), scan("TAG-1"));
+        let effects = machine.step(start, Input::WindowElapsed("TAG-1".to_string()));
+        assert!(matches!(&effects[..], [Effect::Request(_)]));
+    }
+
+    #[test]
+    fn test_late_answers_are_settled_but_not_shown() {
+        let mut machine = Machine::new(timing());
+        let now = Instant::now();
+        machine.step(now, scan("TAG-1"));
+        machine.step(now, Input::Reset);
+
+        let effects = machine.step(
+            now,
+            answer("TAG-1", Err(ApiError::NetworkError("down".into()))),
+        );
+        assert!(matches!(&effects[0], Effect::Buffer(_)));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Show { .. })));
+        assert_eq!(*machine.phase(), Phase::Idle);
+    }
+
+    #[test]
+    fn test_revoked_badge_is_refused_without_a_request() {
+        let mut machine = Machine::new(timing());
+        machine.set_blocklist(HashSet::from(["TAG-9".to_string()]));
+        let now = Instant::now();
+
+        let effects = machine.step(now, scan("TAG-9"));
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::Request(_) | Effect::Buffer(_))));
+        assert!(effects
+            .iter()
+            .any(|e| matches!(e, Effect::Play(SoundEvent::BadgeBlocked))));
+        assert!(matches!(
+            effects.last(),
+            Some(Effect::Show {
+                outcome: ClockOutcome::Blocked,
+                ..
+            })
+        ));
+
+        // A clocking in progress keeps its screen; the refusal is only heard and logged.
+        machine.step(now, Input::Reset);
+        machine.step(now, scan("TAG-1"));
+        let effects = machine.step(now, scan("TAG-9"));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Show { .. })));
+        assert_eq!(machine.queued(), 0);
+        assert!(matches!(machine.phase(), Phase::Loading { rfid } if rfid == "TAG-1"));
+
+        // Revoked while its request was out: not buffered for the next sync.
+        machine.set_blocklist(HashSet::from(["TAG-1".to_string()]));
+        let effects = machine.step(
+            now,
+            answer("TAG-1", Err(ApiError::NetworkError("down".into()))),
+        );
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Buffer(_))));
+        assert!(effects
+            .iter()
+            .any(|e| matches!(e, Effect::Record(_, ScanOutcome::Blocked))));
+    }
+
+    /// Distinct unknown badges, `gap` apart, each answered with 404 right away.
+    fn unknown_burst(
+        machine: &mut Machine,
+        start: Instant,
+        gap: Duration,
+        count: u32,
+    ) -> Vec<Effect> {
+        let mut effects = Vec::new();
+        for i in 0..count {
+            let now = start + gap * i;
+            let rfid = format!("CARD-{}", i);
+            if !machine.admit(now, &rfid) {
+                continue;
+            }
+            machine.step(now, scan(&rfid));
+            effects = machine.step(
+                now,
+                answer(&rfid, Err(ApiError::NotFound("unknown".into()))),
+            );
+        }
+        effects
+    }
+
+    fn lockout_timing() -> Timing {
+        Timing {
+            max_rejected: 5,
+            rejected_window: Duration::from_secs(10),
+            lockout: Duration::from_secs(30),
+            ..timing()
+        }
+    }
+
+    #[test]
+    fn test_burst_of_unknown_badges_locks_the_reader() {
+        let mut machine = Machine::new(lockout_timing());
+        let start = Instant::now();
+
+        let effects = unknown_burst(&mut machine, start, Duration::from_millis(200), 5);
+        assert!(matches!(
+            effects.last(),
+            Some(Effect::LockedOut { rejected: 5, .. })
+        ));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Show { .. })));
+        assert_eq!(*machine.phase(), Phase::Idle);
+
+        // Every read is ignored until the lockout ends, known badges included.
+        let during = start + Duration::from_secs(29);
+        assert!(machine.locked(during));
+        assert!(!machine.admit(during, "TAG-1"));
+        let after = start + Duration::from_secs(31);
+        assert!(!machine.locked(after));
+        assert!(machine.admit(after, "TAG-1"));
+    }
+
+    #[test]
+    fn test_slow_or_accepted_scans_do_not_lock_the_reader() {
+        let mut machine = Machine::new(lockout_timing());
+        let start = Instant::now();
+
+        // Four per window at most.
+        let effects = unknown_burst(&mut machine, start, Duration::from_millis(2600), 20);
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::LockedOut { .. })));
+        assert!(!machine.locked(start + Duration::from_secs(50)));
+
+        // Known badges do not count, however fast.
+        let mut machine = Machine::new(lockout_timing());
+        for i in 0..20 {
+            let rfid = format!("TAG-{}", i);
+            let effects = machine.step(start, scan(&rfid));
+            assert!(matches!(&effects[..], [Effect::Request(_)]));
+            machine.step(start, answer(&rfid, Ok(response("CLOCK_IN"))));
+            machine.step(start, Input::Reset);
+        }
+        assert!(!machine.locked(start));
+    }
+
+    #[test]
+    fn test_lockout_drops_the_queue_and_counts_revoked_badges() {
+        let mut machine = Machine::new(lockout_timing());
+        machine.set_blocklist(HashSet::from(["GONE".to_string()]));
+        let now = Instant::now();
+
+        // Four revoked scans, then an unknown badge with two more waiting behind it.
+        for _ in 0..4 {
+            machine.step(now, scan("GONE"));
+        }
+        machine.step(now, scan("CARD-1"));
+        machine.step(now, scan("TAG-1"));
+        machine.step(now, scan("TAG-2"));
+        assert_eq!(machine.queued(), 2);
+
+        let effects = machine.step(now, answer("CARD-1", Err(ApiError::NotFound("x".into()))));
+        assert!(!effects.iter().any(|e| matches!(e, Effect::Request(_))));
+        assert_eq!(
+            effects
+                .iter()
+                .filter(|e| matches!(
+                    e,
+                    Effect::Ignored {
+                        reason: IgnoreReason::Cancelled,
+                        ..
+                    }
+                ))
+                .count(),
+            2
+        );
+        assert!(matches!(effects.last(), Some(Effect::LockedOut { .. })));
+        assert_eq!(machine.queued(), 0);
+        assert_eq!(*machine.phase(), Phase::Idle);
+    }
+
+    fn random_result(rng: &mut StdRng) -> Result<ClockResponse, ApiError> {
+        match rng.gen_range(0..6) {
+            0 => Ok(response("CLOCK_IN")),
+            1 => Ok(response("CLOCK_OUT")),
+            2 => Err(ApiError::Timeout),
+            3 => Err(ApiError::NetworkError("unreachable".to_string())),
+            4 => Err(ApiError::NotFound("unknown".to_string())),
+            _ => Err(ApiError::Conflict),
+        }
+    }
+
+    /// Random scans, answers, elapsed windows and ticks; `check` sees every step.
+    fn run_random(seed: u64, mut check: impl FnMut(&Machine, &Input, &[Effect])) -> Machine {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let window = if seed.is_multiple_of(2) { 0 } else { 1200 };
+        let mut machine = Machine::new(Timing {
+            double_scan_window: Duration::from_millis(window),
+            cooldown: Duration::from_secs(30),
+            max_rejected: 3,
+            rejected_window: Duration::from_secs(5),
+            lockout: Duration::from_secs(3),
+            ..timing()
+        });
+        machine.set_blocklist(HashSet::from(["TAG-3".to_string()]));
+        let mut now = Instant::now();
+        let mut requests: Vec<String> = Vec::new();
+        let mut windows: Vec<String> = Vec::new();
+
+        for _ in 0..300 {
+            now += Duration::from_millis(rng.gen_range(0..1500));
+            let input = match rng.gen_range(0..7) {
+                0 | 1 => {
+                    let rfid = format!("TAG-{}", rng.gen_range(0..4));
+                    if !machine.admit(now, &rfid) {
+                        continue;
+                    }
+                    Input::Scan(rfid)
+                }
+                2 if !requests.is_empty() => Input::Response {
+                    rfid: requests.remove(0),
+                    result: random_result(&mut rng),
+                },
+                3 if !windows.is_empty() => Input::WindowElapsed(windows.remove(0)),
+                4 => match machine.phase() {
+                    Phase::Confirming { rfid, .. } => Input::Confirmed(rfid.clone()),
+                    Phase::Info { .. } => Input::Dismissed,
+                    _ => Input::Tick,
+                },
+                // The screen leaves the flow for a while (the badge PIN prompt, the project
+                // selection or taking back a clocking) and comes back.
+                5 => match machine.phase() {
+                    Phase::Held { .. } if rng.gen_bool(0.5) => Input::Shown(rng.gen_range(0..5)),
+                    Phase::Held { .. } => Input::Dismissed,
+                    Phase::Idle
+                    | Phase::Loading { .. }
+                    | Phase::Showing { .. }
+                    | Phase::Confirming { .. }
+                        if rng.gen_bool(0.2) =>
+                    {
+                        Input::Hold
+                    }
+                    _ => Input::Tick,
+                },
+                _ => Input::Tick,
+            };
+            let effects = machine.step(now, input.clone());
+            for effect in &effects {
+                match effect {
+                    Effect::Request(rfid) => requests.push(rfid.clone()),
+                    Effect::Wait { rfid, .. } => windows.push(rfid.clone()),
+                    _ => {}
+                }
+            }
+            check(&machine, &input, &effects);
+        }
+
+        // Let every pending answer and window arrive, and every queued badge get its turn.
+        loop {
+            let input = if let Some(rfid) = windows.pop() {
+                Input::WindowElapsed(rfid)
+            } else if !requests.is_empty() {
+                Input::Response {
+                    rfid: requests.remove(0),
+                    result: random_result(&mut rng),
+                }
+            } else if let Phase::Info { .. } | Phase::Held { .. } = machine.phase() {
+                Input::Dismissed
+            } else if machine.queued() > 0 {
+                Input::Tick
+            } else {
+                break;
+            };
+            let effects = machine.step(now, input.clone());
+            for effect in &effects {
+                match effect {
+                    Effect::Request(rfid) => requests.push(rfid.clone()),
+                    Effect::Wait { rfid, .. } => windows.push(rfid.clone()),
+                    _ => {}
+                }
+            }
+            check(&machine, &input, &effects);
+        }
+        machine
+    }
+
+    #[test]
+    fn test_property_no_scan_is_lost() {
+        for seed in 0..200 {
+            let (mut scans, mut accounted) = (0, 0);
+            let (mut waits, mut windows_ended) = (0, 0);
+            let (mut requests, mut settled) = (0, 0);
+            let (mut queued, mut dequeued, mut cancelled) = (0, 0, 0);
+
+            run_random(seed, |_, input, effects| {
+                let locked = effects
+                    .iter()
+                    .any(|e| matches!(e, Effect::LockedOut { .. }));
+                match input {
+                    Input::Scan(_) => scans += 1,
+                    Input::Response { result, .. } => {
+                        let offline = matches!(
+                            result,
+                            Err(ApiError::Timeout) | Err(ApiError::NetworkError(_))
+                        );
+                        let buffered = effects.iter().any(|e| matches!(e, Effect::Buffer(_)));
+                        assert_eq!(offline, buffered, "seed {}", seed);
+                    }
+                    _ => {}
+                }
+                for effect in effects {
+                    match (input, effect) {
+                        (
+                            _,
+                            Effect::Ignored {
+                                reason: IgnoreReason::Cancelled,
+                                ..
+                            },
+                        ) => {
+                            // Only a reset or a lockout drops the queue.
+                            assert!(
+                                matches!(input, Input::Reset) || locked,
+                                "seed {}: queue cancelled by {:?}",
+                                seed,
+                                input
+                            );
+                            cancelled += 1;
+                        }
+                        (Input::Scan(_), Effect::Request(_) | Effect::Ignored { .. }) => {
+                            accounted += 1
+                        }
+                        (Input::Scan(_), Effect::Wait { .. }) => {
+                            accounted += 1;
+                            waits += 1;
+                        }
+                        (Input::Scan(_), Effect::ShowInfo(_)) => {
+                            accounted += 1;
+                            windows_ended += 1;
+                        }
+                        (Input::Scan(_), Effect::Queued(_)) => {
+                            accounted += 1;
+                            queued += 1;
+                        }
+                        (Input::Scan(_), Effect::Confirm { .. }) => accounted += 1,
+                        (Input::Scan(_), Effect::Record(_, ScanOutcome::Blocked)) => accounted += 1,
+                        (Input::Tick | Input::Dismissed, Effect::Request(_)) => dequeued += 1,
+                        (Input::Tick | Input::Dismissed, Effect::Wait { .. }) => {
+                            dequeued += 1;
+                            waits += 1;
+                        }
+                        (Input::WindowElapsed(_), Effect::Request(_)) => windows_ended += 1,
+                        _ => {}
+                    }
+                    match effect {
+                        Effect::Request(_) => requests += 1,
+                        Effect::Record(_, ScanOutcome::Blocked) => {}
+                        Effect::Record(..) => settled += 1,
+                        _ => {}
+                    }
+                }
+            });
+
+            // Every scan was clocked, queued, ignored on purpose, questioned, refused as revoked
+            // or turned into an info request, every queued scan got its turn (unless a lockout
+            // dropped it) and every clocking sent was settled.
+            assert_eq!(scans, accounted, "seed {}", seed);
+            assert_eq!(queued, dequeued + cancelled, "seed {}", seed);
+            assert_eq!(waits, windows_ended, "seed {}", seed);
+            assert_eq!(requests, settled, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_property_every_state_returns_to_idle() {
+        let longest = [
+            timing().confirmation_seconds,
+            timing().error_seconds,
+            timing().cooldown_prompt_seconds,
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+        for seed in 0..200 {
+            let mut machine = run_random(seed, |machine, _, _| {
+                if let Phase::Showing { seconds_left, .. }
+                | Phase::Confirming { seconds_left, .. } = machine.phase()
+                {
+                    assert!(*seconds_left <= longest, "seed {}", seed);
+                }
+            });
+            for _ in 0..=longest {
+                machine.step(Instant::now(), Input::Tick);
+            }
+            assert_eq!(*machine.phase(), Phase::Idle, "seed {}", seed);
+        }
+    }
+}
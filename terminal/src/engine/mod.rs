@@ -0,0 +1,649 @@
+pub mod machine;
+
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::api::{ApiClient, ApiError, BlockedBadge, ClockResponse};
+use crate::audio::AudioPlayer;
+use crate::buffer::{EventBuffer, Removal};
+use crate::clock::{Clock, SystemClock};
+use crate::config::AppConfig;
+use crate::maintenance::{ScanLog, ScanOutcome};
+use crate::sync;
+use machine::{ClockOutcome, Effect, IgnoreReason, Input, Machine, Phase, Timing};
+
+/// The backend, as far as the clocking flow needs it.
+pub trait Backend {
+    fn clock_in_out(
+        &self,
+        rfid: &str,
+        terminal_id: &str,
+    ) -> impl Future<Output = Result<ClockResponse, ApiError>> + Send;
+}
+
+impl Backend for ApiClient {
+    fn clock_in_out(
+        &self,
+        rfid: &str,
+        terminal_id: &str,
+    ) -> impl Future<Output = Result<ClockResponse, ApiError>> + Send {
+        ApiClient::clock_in_out(self, rfid, terminal_id)
+    }
+}
+
+/// Where clockings wait while the backend is unreachable.
+pub trait Store {
+    /// Returns the ID of the buffered event.
+    fn push(&self, rfid: &str, terminal_id: &str) -> Result<i64, String>;
+    /// `Ok(Removal::Busy)` while the event may be being synced.
+    fn remove_pending(&self, id: i64) -> Result<Removal, String>;
+    fn pending_count(&self) -> u32;
+}
+
+pub type SharedBuffer = Arc<Mutex<EventBuffer>>;
+
+impl Store for SharedBuffer {
+    fn push(&self, rfid: &str, terminal_id: &str) -> Result<i64, String> {
+        let buf = self.lock().map_err(|e| e.to_string())?;
+        buf.push(rfid, terminal_id).map_err(|e| e.to_string())
+    }
+
+    fn remove_pending(&self, id: i64) -> Result<Removal, String> {
+        let buf = self.lock().map_err(|e| e.to_string())?;
+        buf.remove_pending(id).map_err(|e| e.to_string())
+    }
+
+    fn pending_count(&self) -> u32 {
+        self.lock()
+            .map(|buf| buf.pending_count().unwrap_or(0))
+            .unwrap_or(0)
+    }
+}
+
+/// A clocking that can still be taken back from its confirmation screen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndoTarget {
+    /// Booked by the backend as this time entry.
+    Booked(String),
+    /// Still waiting in the offline buffer as this event.
+    Buffered(i64),
+}
+
+/// Scan, API, buffer and sync logic shared by the GUI and the headless mode.  Decisions are
+/// made by the [`Machine`]; the engine carries out the effects that need no screen (buffer,
+/// sound, scan log) and hands the rest back to the caller.
+pub struct Engine<A = ApiClient, S = SharedBuffer, C = SystemClock> {
+    pub api: A,
+    pub terminal_id: String,
+    pub buffer: S,
+    pub audio: AudioPlayer,
+    pub scan_log: ScanLog,
+    /// Number of buffered events waiting to be synced.
+    pub pending_count: u32,
+    /// Whether the last clocking reached the backend.
+    pub is_online: bool,
+    clock: C,
+    machine: Machine,
+    /// The badge and event ID of the last clocking buffered by the machine.
+    last_buffered: Option<(String, i64)>,
+}
+
+impl Engine {
+    /// Opens the offline buffer, falling back to an in-memory one if the file cannot be used.
+    pub fn new(config: &AppConfig) -> Self {
+        let buffer = EventBuffer::new(&config.offline.buffer_path, config.offline.max_buffer_size)
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Cannot open event buffer at '{}': {}. Falling back to in-memory.",
+                    config.offline.buffer_path, e
+                );
+                EventBuffer::new(":memory:", config.offline.max_buffer_size)
+                    .expect("in-memory buffer must always succeed")
+            });
+        let blocklist = buffer.cached_blocklist().unwrap_or_else(|e| {
+            warn!("Cannot read the cached blocklist: {}", e);
+            Vec::new()
+        });
+        let mut engine = Self::with_parts(
+            config,
+            ApiClient::new(&config.api),
+            Arc::new(Mutex::new(buffer)),
+            SystemClock,
+        );
+        engine.set_blocklist(blocklist);
+        engine
+    }
+
+    /// Takes over a reloaded configuration.  The buffer and the audio output stay open.
+    pub fn apply_config(&mut self, config: &AppConfig) {
+        self.api = ApiClient::new(&config.api);
+        self.terminal_id = config.api.terminal_id.clone();
+        self.audio.reconfigure(config.audio.clone(), &config.locale);
+        self.machine.set_timing(Timing::new(config));
+    }
+
+    /// The backend request for [`Effect::Request`], to be awaited outside the engine.
+    pub fn clock(
+        &self,
+        rfid: &str,
+    ) -> impl Future<Output = Result<ClockResponse, ApiError>> + Send + 'static {
+        let api = self.api.clone();
+        let rfid = rfid.to_string();
+        let terminal_id = self.terminal_id.clone();
+        async move { api.clock_in_out(&rfid, &terminal_id).await }
+    }
+
+    /// The backend request that takes back a booked clocking, to be awaited outside the
+    /// engine.
+    pub fn undo(
+        &self,
+        entry_id: &str,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send + 'static {
+        let api = self.api.clone();
+        let entry_id = entry_id.to_string();
+        let terminal_id = self.terminal_id.clone();
+        async move { api.undo_clocking(&entry_id, &terminal_id).await }
+    }
+
+    /// The backend request for the revoked badges, to be awaited outside the engine.
+    pub fn fetch_blocklist(
+        &self,
+    ) -> impl Future<Output = Result<Vec<BlockedBadge>, ApiError>> + Send + 'static {
+        let api = self.api.clone();
+        let terminal_id = self.terminal_id.clone();
+        async move { api.get_blocklist(&terminal_id).await }
+    }
+
+    /// The backend request for this terminal's remote configuration document, to be awaited
+    /// outside the engine.
+    pub fn fetch_remote_config(
+        &self,
+    ) -> impl Future<Output = Result<toml::Table, ApiError>> + Send + 'static {
+        let api = self.api.clone();
+        let terminal_id = self.terminal_id.clone();
+        async move { api.get_remote_config(&terminal_id).await }
+    }
+
+    /// Takes over a fetched blocklist and caches it, so revoked badges stay refused after a
+    /// restart without network.
+    pub fn update_blocklist(&mut self, blocklist: Vec<BlockedBadge>) {
+        let tags: Vec<String> = blocklist.into_iter().map(|b| b.rfid_tag_id).collect();
+        match self.buffer.lock() {
+            Ok(mut buf) => {
+                if let Err(e) = buf.replace_cached_blocklist(&tags) {
+                    warn!("Cannot cache the blocklist: {}", e);
+                }
+            }
+            Err(e) => warn!("Cannot cache the blocklist: {}", e),
+        }
+        info!("Blocklist updated: {} revoked badge(s)", tags.len());
+        self.set_blocklist(tags);
+    }
+
+    /// The alert for [`Effect::LockedOut`], to be awaited outside the engine.
+    pub fn report_lockout(
+        &self,
+        rejected: u32,
+        window: Duration,
+        lockout: Duration,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send + 'static {
+        let api = self.api.clone();
+        let terminal_id = self.terminal_id.clone();
+        async move {
+            api.report_scan_alert(&terminal_id, rejected, window.as_secs(), lockout.as_secs())
+                .await
+        }
+    }
+
+    /// Replays the buffered events; see [`sync::sync_buffered_events`].
+    pub fn sync(&self) -> impl Future<Output = Option<u32>> + Send + 'static {
+        sync::sync_buffered_events(self.api.clone(), Arc::clone(&self.buffer))
+    }
+}
+
+impl<A: Backend, S: Store, C: Clock> Engine<A, S, C> {
+    pub fn with_parts(config: &AppConfig, api: A, buffer: S, clock: C) -> Self {
+        let pending_count = buffer.pending_count();
+        Self {
+            api,
+            terminal_id: config.api.terminal_id.clone(),
+            buffer,
+            audio: AudioPlayer::new(config.audio.clone(), &config.locale),
+            scan_log: ScanLog::new(config.maintenance.recent_scans),
+            pending_count,
+            is_online: true,
+            clock,
+            machine: Machine::new(Timing::new(config)),
+            last_buffered: None,
+        }
+    }
+
+    pub fn phase(&self) -> &Phase {
+        self.machine.phase()
+    }
+
+    pub fn set_blocklist(&mut self, tags: Vec<String>) {
+        self.machine
+            .set_blocklist(tags.into_iter().collect::<HashSet<_>>());
+    }
+
+    /// Whether `rfid` has been revoked; such scans go to the machine before anything else.
+    pub fn is_blocked(&self, rfid: &str) -> bool {
+        self.machine.is_blocked(rfid)
+    }
+
+    /// Badges waiting for the current clocking to finish.
+    pub fn queued(&self) -> usize {
+        self.machine.queued()
+    }
+
+    /// The single entry for tag reads, before the caller routes them anywhere (maintenance,
+    /// info, clocking): `None` for a repeated read within `rfid.debounce_ms` and for every
+    /// read while the reader is locked.
+    pub fn read(&mut self, rfid: String) -> Option<String> {
+        if self.machine.admit(self.clock.instant(), &rfid) {
+            Some(rfid)
+        } else {
+            debug!(
+                "Read of {} ignored: repeated, or the reader is locked",
+                rfid
+            );
+            None
+        }
+    }
+
+    /// Feeds `input` to the machine and carries out buffering, sounds and logging.  The
+    /// returned effects are for the caller; those already carried out can be ignored.
+    pub fn handle(&mut self, input: Input) -> Vec<Effect> {
+        let effects = self.machine.step(self.clock.instant(), input);
+        for effect in &effects {
+            self.apply(effect);
+        }
+        effects
+    }
+
+    /// Like [`Engine::handle`], but also sends requests to the backend and settles their
+    /// answers.  For callers that can wait, such as the headless mode.
+    pub async fn run(&mut self, input: Input) -> Vec<Effect> {
+        let mut effects = self.handle(input);
+        let mut next = 0;
+        while next < effects.len() {
+            if let Effect::Request(rfid) = &effects[next] {
+                let rfid = rfid.clone();
+                let result = self.api.clock_in_out(&rfid, &self.terminal_id).await;
+                let settled = self.handle(Input::Response { rfid, result });
+                effects.extend(settled);
+            }
+            next += 1;
+        }
+        effects
+    }
+
+    /// What the "Rückgängig" button on the screen for `outcome` takes back.  Buffered
+    /// clockings can only be taken back right after the machine buffered them for `rfid`.
+    pub fn undo_target(
+        &mut self,
+        rfid: Option<&str>,
+        outcome: &ClockOutcome,
+    ) -> Option<UndoTarget> {
+        let buffered = self.last_buffered.take();
+        match outcome {
+            ClockOutcome::Accepted(response) => response.entry_id.clone().map(UndoTarget::Booked),
+            ClockOutcome::Buffered => buffered
+                .filter(|(buffered_rfid, _)| Some(buffered_rfid.as_str()) == rfid)
+                .map(|(_, id)| UndoTarget::Buffered(id)),
+            _ => None,
+        }
+    }
+
+    /// Deletes a buffered clocking.  A buffer that cannot be read counts as busy, so the
+    /// screen offers to try again.
+    pub fn remove_buffered(&mut self, id: i64) -> Removal {
+        let removed = self.buffer.remove_pending(id).unwrap_or_else(|e| {
+            warn!("Cannot remove buffered event {}: {}", id, e);
+            Removal::Busy
+        });
+        self.refresh_pending_count();
+        removed
+    }
+
+    /// Sound and scan log for a clocking settled outside the machine (PIN clockings).  A
+    /// buffered clocking must already be in the buffer.
+    pub fn settle(&mut self, log_id: &str, outcome: ClockOutcome) -> ClockOutcome {
+        self.last_buffered = None;
+        for effect in machine::feedback(log_id, &outcome) {
+            self.apply(&effect);
+        }
+        outcome
+    }
+
+    fn apply(&mut self, effect: &Effect) {
+        match effect {
+            Effect::Buffer(rfid) => match self.buffer.push(rfid, &self.terminal_id) {
+                Ok(id) => self.last_buffered = Some((rfid.clone(), id)),
+                Err(e) => {
+                    self.last_buffered = None;
+                    warn!("Cannot buffer the clocking of {}: {}", rfid, e);
+                }
+            },
+            Effect::Play(sound) => self.audio.play(*sound),
+            Effect::Record(log_id, outcome) => {
+                self.scan_log.record(log_id, *outcome);
+                match outcome {
+                    ScanOutcome::ClockIn | ScanOutcome::ClockOut => self.is_online = true,
+                    ScanOutcome::Offline => self.is_online = false,
+                    _ => {}
+                }
+                match outcome {
+                    // The next scan will succeed.
+                    ScanOutcome::Conflict => warn!(
+                        "Scan conflict for {}: another terminal processed the same employee simultaneously",
+                        log_id
+                    ),
+                    ScanOutcome::Error => warn!("{}: {}", log_id, outcome.label()),
+                    ScanOutcome::Blocked => warn!(
+                        "Security event: revoked badge {} scanned at terminal {}",
+                        log_id, self.terminal_id
+                    ),
+                    _ => info!("{}: {}", log_id, outcome.label()),
+                }
+                // Refresh pending count in case a previous sync cleared some events.
+                self.refresh_pending_count();
+            }
+            Effect::Confirm { rfid, .. } => {
+                info!(
+                    "{} was clocked moments ago, asking before clocking it again",
+                    rfid
+                )
+            }
+            Effect::LockedOut {
+                rejected,
+                window,
+                lockout,
+            } => warn!(
+                "Security event: {} unknown or revoked badges within {}s at terminal {}, \
+                 ignoring the reader for {}s",
+                rejected,
+                window.as_secs(),
+                self.terminal_id,
+                lockout.as_secs()
+            ),
+            Effect::Queued(rfid) => info!(
+                "{} queued behind the current clocking ({} waiting)",
+                rfid,
+                self.machine.queued()
+            ),
+            Effect::Ignored {
+                rfid,
+                reason: IgnoreReason::Cancelled,
+            } => warn!(
+                "Queued scan of {} dropped: the screen left the clocking flow",
+                rfid
+            ),
+            Effect::Ignored { rfid, reason } => debug!("Scan of {} ignored: {:?}", rfid, reason),
+            _ => {}
+        }
+    }
+
+    pub fn refresh_pending_count(&mut self) {
+        self.pending_count = self.buffer.pending_count();
+    }
+
+    pub fn sync_complete(&mut self, count: u32) {
+        if count > 0 {
+            info!("Synced {} buffered events", count);
+            self.refresh_pending_count();
+            if self.pending_count == 0 {
+                self.is_online = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::machine::tests::response;
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::time::Instant;
+
+    /// Answers clockings from a script; an empty script means the backend is down.
+    #[derive(Default)]
+    struct ScriptedApi {
+        answers: RefCell<VecDeque<Result<ClockResponse, ApiError>>>,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl Backend for ScriptedApi {
+        fn clock_in_out(
+            &self,
+            rfid: &str,
+            _terminal_id: &str,
+        ) -> impl Future<Output = Result<ClockResponse, ApiError>> + Send {
+            self.calls.borrow_mut().push(rfid.to_string());
+            let answer = self
+                .answers
+                .borrow_mut()
+                .pop_front()
+                .unwrap_or(Err(ApiError::Timeout));
+            async move { answer }
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryStore {
+        events: RefCell<Vec<(String, String)>>,
+    }
+
+    impl Store for MemoryStore {
+        /// IDs are positions, good enough for a test or two.
+        fn push(&self, rfid: &str, terminal_id: &str) -> Result<i64, String> {
+            let mut events = self.events.borrow_mut();
+            events.push((rfid.to_string(), terminal_id.to_string()));
+            Ok(events.len() as i64)
+        }
+
+        fn remove_pending(&self, id: i64) -> Result<Removal, String> {
+            let mut events = self.events.borrow_mut();
+            if id < 1 || id as usize > events.len() {
+                return Ok(Removal::AlreadySynced);
+            }
+            events.remove(id as usize - 1);
+            Ok(Removal::Removed)
+        }
+
+        fn pending_count(&self) -> u32 {
+            self.events.borrow().len() as u32
+        }
+    }
+
+    /// Wall and monotonic time that only move when told to.
+    struct ManualClock(Mutex<(DateTime<Utc>, Instant)>);
+
+    impl ManualClock {
+        fn new() -> Self {
+            Self(Mutex::new((Utc::now(), Instant::now())))
+        }
+
+        fn advance(&self, by: Duration) {
+            let mut now = self.0.lock().unwrap();
+            now.0 += by;
+            now.1 += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0.lock().unwrap().0
+        }
+
+        fn instant(&self) -> Instant {
+            self.0.lock().unwrap().1
+        }
+    }
+
+    fn engine(
+        answers: Vec<Result<ClockResponse, ApiError>>,
+    ) -> Engine<ScriptedApi, MemoryStore, ManualClock> {
+        let mut config = AppConfig::default();
+        config.api.terminal_id = "lager-01".to_string();
+        config.audio.enabled = false;
+        config.display.idle_timeout_seconds = 2;
+        let api = ScriptedApi {
+            answers: RefCell::new(answers.into()),
+            ..Default::default()
+        };
+        Engine::with_parts(&config, api, MemoryStore::default(), ManualClock::new())
+    }
+
+    fn scan(engine: &mut Engine<ScriptedApi, MemoryStore, ManualClock>, rfid: &str) -> Vec<Effect> {
+        match engine.read(rfid.to_string()) {
+            Some(rfid) => tokio_test::block_on(engine.run(Input::Scan(rfid))),
+            None => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_offline_scans_are_buffered_until_the_backend_answers() {
+        let mut engine = engine(vec![Err(ApiError::NetworkError("down".to_string()))]);
+
+        let effects = scan(&mut engine, "TAG-1");
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::Show {
+                outcome: ClockOutcome::Buffered,
+                ..
+            }
+        )));
+        assert_eq!(
+            *engine.buffer.events.borrow(),
+            vec![("TAG-1".to_string(), "lager-01".to_string())]
+        );
+        assert_eq!(engine.pending_count, 1);
+        assert!(!engine.is_online);
+        assert_eq!(engine.scan_log.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_revoked_badges_are_never_sent_or_buffered() {
+        // The backend is down: anything that got through would be buffered.
+        let mut engine = engine(Vec::new());
+        engine.set_blocklist(vec!["TAG-1".to_string()]);
+
+        let effects = scan(&mut engine, "TAG-1");
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::Show {
+                outcome: ClockOutcome::Blocked,
+                ..
+            }
+        )));
+        assert!(engine.api.calls.borrow().is_empty());
+        assert!(engine.buffer.events.borrow().is_empty());
+        assert_eq!(engine.scan_log.entries()[0].outcome, ScanOutcome::Blocked);
+    }
+
+    #[test]
+    fn test_scan_burst_locks_the_reader_without_backend_calls() {
+        // Defaults: 10 unknown badges within 60 seconds lock the reader for 120.
+        let answers = (0..10)
+            .map(|_| Err(ApiError::NotFound("unknown".to_string())))
+            .collect();
+        let mut engine = engine(answers);
+
+        let mut locked = false;
+        for i in 0..10 {
+            engine.clock.advance(Duration::from_millis(300));
+            let effects = scan(&mut engine, &format!("CARD-{}", i));
+            locked = effects
+                .iter()
+                .any(|e| matches!(e, Effect::LockedOut { rejected: 10, .. }));
+        }
+        assert!(locked);
+        assert_eq!(engine.api.calls.borrow().len(), 10);
+
+        // A card reader waved at the terminal costs nothing while it is locked.
+        for i in 10..50 {
+            engine.clock.advance(Duration::from_millis(100));
+            assert!(scan(&mut engine, &format!("CARD-{}", i)).is_empty());
+        }
+        assert_eq!(engine.api.calls.borrow().len(), 10);
+
+        engine.clock.advance(Duration::from_secs(120));
+        scan(&mut engine, "TAG-1");
+        assert_eq!(engine.api.calls.borrow().len(), 11);
+    }
+
+    #[test]
+    fn test_undo_targets_the_clocking_just_shown() {
+        let mut engine = engine(vec![Ok(response("CLOCK_IN")), Err(ApiError::Timeout)]);
+
+        scan(&mut engine, "TAG-1");
+        let booked = ClockOutcome::from(Ok(response("CLOCK_IN")));
+        assert_eq!(
+            engine.undo_target(Some("TAG-1"), &booked),
+            Some(UndoTarget::Booked("entry-1".to_string()))
+        );
+
+        // A buffered clocking only for the badge just buffered, and only once.
+        scan(&mut engine, "TAG-2");
+        assert_eq!(
+            engine.undo_target(Some("TAG-2"), &ClockOutcome::Buffered),
+            Some(UndoTarget::Buffered(1))
+        );
+        assert_eq!(
+            engine.undo_target(Some("TAG-2"), &ClockOutcome::Buffered),
+            None
+        );
+        assert_eq!(engine.remove_buffered(1), Removal::Removed);
+        assert_eq!(engine.pending_count, 0);
+        assert_eq!(engine.remove_buffered(1), Removal::AlreadySynced);
+    }
+
+    #[test]
+    fn test_second_scan_after_the_confirmation_asks_before_clocking_out() {
+        let mut engine = engine(vec![Ok(response("CLOCK_IN")), Ok(response("CLOCK_OUT"))]);
+
+        scan(&mut engine, "TAG-1");
+        assert!(engine.is_online);
+        // The same badge is busy while its confirmation is on screen.
+        engine.clock.advance(Duration::from_secs(1));
+        assert!(matches!(
+            &scan(&mut engine, "TAG-1")[..],
+            [Effect::Ignored {
+                reason: IgnoreReason::Busy,
+                ..
+            }]
+        ));
+
+        let ticks: Vec<Effect> = (0..3).flat_map(|_| engine.handle(Input::Tick)).collect();
+        assert!(matches!(&ticks[..], [Effect::ShowIdle]));
+
+        // Once the confirmation is gone, the same badge asks within the cooldown.
+        engine.clock.advance(Duration::from_secs(2));
+        assert!(matches!(
+            &scan(&mut engine, "TAG-1")[..],
+            [Effect::Confirm { last_entry: Some(entry), .. }] if entry == "CLOCK_IN"
+        ));
+        assert_eq!(engine.api.calls.borrow().len(), 1);
+        tokio_test::block_on(engine.run(Input::Confirmed("TAG-1".to_string())));
+        assert_eq!(*engine.api.calls.borrow(), vec!["TAG-1", "TAG-1"]);
+        assert!(engine.buffer.events.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_debounce_uses_the_injected_clock() {
+        let mut engine = engine(Vec::new());
+        assert!(engine.read("TAG-1".to_string()).is_some());
+
+        engine.clock.advance(Duration::from_millis(200));
+        assert!(engine.read("TAG-1".to_string()).is_none());
+        engine.clock.advance(Duration::from_millis(400));
+        assert!(engine.read("TAG-1".to_string()).is_some());
+    }
+}
@@ -0,0 +1,287 @@
+use log::{debug, info, warn};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+use crate::config::AppConfig;
+use crate::engine::machine::{Effect, Input};
+use crate::engine::Engine;
+use crate::maintenance;
+use crate::reload;
+use crate::rfid::RfidReader;
+
+/// How often the reader is polled, as in the GUI.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs the terminal without a window: scans are clocked with audio feedback only, buffered
+/// while the backend is unreachable and synced in the background.  Configuration changes,
+/// local or remote, are applied like in the GUI.  Returns on SIGINT or
+/// SIGTERM.
+pub fn run(config: AppConfig, config_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(Headless::new(config, config_path).run())
+}
+
+struct Headless {
+    config: AppConfig,
+    config_path: PathBuf,
+    engine: Engine,
+    reader: RfidReader,
+}
+
+impl Headless {
+    fn new(mut config: AppConfig, config_path: PathBuf) -> Self {
+        // Info mode needs the screen; clock every scan right away.
+        config.info.enabled = false;
+        let engine = Engine::new(&config);
+        let reader = RfidReader::new(&config.rfid.input_device);
+        Self {
+            config,
+            config_path,
+            engine,
+            reader,
+        }
+    }
+
+    async fn run(mut self) -> Result<(), Box<dyn Error>> {
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut poll = interval(POLL_INTERVAL);
+        let mut sync_tick = sync_interval(&self.config);
+        let mut blocklist_tick = blocklist_interval(&self.config);
+        let mut remote_tick = remote_interval(&self.config);
+        let mut watch = interval(reload::WATCH_INTERVAL);
+        let mut last_modified = reload::modified(&self.config_path);
+
+        info!(
+            "Running headless as terminal {}, {} buffered event(s) pending",
+            self.engine.terminal_id, self.engine.pending_count
+        );
+        if self.config.pin.require_with_badge {
+            warn!(
+                "pin.require_with_badge is set, but no PIN can be entered headless; \
+                 badges are refused"
+            );
+        }
+
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    while let Some(tag) = self.reader.poll() {
+                        if let Some(tag) = self.engine.read(tag) {
+                            self.handle_scan(tag).await;
+                        }
+                    }
+                }
+                _ = sync_tick.tick() => self.sync().await,
+                _ = blocklist_tick.tick() => self.refresh_blocklist().await,
+                _ = remote_tick.tick(), if self.config.remote.enabled => {
+                    if self.refresh_remote_config().await {
+                        sync_tick = sync_interval(&self.config);
+                        blocklist_tick = blocklist_interval(&self.config);
+                    }
+                }
+                _ = watch.tick() => {
+                    if reload::modified(&self.config_path) != last_modified {
+                        info!("Configuration file changed, reloading configuration");
+                        last_modified = reload::modified(&self.config_path);
+                        self.reload();
+                        sync_tick = sync_interval(&self.config);
+                        blocklist_tick = blocklist_interval(&self.config);
+                        remote_tick = remote_interval(&self.config);
+                    }
+                }
+                _ = hangup.recv() => {
+                    info!("SIGHUP received, reloading configuration");
+                    last_modified = reload::modified(&self.config_path);
+                    self.reload();
+                    sync_tick = sync_interval(&self.config);
+                    blocklist_tick = blocklist_interval(&self.config);
+                    remote_tick = remote_interval(&self.config);
+                }
+                _ = tokio::signal::ctrl_c() => break,
+                _ = terminate.recv() => break,
+            }
+        }
+
+        info!(
+            "Headless terminal stopped, {} buffered event(s) pending",
+            self.engine.pending_count
+        );
+        Ok(())
+    }
+
+    async fn handle_scan(&mut self, tag: String) {
+        // Revoked badges are refused first; the engine sounds and logs the security event.
+        if self.engine.is_blocked(&tag) {
+            let effects = self.engine.handle(Input::Scan(tag));
+            self.report_lockout(&effects).await;
+            self.engine.handle(Input::Reset);
+            return;
+        }
+        // Maintenance needs the screen; admin badges never reach the backend.
+        if maintenance::is_admin_badge(&self.config.maintenance, &tag) {
+            info!("Admin badge scanned; the maintenance screen is not available headless");
+            return;
+        }
+        if self.config.pin.require_with_badge {
+            warn!(
+                "Refused RFID {}: a PIN is required, which cannot be entered headless",
+                tag
+            );
+            self.engine.audio.play_error();
+            return;
+        }
+
+        // The outcome is logged by the engine.
+        let effects = self.engine.run(Input::Scan(tag)).await;
+        if let Some(Effect::Confirm { rfid, .. }) = effects.first() {
+            warn!(
+                "Refused RFID {}: clocked moments ago, and the second clocking cannot be \
+                 confirmed headless",
+                rfid
+            );
+            self.engine.audio.play_error();
+        }
+        self.report_lockout(&effects).await;
+        // There is no confirmation screen to wait for.
+        self.engine.handle(Input::Reset);
+    }
+
+    /// Alerts the backend if the scan locked the reader; the engine ignores reads until the
+    /// lockout ends.
+    async fn report_lockout(&self, effects: &[Effect]) {
+        for effect in effects {
+            if let Effect::LockedOut {
+                rejected,
+                window,
+                lockout,
+            } = effect
+            {
+                if let Err(err) = self
+                    .engine
+                    .report_lockout(*rejected, *window, *lockout)
+                    .await
+                {
+                    warn!("Cannot report the reader lockout to the backend: {}", err);
+                }
+            }
+        }
+    }
+
+    async fn sync(&mut self) {
+        if self.engine.pending_count == 0 {
+            return;
+        }
+        let count = self.engine.sync().await.unwrap_or(0);
+        self.engine.sync_complete(count);
+    }
+
+    async fn refresh_blocklist(&mut self) {
+        match self.engine.fetch_blocklist().await {
+            Ok(blocklist) => self.engine.update_blocklist(blocklist),
+            // Keep the cached blocklist; the next tick tries again.
+            Err(err) => warn!("Blocklist refresh failed: {}", err),
+        }
+    }
+
+    /// Fetches the remote configuration document and applies it; `true` if the running
+    /// configuration changed.  On failure the last known-good document stays in effect.
+    async fn refresh_remote_config(&mut self) -> bool {
+        let document = match self.engine.fetch_remote_config().await {
+            Ok(document) => document,
+            Err(err) => {
+                warn!("Remote configuration fetch failed: {}", err);
+                return false;
+            }
+        };
+        match reload::merge_remote(&self.config_path, &self.config, &document) {
+            Ok(config) => {
+                let applied = self.apply(config);
+                if applied {
+                    info!("Applied remote configuration");
+                }
+                applied
+            }
+            Err(e) => {
+                warn!(
+                    "Rejected remote configuration, keeping the running one: {}",
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    /// Applies the configuration files like the GUI does on a change or SIGHUP.
+    fn reload(&mut self) {
+        match reload::load(&self.config_path) {
+            Ok(config) => {
+                if self.apply(config) {
+                    info!("Configuration reloaded from {}", self.config_path.display());
+                } else {
+                    debug!("Configuration file unchanged");
+                }
+            }
+            Err(rejected) => {
+                warn!(
+                    "Rejected configuration file {}, keeping the running one: {}",
+                    self.config_path.display(),
+                    rejected.error
+                );
+                if let Some(file) = &rejected.file {
+                    for line in reload::diff(&self.config, file) {
+                        warn!("  {}", line);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Switches to `config` without a restart; `false` if nothing would change.  Keys only
+    /// read at startup keep their running value and are logged.
+    fn apply(&mut self, mut config: AppConfig) -> bool {
+        config.info.enabled = false;
+        if reload::unchanged(&self.config, &config) {
+            return false;
+        }
+        let deferred = self.config.restart_required_changes(&config);
+        if !deferred.is_empty() {
+            warn!(
+                "Configuration changes take effect after a restart: {}",
+                deferred.join(", ")
+            );
+        }
+        self.config = config.with_startup_values(&self.config);
+        self.engine.apply_config(&self.config);
+        true
+    }
+}
+
+fn sync_interval(config: &AppConfig) -> Interval {
+    let mut tick = interval(Duration::from_secs(
+        config.offline.sync_interval_seconds.max(5),
+    ));
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tick
+}
+
+/// Fires right away, so the blocklist is fetched at startup.
+fn blocklist_interval(config: &AppConfig) -> Interval {
+    let mut tick = interval(Duration::from_secs(
+        config.rfid.blocklist_refresh_minutes.max(1) * 60,
+    ));
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tick
+}
+
+/// Fires right away, so the remote document is fetched at startup.
+fn remote_interval(config: &AppConfig) -> Interval {
+    let mut tick = interval(Duration::from_secs(
+        config.remote.refresh_minutes.max(1) * 60,
+    ));
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tick
+}
@@ -0,0 +1,344 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::config::{lookup, AppConfig, SECRET_KEYS};
+use crate::remote;
+
+/// Environment variables with this prefix override single keys: `ZT_API__BASE_URL` sets
+/// `api.base_url`.  `__` separates the levels.
+pub const ENV_PREFIX: &str = "ZT_";
+
+/// Where a configuration value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    /// Settings shared by all terminals of a site.
+    SiteFile(PathBuf),
+    /// Settings of this terminal (`CONFIG_PATH`).
+    DeviceFile(PathBuf),
+    /// The environment variable of that name.
+    Env(String),
+    /// The remote configuration document.
+    Remote,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::SiteFile(path) => write!(f, "site file {}", path.display()),
+            Source::DeviceFile(path) => write!(f, "device file {}", path.display()),
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::Remote => write!(f, "remote"),
+        }
+    }
+}
+
+/// Keys set by one source.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub source: Source,
+    pub table: toml::Table,
+}
+
+/// `$SITE_CONFIG_PATH`, or `site.toml` next to the device file.
+pub fn site_path(device_path: &Path) -> PathBuf {
+    std::env::var("SITE_CONFIG_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| device_path.with_file_name("site.toml"))
+}
+
+/// Site file (if present), device file and environment overrides, lowest priority first.
+/// Built-in defaults are not a layer: they fill in whatever no layer sets.
+pub fn load(device_path: &Path) -> Result<Vec<Layer>, Box<dyn Error>> {
+    load_from(device_path, &site_path(device_path), std::env::vars())
+}
+
+fn load_from(
+    device_path: &Path,
+    site_path: &Path,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<Layer>, Box<dyn Error>> {
+    let mut layers = Vec::new();
+    if site_path.exists() {
+        layers.push(Layer {
+            source: Source::SiteFile(site_path.to_path_buf()),
+            table: AppConfig::load_table(site_path)?,
+        });
+    }
+    layers.push(Layer {
+        source: Source::DeviceFile(device_path.to_path_buf()),
+        table: AppConfig::load_table(device_path)?,
+    });
+
+    // Values are typed like the key they replace, so `ZT_API__TERMINAL_ID=0042` stays a string.
+    let mut typed = toml::Table::try_from(AppConfig::default())?;
+    merge_into(&mut typed, &merge(&layers));
+
+    let mut vars: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    vars.sort();
+    for (name, raw) in vars {
+        let Some(path) = env_key(&name) else {
+            continue;
+        };
+        let value = match lookup(&typed, &path.join(".")) {
+            Some(toml::Value::String(_)) => toml::Value::String(raw),
+            _ => parse_value(&raw).unwrap_or(toml::Value::String(raw)),
+        };
+        let mut table = toml::Table::new();
+        insert_path(&mut table, &path, value);
+        layers.push(Layer {
+            source: Source::Env(name),
+            table,
+        });
+    }
+    Ok(layers)
+}
+
+/// Dotted path segments of `ZT_SECTION__KEY`, lowercased.
+fn env_key(name: &str) -> Option<Vec<String>> {
+    let segments: Vec<String> = name
+        .strip_prefix(ENV_PREFIX)?
+        .split("__")
+        .map(str::to_lowercase)
+        .collect();
+    if segments.iter().any(String::is_empty) {
+        None
+    } else {
+        Some(segments)
+    }
+}
+
+fn parse_value(raw: &str) -> Option<toml::Value> {
+    let mut table: toml::Table = format!("value = {}", raw).parse().ok()?;
+    table.remove("value")
+}
+
+fn insert_path(table: &mut toml::Table, path: &[String], value: toml::Value) {
+    let (last, parents) = path
+        .split_last()
+        .expect("env_key never returns an empty path");
+    let mut current = table;
+    for segment in parents {
+        current = current
+            .entry(segment.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .expect("only tables are inserted on the way down");
+    }
+    current.insert(last.clone(), value);
+}
+
+/// All layers deep-merged, later layers winning.
+pub fn merge(layers: &[Layer]) -> toml::Table {
+    let mut merged = toml::Table::new();
+    for layer in layers {
+        merge_into(&mut merged, &layer.table);
+    }
+    merged
+}
+
+fn merge_into(target: &mut toml::Table, source: &toml::Table) {
+    for (key, value) in source {
+        match (target.get_mut(key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(section)) => {
+                merge_into(existing, section)
+            }
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// The merged local layers for the device file `device_path`.
+pub fn local_table(device_path: &Path) -> Result<toml::Table, Box<dyn Error>> {
+    Ok(merge(&load(device_path)?))
+}
+
+/// The effective configuration as TOML, every value annotated with its source.  Secrets
+/// are masked.
+pub fn describe(device_path: &Path) -> Result<String, Box<dyn Error>> {
+    describe_layers(&load(device_path)?)
+}
+
+fn describe_layers(layers: &[Layer]) -> Result<String, Box<dyn Error>> {
+    let local = merge(layers);
+    let local_values = toml::Table::try_from(AppConfig::from_table(local.clone())?)?;
+    let effective = toml::Table::try_from(remote::effective_config_from_table(local)?)?;
+
+    let source_of = |path: &str| -> Source {
+        // Only the remote document changes values after the local layers are merged.
+        if lookup(&local_values, path) != lookup(&effective, path) {
+            return Source::Remote;
+        }
+        layers
+            .iter()
+            .rev()
+            .find(|layer| lookup(&layer.table, path).is_some())
+            .map(|layer| layer.source.clone())
+            .unwrap_or(Source::Default)
+    };
+
+    let mut out = String::new();
+    for (section, value) in &effective {
+        if let toml::Value::Table(table) = value {
+            write_table(&mut out, table, section, &source_of)?;
+        }
+    }
+    Ok(out)
+}
+
+fn write_table(
+    out: &mut String,
+    table: &toml::Table,
+    prefix: &str,
+    source_of: &dyn Fn(&str) -> Source,
+) -> fmt::Result {
+    let mut values = table.iter().filter(|(_, v)| !v.is_table()).peekable();
+    if values.peek().is_some() {
+        writeln!(out, "[{}]", prefix)?;
+        for (key, value) in values {
+            let path = format!("{}.{}", prefix, key);
+            let shown = match value {
+                toml::Value::String(s) if !s.is_empty() && SECRET_KEYS.contains(&path.as_str()) => {
+                    "\"***\"".to_string()
+                }
+                // Volumes are `f32`; print them as written in the file.
+                toml::Value::Float(f) => {
+                    let written: f64 = (*f as f32).to_string().parse().unwrap_or(*f);
+                    toml::Value::Float(written).to_string()
+                }
+                _ => value.to_string(),
+            };
+            let line = format!("{} = {}", key, shown);
+            writeln!(out, "{:<48} # {}", line, source_of(&path))?;
+        }
+        writeln!(out)?;
+    }
+    for (key, value) in table {
+        if let toml::Value::Table(section) = value {
+            write_table(out, section, &format!("{}.{}", prefix, key), source_of)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn setup(test_name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("zt-layers-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let site = dir.join("site.toml");
+        let device = dir.join("terminal.toml");
+        std::fs::write(
+            &site,
+            "[api]\nbase_url = \"https://zeit.example.com/api\"\nterminal_id = \"site\"\n\
+             [display]\ntheme = \"light\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &device,
+            "[api]\nterminal_id = \"lager-01\"\n[qr]\nsecret = \"do-not-print\"\n",
+        )
+        .unwrap();
+        (dir, site, device)
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let (dir, site, device) = setup("order");
+        let layers = load_from(
+            &device,
+            &site,
+            vars(&[
+                ("ZT_DISPLAY__THEME", "dark"),
+                ("ZT_DISPLAY__IDLE_TIMEOUT_SECONDS", "12"),
+                ("ZT_AUDIO__ENABLED", "false"),
+                ("ZT_OFFLINE__BUFFER_PATH", "/tmp/buffer.db"),
+                ("ZT_API__RETRY_ATTEMPTS", "viele"),
+                ("ZT_BROKEN__", "1"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(layers.len(), 7);
+
+        let mut without_typo = layers.clone();
+        without_typo.retain(|l| l.source != Source::Env("ZT_API__RETRY_ATTEMPTS".to_string()));
+        let config = AppConfig::from_table(merge(&without_typo)).unwrap();
+        assert_eq!(config.api.base_url, "https://zeit.example.com/api");
+        assert_eq!(config.api.terminal_id, "lager-01");
+        assert_eq!(config.display.theme, "dark");
+        assert_eq!(config.display.idle_timeout_seconds, 12);
+        assert!(!config.audio.enabled);
+        assert_eq!(config.offline.buffer_path, "/tmp/buffer.db");
+        assert_eq!(config.display.resolution, "1024x600");
+
+        // A value of the wrong type is reported like a typo in a file.
+        assert!(AppConfig::from_table(merge(&layers)).is_err());
+
+        // Without a site file the device file stands alone.
+        let layers = load_from(&device, &dir.join("missing.toml"), Vec::new()).unwrap();
+        let config = AppConfig::from_table(merge(&layers)).unwrap();
+        assert_eq!(config.api.base_url, AppConfig::default().api.base_url);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_env_values_keep_the_type_of_the_key() {
+        let (dir, site, device) = setup("types");
+        let layers = load_from(
+            &device,
+            &site,
+            vars(&[("ZT_API__TERMINAL_ID", "0042"), ("ZT_AUDIO__VOLUME", "0.5")]),
+        )
+        .unwrap();
+        let config = AppConfig::from_table(merge(&layers)).unwrap();
+        assert_eq!(config.api.terminal_id, "0042");
+        assert_eq!(config.audio.volume, 0.5);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_describe_names_the_source_of_each_value() {
+        let (dir, site, device) = setup("describe");
+        let layers = load_from(&device, &site, vars(&[("ZT_DISPLAY__THEME", "dark")])).unwrap();
+        let text = describe_layers(&layers).unwrap();
+
+        let line = |key: &str| {
+            text.lines()
+                .find(|l| l.starts_with(&format!("{} = ", key)))
+                .unwrap_or_else(|| panic!("{} missing in\n{}", key, text))
+                .to_string()
+        };
+        assert!(line("base_url").ends_with(&format!("# site file {}", site.display())));
+        assert!(line("terminal_id").ends_with(&format!("# device file {}", device.display())));
+        assert!(line("theme").ends_with("# env ZT_DISPLAY__THEME"));
+        assert!(line("resolution").ends_with("# default"));
+        assert!(line("secret").contains("\"***\""));
+        assert!(line("volume").starts_with("volume = 0.7 "));
+        assert!(!text.contains("do-not-print"));
+        // The output is itself a valid configuration file.
+        assert!(toml::from_str::<AppConfig>(&text).is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
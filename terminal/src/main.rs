@@ -1,47 +1,211 @@
 mod api;
 mod audio;
+mod branding;
 mod buffer;
+mod check;
+mod cli;
+mod clock;
 mod config;
+mod engine;
+mod headless;
+mod layers;
+mod maintenance;
+mod photos;
+mod pin;
+mod projects;
+mod qr;
+mod reload;
+mod remote;
 mod rfid;
+mod sync;
 mod ui;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use config::AppConfig;
-use log::{error, info};
+use log::{error, info, warn};
+
+const USAGE: &str = "\
+Usage: zeiterfassung-terminal [OPTIONS]
+       zeiterfassung-terminal buffer <COMMAND>   (see buffer --help)
+
+Options:
+  --check-config     Validate the configuration, print all problems and exit
+  --print-config     Print the effective configuration and the source of each value
+  --allow-defaults   Run with the default terminal ID and backend URL (development only)
+  --headless         Run without a display: audio feedback and log output only
+  -h, --help         Show this help
+
+The configuration is merged from the built-in defaults, the site file ($SITE_CONFIG_PATH,
+default: site.toml next to the device file), the device file ($CONFIG_PATH, default:
+terminal.toml) and ZT_* environment variables (ZT_API__BASE_URL sets api.base_url).";
+
+/// Command line flags.
+#[derive(Debug, Default)]
+struct Options {
+    check_config: bool,
+    print_config: bool,
+    allow_defaults: bool,
+    headless: bool,
+}
+
+impl Options {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        for arg in args {
+            match arg.as_str() {
+                "--check-config" => options.check_config = true,
+                "--print-config" => options.print_config = true,
+                "--allow-defaults" => options.allow_defaults = true,
+                "--headless" => options.headless = true,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                other => return Err(format!("unknown argument: {}", other)),
+            }
+        }
+        Ok(options)
+    }
+}
 
 fn main() {
     env_logger::init();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
     let config_path = std::env::var("CONFIG_PATH")
         .map(PathBuf::from)
         .unwrap_or_else(|_| PathBuf::from("terminal.toml"));
 
-    let config = match AppConfig::load(&config_path) {
+    if args.first().map(String::as_str) == Some("buffer") {
+        run_buffer_command(&args[1..], &config_path);
+    }
+
+    let options = match Options::parse(args.into_iter()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if options.print_config {
+        match layers::describe(&config_path) {
+            Ok(text) => {
+                print!("{}", text);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let config = match remote::effective_config(&config_path) {
         Ok(c) => {
             info!("Configuration loaded from {}", config_path.display());
             c
         }
-        Err(e) => {
-            error!("Failed to load configuration: {}. Using defaults.", e);
+        Err(e) if options.allow_defaults && !options.check_config => {
+            warn!("Failed to load configuration: {}. Using defaults.", e);
             AppConfig::default()
         }
+        Err(e) => {
+            let message = format!(
+                "Failed to load configuration from {}: {}",
+                config_path.display(),
+                e
+            );
+            if options.check_config {
+                eprintln!("error: {}", message);
+            } else {
+                error!("{}", message);
+            }
+            std::process::exit(1);
+        }
     };
 
+    let problems = check::check(&config, options.allow_defaults);
+    let error_count = problems.iter().filter(|p| p.is_error()).count();
+
+    if options.check_config {
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        println!(
+            "{}: {} error(s), {} warning(s)",
+            config_path.display(),
+            error_count,
+            problems.len() - error_count
+        );
+        std::process::exit(if error_count > 0 { 1 } else { 0 });
+    }
+
+    for problem in &problems {
+        if problem.is_error() {
+            error!("Configuration: {}", problem.message);
+        } else {
+            warn!("Configuration: {}", problem.message);
+        }
+    }
+    if error_count > 0 {
+        error!(
+            "Refusing to start with {} configuration error(s); run with --check-config for details",
+            error_count
+        );
+        std::process::exit(1);
+    }
+
     info!(
         "Starting Zeiterfassung Terminal v{}",
         env!("CARGO_PKG_VERSION")
     );
+    info!("API endpoint: {}", config.api.base_url);
+    info!("Language: {}", config.locale.language);
+
+    if options.headless {
+        if let Err(e) = headless::run(config, config_path) {
+            error!("Fatal error in headless mode: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     info!(
         "Display: {}x{}",
         config.display.resolution_width(),
         config.display.resolution_height()
     );
-    info!("API endpoint: {}", config.api.base_url);
-    info!("Language: {}", config.locale.language);
-
-    if let Err(e) = ui::run(config) {
+    if let Err(e) = ui::run(config, config_path) {
         error!("Fatal error in UI: {}", e);
         std::process::exit(1);
     }
 }
+
+/// `zeiterfassung-terminal buffer ...`: runs the command and exits.
+fn run_buffer_command(args: &[String], config_path: &Path) -> ! {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", cli::BUFFER_USAGE);
+        std::process::exit(0);
+    }
+    let command = match cli::BufferCommand::parse(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::BUFFER_USAGE);
+            std::process::exit(2);
+        }
+    };
+    let config = match remote::effective_config(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!(
+                "error: Failed to load configuration from {}: {}",
+                config_path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+    std::process::exit(cli::run(command, &config));
+}
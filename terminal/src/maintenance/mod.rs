@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+
+use crate::config::MaintenanceConfig;
+
+/// Hex-encoded SHA-256 of a badge ID, as stored in `maintenance.admin_badge_hashes`.
+///
+/// Equivalent to `printf '%s' '<badge id>' | sha256sum`.
+pub fn hash_badge(tag_id: &str) -> String {
+    Sha256::digest(tag_id.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Returns true if `tag_id` is one of the configured admin badges.
+pub fn is_admin_badge(config: &MaintenanceConfig, tag_id: &str) -> bool {
+    if config.admin_badge_hashes.is_empty() {
+        return false;
+    }
+    let hash = hash_badge(tag_id);
+    config
+        .admin_badge_hashes
+        .iter()
+        .any(|h| h.trim().eq_ignore_ascii_case(&hash))
+}
+
+/// Outcome of a scan as listed on the maintenance screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanOutcome {
+    ClockIn,
+    ClockOut,
+    /// Buffered locally because the backend was unreachable.
+    Offline,
+    Info,
+    UnknownBadge,
+    WrongPin,
+    Conflict,
+    /// The badge is on the blocklist.
+    Blocked,
+    Error,
+}
+
+impl ScanOutcome {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanOutcome::ClockIn => "Eingestempelt",
+            ScanOutcome::ClockOut => "Ausgestempelt",
+            ScanOutcome::Offline => "Offline gespeichert",
+            ScanOutcome::Info => "Kontostand",
+            ScanOutcome::UnknownBadge => "Unbekannter Ausweis",
+            ScanOutcome::WrongPin => "PIN falsch",
+            ScanOutcome::Conflict => "Konflikt",
+            ScanOutcome::Blocked => "Ausweis gesperrt",
+            ScanOutcome::Error => "Fehler",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanRecord {
+    pub time: DateTime<Utc>,
+    /// Masked badge ID; the full ID is never kept in memory longer than the scan itself.
+    pub tag: String,
+    pub outcome: ScanOutcome,
+}
+
+/// The last scans of this session, newest first.
+#[derive(Debug, Clone)]
+pub struct ScanLog {
+    entries: VecDeque<ScanRecord>,
+    capacity: usize,
+}
+
+impl ScanLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, tag_id: &str, outcome: ScanOutcome) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(ScanRecord {
+            time: Utc::now(),
+            tag: mask_tag(tag_id),
+            outcome,
+        });
+    }
+
+    pub fn entries(&self) -> Vec<ScanRecord> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+/// Shows only the last four characters of a badge ID.
+pub fn mask_tag(tag_id: &str) -> String {
+    let chars: Vec<char> = tag_id.chars().collect();
+    let visible = chars.len().min(4);
+    let hidden = chars.len() - visible;
+    "\u{2022}".repeat(hidden.min(8)) + &chars[hidden..].iter().collect::<String>()
+}
+
+/// Local address of the interface used to reach `base_url`.  Connecting a UDP socket only
+/// selects the route; no packet is sent.
+pub async fn local_ip(base_url: &str) -> Option<IpAddr> {
+    let url = reqwest::Url::parse(base_url).ok()?;
+    let host = url.host_str()?.to_string();
+    let port = url.port_or_known_default()?;
+    let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await.ok()?;
+    socket.connect((host.as_str(), port)).await.ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_badge_matches_hash() {
+        let config = MaintenanceConfig {
+            // printf '%s' 'ADMIN-0001' | sha256sum
+            admin_badge_hashes: vec![hash_badge("ADMIN-0001").to_uppercase()],
+            ..MaintenanceConfig::default()
+        };
+        assert!(is_admin_badge(&config, "ADMIN-0001"));
+        assert!(!is_admin_badge(&config, "ADMIN-0002"));
+        assert!(!is_admin_badge(&MaintenanceConfig::default(), "ADMIN-0001"));
+        assert_eq!(
+            hash_badge("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_scan_log_keeps_newest_entries_masked() {
+        let mut log = ScanLog::new(2);
+        log.record("04A1B2C3D4", ScanOutcome::ClockIn);
+        log.record("04A1B2C3D5", ScanOutcome::Offline);
+        log.record("04A1B2C3D6", ScanOutcome::UnknownBadge);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].outcome, ScanOutcome::UnknownBadge);
+        assert_eq!(
+            entries[0].tag,
+            "\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}C3D6"
+        );
+        assert_eq!(entries[1].outcome, ScanOutcome::Offline);
+        assert_eq!(mask_tag("AB"), "AB");
+    }
+}
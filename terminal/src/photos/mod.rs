@@ -0,0 +1,231 @@
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::config::PhotosConfig;
+
+/// On-disk cache of employee photos, bounded by total size and entry age.
+///
+/// Entries expire `max_age` after they were downloaded (the file's modification time).  When the
+/// cache grows beyond `max_bytes`, the least recently shown photos are evicted first.
+pub struct PhotoCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+    /// Last time each cached photo was shown, keyed by file name.  Seeded from the modification
+    /// time on startup, since access times are unreliable on `noatime` mounts.
+    last_access: HashMap<String, SystemTime>,
+}
+
+impl PhotoCache {
+    pub fn new(config: &PhotosConfig) -> io::Result<Self> {
+        let dir = PathBuf::from(&config.cache_dir);
+        std::fs::create_dir_all(&dir)?;
+
+        let mut last_access = HashMap::new();
+        for entry in std::fs::read_dir(&dir)?.flatten() {
+            if let (Some(name), Ok(meta)) = (entry.file_name().to_str(), entry.metadata()) {
+                // Leftover `.part` files from an interrupted download are picked up by eviction
+                // only if tracked, so remove them right away.
+                if name.ends_with(".part") {
+                    let _ = std::fs::remove_file(entry.path());
+                } else if meta.is_file() {
+                    last_access.insert(
+                        name.to_string(),
+                        meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    );
+                }
+            }
+        }
+
+        let mut cache = Self {
+            dir,
+            max_bytes: config.max_cache_mb * 1024 * 1024,
+            max_age: Duration::from_secs(config.max_age_hours * 3600),
+            last_access,
+        };
+        cache.evict();
+        info!(
+            "Photo cache at {} holds {} photos",
+            cache.dir.display(),
+            cache.last_access.len()
+        );
+        Ok(cache)
+    }
+
+    /// Returns the cached photo for `employee_id` if present and not expired.
+    pub fn get(&mut self, employee_id: &str) -> Option<PathBuf> {
+        let name = file_name(employee_id);
+        let path = self.dir.join(&name);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+        if is_expired(modified, self.max_age) {
+            debug!("Cached photo for {} expired", employee_id);
+            self.remove(&name);
+            return None;
+        }
+
+        self.last_access.insert(name, SystemTime::now());
+        Some(path)
+    }
+
+    /// Stores a downloaded photo and evicts old entries to stay within the size cap.
+    pub fn store(&mut self, employee_id: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+        let name = file_name(employee_id);
+        let path = self.dir.join(&name);
+        // Write to a temporary file first so a crash never leaves a truncated image behind.
+        let tmp = self.dir.join(format!("{}.part", name));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)?;
+
+        self.last_access.insert(name, SystemTime::now());
+        self.evict();
+        Ok(path)
+    }
+
+    /// Drops expired photos, then the least recently shown ones until the cache fits `max_bytes`.
+    fn evict(&mut self) {
+        let mut entries: Vec<(String, u64, SystemTime)> = Vec::new();
+        for name in self.last_access.keys().cloned().collect::<Vec<_>>() {
+            match std::fs::metadata(self.dir.join(&name)) {
+                Ok(meta) => {
+                    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    if is_expired(modified, self.max_age) {
+                        self.remove(&name);
+                    } else {
+                        entries.push((name.clone(), meta.len(), self.last_access[&name]));
+                    }
+                }
+                Err(_) => {
+                    self.last_access.remove(&name);
+                }
+            }
+        }
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, accessed)| *accessed);
+        for (name, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            self.remove(&name);
+            total -= size;
+        }
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.last_access.remove(name);
+        if let Err(e) = std::fs::remove_file(self.dir.join(name)) {
+            warn!("Failed to remove cached photo {}: {}", name, e);
+        }
+    }
+
+    #[cfg(test)]
+    fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+}
+
+/// Employee IDs are UUIDs, but never trust them as path components.
+fn file_name(employee_id: &str) -> String {
+    let safe: String = employee_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.img", safe)
+}
+
+fn is_expired(modified: SystemTime, max_age: Duration) -> bool {
+    SystemTime::now()
+        .duration_since(modified)
+        .map(|age| age > max_age)
+        .unwrap_or(false)
+}
+
+/// Returns true if `bytes` start like a PNG, JPEG or WebP image, so error pages served with a
+/// 200 status never end up in the cache.
+pub fn looks_like_image(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0x89, b'P', b'N', b'G'])
+        || bytes.starts_with(&[0xFF, 0xD8, 0xFF])
+        || (bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_cache(test_name: &str, max_cache_mb: u64) -> PhotoCache {
+        let dir =
+            std::env::temp_dir().join(format!("zt-photos-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        PhotoCache::new(&PhotosConfig {
+            enabled: true,
+            cache_dir: dir.to_string_lossy().to_string(),
+            max_cache_mb,
+            max_age_hours: 24,
+        })
+        .expect("failed to create photo cache")
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let mut cache = make_cache("store", 1);
+        assert!(cache.get("emp-1").is_none());
+
+        let path = cache.store("emp-1", b"\x89PNG....").unwrap();
+        assert_eq!(cache.get("emp-1"), Some(path));
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted_first() {
+        let mut cache = make_cache("lru", 1);
+        let half_mb = vec![0u8; 512 * 1024];
+
+        cache.store("emp-1", &half_mb).unwrap();
+        cache.store("emp-2", &half_mb).unwrap();
+        // Showing emp-1 again makes emp-2 the least recently used entry.
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(cache.get("emp-1").is_some());
+        cache.store("emp-3", &half_mb).unwrap();
+
+        assert!(cache.get("emp-1").is_some());
+        assert!(cache.get("emp-2").is_none());
+        assert!(cache.get("emp-3").is_some());
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn test_file_name_is_sanitized() {
+        assert_eq!(file_name("../../etc/passwd"), "______etc_passwd.img");
+        assert_eq!(
+            file_name("0b7e6f1c-1a2b-4c3d-9e8f-001122334455"),
+            "0b7e6f1c-1a2b-4c3d-9e8f-001122334455.img"
+        );
+    }
+
+    #[test]
+    fn test_looks_like_image() {
+        assert!(looks_like_image(b"\x89PNG\r\n\x1a\n"));
+        assert!(looks_like_image(&[0xFF, 0xD8, 0xFF, 0xE0]));
+        assert!(!looks_like_image(b"<!DOCTYPE html>"));
+    }
+
+    #[test]
+    fn test_expiry() {
+        let now = SystemTime::now();
+        assert!(!is_expired(now, Duration::from_secs(60)));
+        assert!(is_expired(
+            now - Duration::from_secs(120),
+            Duration::from_secs(60)
+        ));
+    }
+}
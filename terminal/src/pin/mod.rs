@@ -0,0 +1,194 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::api::RosterEntry;
+use crate::config::PinConfig;
+
+/// Longest employee number accepted on the keypad.
+pub const MAX_EMPLOYEE_NUMBER_LEN: usize = 10;
+/// PINs are 4 to 8 digits.
+pub const MIN_PIN_LEN: usize = 4;
+pub const MAX_PIN_LEN: usize = 8;
+
+/// Digits in the order of a phone keypad: 1–9, then 0 in the bottom row.
+pub const STANDARD_KEYPAD: [u8; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 0];
+
+/// Digit order for a new keypad; shuffled if `shuffle_keypad` is set.
+pub fn keypad(config: &PinConfig) -> [u8; 10] {
+    if config.shuffle_keypad {
+        shuffled_keypad(&mut rand::thread_rng())
+    } else {
+        STANDARD_KEYPAD
+    }
+}
+
+fn shuffled_keypad(rng: &mut impl Rng) -> [u8; 10] {
+    let mut keys = STANDARD_KEYPAD;
+    keys.shuffle(rng);
+    keys
+}
+
+/// Hex SHA-256 of the salt followed by the PIN, as distributed in the roster.
+pub fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(pin.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Checks a PIN against the roster while the backend is unreachable.  `None` if the roster
+/// has no PIN hash for this badge, so the PIN cannot be checked offline.
+pub fn verify_offline(entry: &RosterEntry, pin: &str) -> Option<bool> {
+    let hash = entry.pin_hash.as_deref()?;
+    let salt = entry.pin_salt.as_deref().unwrap_or_default();
+    Some(hash_pin(salt, pin).eq_ignore_ascii_case(hash.trim()))
+}
+
+#[derive(Debug, Default)]
+struct Attempts {
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+/// Counts wrong PINs per employee number (or per badge in two-factor mode) and locks a
+/// number after too many.
+///
+/// Kept in memory only: a restart clears all lockouts, which is acceptable because the
+/// backend enforces its own limit as well.
+#[derive(Debug)]
+pub struct PinRateLimiter {
+    max_attempts: u32,
+    lockout: Duration,
+    attempts: HashMap<String, Attempts>,
+}
+
+impl PinRateLimiter {
+    pub fn new(config: &PinConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            lockout: Duration::from_secs(config.lockout_seconds),
+            attempts: HashMap::new(),
+        }
+    }
+
+    /// Remaining lockout for `employee_number`, or `None` if a PIN may be entered.
+    pub fn locked_for(&self, employee_number: &str, now: Instant) -> Option<Duration> {
+        self.attempts
+            .get(employee_number)
+            .and_then(|a| a.locked_until)
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Records a wrong PIN.  Returns the lockout if this attempt triggered one.
+    pub fn record_failure(&mut self, employee_number: &str, now: Instant) -> Option<Duration> {
+        let attempts = self
+            .attempts
+            .entry(employee_number.to_string())
+            .or_default();
+        // A lockout that has run out starts a fresh count.
+        if attempts.locked_until.is_some_and(|until| until <= now) {
+            *attempts = Attempts::default();
+        }
+        attempts.failures += 1;
+        if attempts.failures >= self.max_attempts {
+            attempts.locked_until = Some(now + self.lockout);
+            Some(self.lockout)
+        } else {
+            None
+        }
+    }
+
+    pub fn record_success(&mut self, employee_number: &str) {
+        self.attempts.remove(employee_number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn config(max_attempts: u32, lockout_seconds: u64) -> PinConfig {
+        PinConfig {
+            max_attempts,
+            lockout_seconds,
+            ..PinConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_shuffled_keypad_is_a_permutation() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let keys = shuffled_keypad(&mut rng);
+        let mut sorted = keys;
+        sorted.sort();
+        assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_ne!(keys, STANDARD_KEYPAD);
+
+        let fixed = PinConfig {
+            shuffle_keypad: false,
+            ..PinConfig::default()
+        };
+        assert_eq!(keypad(&fixed), STANDARD_KEYPAD);
+    }
+
+    #[test]
+    fn test_verify_offline_against_salted_hash() {
+        let mut entry = RosterEntry {
+            rfid_tag_id: "TAG1".to_string(),
+            pin_hash: Some(hash_pin("c0ffee", "4711").to_uppercase()),
+            pin_salt: Some("c0ffee".to_string()),
+            pin_exempt: false,
+        };
+        assert_eq!(verify_offline(&entry, "4711"), Some(true));
+        assert_eq!(verify_offline(&entry, "4712"), Some(false));
+        // The salt is part of the hash.
+        assert_ne!(hash_pin("c0ffee", "4711"), hash_pin("", "4711"));
+
+        entry.pin_hash = None;
+        assert_eq!(verify_offline(&entry, "4711"), None);
+    }
+
+    #[test]
+    fn test_rate_limiter_locks_number_after_max_attempts() {
+        let mut limiter = PinRateLimiter::new(&config(3, 300));
+        let start = Instant::now();
+
+        assert_eq!(limiter.record_failure("1042", start), None);
+        assert_eq!(limiter.record_failure("1042", start), None);
+        assert_eq!(
+            limiter.record_failure("1042", start),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            limiter.locked_for("1042", start + Duration::from_secs(100)),
+            Some(Duration::from_secs(200))
+        );
+        // Other employee numbers are not affected.
+        assert_eq!(limiter.locked_for("1043", start), None);
+
+        // After the lockout the count starts again.
+        let later = start + Duration::from_secs(301);
+        assert_eq!(limiter.locked_for("1042", later), None);
+        assert_eq!(limiter.record_failure("1042", later), None);
+    }
+
+    #[test]
+    fn test_rate_limiter_success_clears_failures() {
+        let mut limiter = PinRateLimiter::new(&config(2, 60));
+        let now = Instant::now();
+        assert_eq!(limiter.record_failure("7", now), None);
+        limiter.record_success("7");
+        assert_eq!(limiter.record_failure("7", now), None);
+        assert!(limiter.record_failure("7", now).is_some());
+    }
+}
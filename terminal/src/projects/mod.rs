@@ -0,0 +1,99 @@
+use crate::api::Project;
+
+/// A project as listed on the selection screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectEntry {
+    pub project: Project,
+    /// Recently used by the employee who is currently selecting.
+    pub favourite: bool,
+}
+
+/// Orders `projects` for the touch list: the employee's favourites first (in the order given),
+/// then everything else in backend order.  A non-empty `query` keeps only projects whose code,
+/// name or cost center contains it, ignoring case.
+pub fn select_projects(
+    projects: &[Project],
+    favourites: &[String],
+    query: &str,
+) -> Vec<ProjectEntry> {
+    let query = query.trim().to_lowercase();
+    let matches = |p: &Project| {
+        query.is_empty()
+            || p.name.to_lowercase().contains(&query)
+            || p.code.to_lowercase().contains(&query)
+            || p.cost_center
+                .as_deref()
+                .is_some_and(|c| c.to_lowercase().contains(&query))
+    };
+
+    let favourite_entries = favourites
+        .iter()
+        .filter_map(|id| projects.iter().find(|p| &p.id == id))
+        .filter(|p| matches(p))
+        .map(|p| ProjectEntry {
+            project: p.clone(),
+            favourite: true,
+        });
+
+    let other_entries = projects
+        .iter()
+        .filter(|p| !favourites.contains(&p.id))
+        .filter(|p| matches(p))
+        .map(|p| ProjectEntry {
+            project: p.clone(),
+            favourite: false,
+        });
+
+    favourite_entries.chain(other_entries).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str, code: &str, name: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            code: code.to_string(),
+            name: name.to_string(),
+            cost_center: None,
+        }
+    }
+
+    fn sample() -> Vec<Project> {
+        vec![
+            project("p-1", "WS", "Werkstatt"),
+            project("p-2", "MNT", "Montage"),
+            project("p-3", "LAG", "Lager"),
+        ]
+    }
+
+    #[test]
+    fn test_favourites_come_first() {
+        let favourites = vec!["p-3".to_string(), "p-2".to_string()];
+        let entries = select_projects(&sample(), &favourites, "");
+        let ids: Vec<&str> = entries.iter().map(|e| e.project.id.as_str()).collect();
+        assert_eq!(ids, vec!["p-3", "p-2", "p-1"]);
+        assert!(entries[0].favourite);
+        assert!(!entries[2].favourite);
+    }
+
+    #[test]
+    fn test_search_matches_code_and_name_case_insensitively() {
+        let entries = select_projects(&sample(), &[], "mnt");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].project.id, "p-2");
+
+        let entries = select_projects(&sample(), &[], "LAGER");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].project.id, "p-3");
+    }
+
+    #[test]
+    fn test_unknown_favourites_are_ignored() {
+        let favourites = vec!["deleted-project".to_string()];
+        let entries = select_projects(&sample(), &favourites, "");
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| !e.favourite));
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Version prefix of the payload, so the format can change without breaking older apps.
+pub const PAYLOAD_PREFIX: &str = "ZT1";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Number of the time window `now` falls into: Unix time divided by the rotation period.
+pub fn time_window(now: DateTime<Utc>, rotation_seconds: u64) -> u64 {
+    now.timestamp().max(0) as u64 / rotation_seconds.max(1)
+}
+
+/// Text encoded in the idle screen QR code:
+/// `ZT1:<terminal_id>:<window>:<hex HMAC-SHA256 over "ZT1:<terminal_id>:<window">`.
+///
+/// The backend recomputes the HMAC with the shared secret and accepts the current and the
+/// previous window, which proves the mobile app scanned the code at this terminal just now.
+/// The terminal ID may contain `:`, so the last two fields are split off from the right.
+pub fn payload(terminal_id: &str, window: u64, secret: &[u8]) -> String {
+    let message = format!("{}:{}:{}", PAYLOAD_PREFIX, terminal_id, window);
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    let signature: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}:{}", message, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_time_window() {
+        let t = Utc.timestamp_opt(1_728_000_000, 0).unwrap();
+        assert_eq!(time_window(t, 10), 172_800_000);
+        assert_eq!(
+            time_window(t + chrono::Duration::seconds(9), 10),
+            172_800_000
+        );
+        assert_eq!(
+            time_window(t + chrono::Duration::seconds(10), 10),
+            172_800_001
+        );
+        // A zero period must not divide by zero.
+        assert_eq!(time_window(t, 0), 1_728_000_000);
+    }
+
+    #[test]
+    fn test_payload_is_deterministic() {
+        assert_eq!(
+            payload("terminal-01", 172_800_000, b"shared-secret"),
+            "ZT1:terminal-01:172800000:\
+             37e345f060fbcb52c4f841ccbda69c214f55abd07408909cfb6f51fe11aaedc3"
+        );
+    }
+
+    #[test]
+    fn test_payload_changes_with_window_terminal_and_secret() {
+        let base = payload("terminal-01", 1, b"secret");
+        assert_ne!(base, payload("terminal-01", 2, b"secret"));
+        assert_ne!(base, payload("terminal-02", 1, b"secret"));
+        assert_ne!(base, payload("terminal-01", 1, b"other"));
+    }
+}
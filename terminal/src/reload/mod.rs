@@ -0,0 +1,195 @@
+use log::warn;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::config::{lookup, AppConfig, SECRET_KEYS};
+use crate::layers;
+use crate::remote::{self, RemoteConfigCache};
+
+/// How often the configuration files' modification times are checked.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A configuration file that could not be applied.
+#[derive(Debug, Clone)]
+pub struct RejectedConfig {
+    pub error: String,
+    /// The merged local files, for logging what changed; `None` if one is not valid TOML.
+    pub file: Option<toml::Table>,
+}
+
+/// Re-reads the configuration layers for the device file `path`, merges the cached remote
+/// document like at startup and validates the result.
+pub fn load(path: &Path) -> Result<AppConfig, RejectedConfig> {
+    let file = layers::local_table(path).map_err(|e| RejectedConfig {
+        error: e.to_string(),
+        file: None,
+    })?;
+    let rejected = |error: String| RejectedConfig {
+        error,
+        file: Some(file.clone()),
+    };
+    let config =
+        remote::effective_config_from_table(file.clone()).map_err(|e| rejected(e.to_string()))?;
+    config.validate().map_err(rejected)?;
+    Ok(config)
+}
+
+/// Merges a remote `document` fetched for the `running` configuration over the local layers
+/// for `path`.  A document that makes a valid configuration is cached, so [`load`] and the
+/// next start use it too.
+pub fn merge_remote(
+    path: &Path,
+    running: &AppConfig,
+    document: &toml::Table,
+) -> Result<AppConfig, String> {
+    let local = layers::local_table(path)
+        .map_err(|e| format!("cannot read the local configuration: {}", e))?;
+    let config = remote::build(&local, document, &running.remote.pinned)?;
+    if let Err(e) = RemoteConfigCache::new(&running.remote.cache_path).save(document) {
+        warn!("Failed to cache remote configuration: {}", e);
+    }
+    Ok(config)
+}
+
+/// Whether applying `config` would leave `running` as it is; pending restart-only changes
+/// alone are not worth re-applying.
+pub fn unchanged(running: &AppConfig, config: &AppConfig) -> bool {
+    let effective = config.clone().with_startup_values(running);
+    toml::Table::try_from(&effective).ok() == toml::Table::try_from(running).ok()
+}
+
+/// Modification times of the device file `path` and the site file, `None` for a file that
+/// cannot be read.
+pub fn modified(path: &Path) -> [Option<SystemTime>; 2] {
+    [path.to_path_buf(), layers::site_path(path)]
+        .map(|file| std::fs::metadata(file).and_then(|m| m.modified()).ok())
+}
+
+/// Keys set in `file` whose value differs from the running configuration, as
+/// `"section.key: old -> new"` lines.  Secrets are masked.
+pub fn diff(running: &AppConfig, file: &toml::Table) -> Vec<String> {
+    let Ok(running) = toml::Table::try_from(running) else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    diff_into(&running, file, "", &mut lines);
+    lines
+}
+
+fn diff_into(running: &toml::Table, file: &toml::Table, prefix: &str, lines: &mut Vec<String>) {
+    for (key, value) in file {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if let toml::Value::Table(section) = value {
+            diff_into(running, section, &path, lines);
+            continue;
+        }
+        let old = lookup(running, &path);
+        if old.is_some_and(|old| same(old, value)) {
+            continue;
+        }
+        let show = |v: Option<&toml::Value>| match v {
+            None => "(unset)".to_string(),
+            Some(_) if SECRET_KEYS.contains(&path.as_str()) => "***".to_string(),
+            // Volumes are `f32`; print them as written in the file.
+            Some(toml::Value::Float(f)) => (*f as f32).to_string(),
+            Some(v) => v.to_string(),
+        };
+        lines.push(format!("{}: {} -> {}", path, show(old), show(Some(value))));
+    }
+}
+
+fn same(running: &toml::Value, file: &toml::Value) -> bool {
+    match (running, file) {
+        (toml::Value::Float(a), toml::Value::Float(b)) => *a as f32 == *b as f32,
+        _ => running == file,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lists_changed_keys_and_masks_secrets() {
+        let running = AppConfig::default();
+        let file: toml::Table = toml::from_str(
+            r#"
+            unknown = 1
+            [display]
+            resolution = "1024x600"
+            idle_timeout_seconds = "long"
+            [qr]
+            secret = "do-not-log"
+            "#,
+        )
+        .unwrap();
+
+        let lines = diff(&running, &file);
+        assert_eq!(
+            lines,
+            vec![
+                "display.idle_timeout_seconds: 8 -> \"long\"".to_string(),
+                "qr.secret: *** -> ***".to_string(),
+                "unknown: (unset) -> 1".to_string(),
+            ]
+        );
+        assert!(lines.iter().all(|l| !l.contains("do-not-log")));
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_files() {
+        let dir = std::env::temp_dir().join(format!("zt-reload-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("terminal.toml");
+
+        let mut config = AppConfig::default();
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        assert!(load(&path).is_ok());
+
+        std::fs::write(&path, "[display\n").unwrap();
+        let rejected = load(&path).unwrap_err();
+        assert!(rejected.file.is_none());
+
+        // Floats read back from the file match the running `f32` values.
+        config.audio.volume = 3.0;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        let rejected = load(&path).unwrap_err();
+        assert!(rejected.error.contains("volume"));
+        assert_eq!(
+            diff(&AppConfig::default(), &rejected.file.unwrap()),
+            vec!["audio.volume: 0.7 -> 3".to_string()]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_remote_caches_valid_documents_only() {
+        let dir = std::env::temp_dir().join(format!("zt-reload-remote-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("terminal.toml");
+        let mut running = AppConfig::default();
+        running.remote.enabled = true;
+        running.remote.cache_path = dir.join("remote.json").to_string_lossy().into_owned();
+        std::fs::write(&path, toml::to_string(&running).unwrap()).unwrap();
+
+        let document: toml::Table = toml::from_str("[offline]\nsync_interval_seconds = 7").unwrap();
+        let config = merge_remote(&path, &running, &document).unwrap();
+        assert_eq!(config.offline.sync_interval_seconds, 7);
+        assert!(!unchanged(&running, &config));
+        assert!(unchanged(&config, &config));
+        assert_eq!(load(&path).unwrap().offline.sync_interval_seconds, 7);
+
+        let invalid: toml::Table = toml::from_str("[audio]\nvolume = 3.0").unwrap();
+        assert!(merge_remote(&path, &running, &invalid).is_err());
+        assert_eq!(load(&path).unwrap().offline.sync_interval_seconds, 7);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,226 @@
+use log::warn;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::AppConfig;
+use crate::layers;
+
+/// Keys the remote document can never change: the terminal's identity, the server it
+/// trusts, the admin badges, and the settings that decide whether and how the remote
+/// document is used at all.
+pub const ALWAYS_PINNED: &[&str] = &["api.terminal_id", "api.base_url", "maintenance", "remote"];
+
+/// Deep-merges `remote` over `local`.  Keys in `pinned` (or `ALWAYS_PINNED`) keep their
+/// local value; tables are merged key by key, everything else is replaced.
+pub fn merge(local: &toml::Table, remote: &toml::Table, pinned: &[String]) -> toml::Table {
+    let mut merged = local.clone();
+    merge_into(&mut merged, remote, "", pinned);
+    merged
+}
+
+fn merge_into(target: &mut toml::Table, source: &toml::Table, prefix: &str, pinned: &[String]) {
+    for (key, value) in source {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if is_pinned(&path, pinned) {
+            continue;
+        }
+        match value {
+            toml::Value::Table(section) => {
+                if !target.get(key).is_some_and(|v| v.is_table()) {
+                    target.insert(key.clone(), toml::Value::Table(toml::Table::new()));
+                }
+                if let Some(toml::Value::Table(existing)) = target.get_mut(key) {
+                    merge_into(existing, section, &path, pinned);
+                }
+            }
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn is_pinned(path: &str, pinned: &[String]) -> bool {
+    ALWAYS_PINNED
+        .iter()
+        .copied()
+        .chain(pinned.iter().map(String::as_str))
+        .any(|p| path == p || path.starts_with(&format!("{}.", p)))
+}
+
+/// Merges `remote` over `local` and returns the result if it is a complete, valid config.
+pub fn build(
+    local: &toml::Table,
+    remote: &toml::Table,
+    pinned: &[String],
+) -> Result<AppConfig, String> {
+    let config = AppConfig::from_table(merge(local, remote, pinned)).map_err(|e| e.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// The last remote document that produced a valid config, kept as JSON.
+pub struct RemoteConfigCache {
+    path: PathBuf,
+}
+
+impl RemoteConfigCache {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: PathBuf::from(path),
+        }
+    }
+
+    pub fn load(&self) -> Option<toml::Table> {
+        let content = std::fs::read_to_string(&self.path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(document) => Some(document),
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable remote config cache {}: {}",
+                    self.path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, document: &toml::Table) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(document).map_err(io::Error::other)?;
+        let tmp = self.path.with_extension("part");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+/// The local layers (site file, device file `path`, environment), merged with the last
+/// known-good remote document when remote configuration is enabled.  A cached document
+/// that no longer fits the local configuration is ignored.
+pub fn effective_config(path: &Path) -> Result<AppConfig, Box<dyn std::error::Error>> {
+    effective_config_from_table(layers::local_table(path)?)
+}
+
+/// Like [`effective_config`], for local layers that have already been merged.
+pub fn effective_config_from_table(
+    local: toml::Table,
+) -> Result<AppConfig, Box<dyn std::error::Error>> {
+    let config = AppConfig::from_table(local.clone())?;
+    if !config.remote.enabled {
+        return Ok(config);
+    }
+    let Some(document) = RemoteConfigCache::new(&config.remote.cache_path).load() else {
+        return Ok(config);
+    };
+    match build(&local, &document, &config.remote.pinned) {
+        Ok(merged) => Ok(merged),
+        Err(e) => {
+            warn!("Cached remote configuration rejected: {}", e);
+            Ok(config)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local() -> toml::Table {
+        toml::Table::try_from(AppConfig::default()).unwrap()
+    }
+
+    fn table(s: &str) -> toml::Table {
+        toml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_merge_respects_pinned_keys() {
+        let remote = table(
+            r#"
+            [display]
+            idle_timeout_seconds = 15
+            theme = "light"
+            [api]
+            terminal_id = "stolen-id"
+            base_url = "https://zeit.example.com/api"
+            timeout_seconds = 30
+            [maintenance]
+            admin_badge_hashes = ["0000"]
+            [remote]
+            enabled = false
+            "#,
+        );
+        let mut enabled = AppConfig::default();
+        enabled.remote.enabled = true;
+        let local = toml::Table::try_from(enabled).unwrap();
+        let pinned = vec!["display.theme".to_string()];
+        let config = build(&local, &remote, &pinned).unwrap();
+
+        assert_eq!(config.display.idle_timeout_seconds, 15);
+        assert_eq!(config.display.theme, "dark");
+        assert_eq!(config.display.resolution, "1024x600");
+        assert_eq!(config.api.timeout_seconds, 30);
+        assert_eq!(config.api.terminal_id, "terminal-01");
+        assert_eq!(config.api.base_url, "http://localhost:8080/api");
+        assert!(config.maintenance.admin_badge_hashes.is_empty());
+        assert!(config.remote.enabled);
+
+        // Pinning a section pins all of its keys.
+        let pinned = vec!["api".to_string()];
+        let config = build(&local, &remote, &pinned).unwrap();
+        assert_eq!(config.api.timeout_seconds, 10);
+    }
+
+    #[test]
+    fn test_invalid_remote_document_is_rejected() {
+        let wrong_type = table("[display]\nidle_timeout_seconds = \"long\"");
+        assert!(build(&local(), &wrong_type, &[]).is_err());
+
+        let invalid = table("[audio]\nvolume = 3.0");
+        assert!(build(&local(), &invalid, &[]).is_err());
+    }
+
+    #[test]
+    fn test_cache_roundtrip_and_fallback() {
+        let dir = std::env::temp_dir().join(format!("zt-remote-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config_path = dir.join("terminal.toml");
+        let cache_path = dir.join("remote-config.json");
+
+        let mut config = AppConfig::default();
+        config.remote.enabled = true;
+        config.remote.cache_path = cache_path.to_string_lossy().into_owned();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        // No cached document yet: the local file is used as is.
+        assert_eq!(
+            effective_config(&config_path)
+                .unwrap()
+                .offline
+                .sync_interval_seconds,
+            config.offline.sync_interval_seconds
+        );
+
+        let cache = RemoteConfigCache::new(&config.remote.cache_path);
+        cache
+            .save(&table("[offline]\nsync_interval_seconds = 7"))
+            .unwrap();
+        assert_eq!(
+            effective_config(&config_path)
+                .unwrap()
+                .offline
+                .sync_interval_seconds,
+            7
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
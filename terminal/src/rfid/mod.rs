@@ -1,16 +1,15 @@
 use log::{debug, info, warn};
 use std::sync::mpsc;
 use std::thread;
-use std::time::{Duration, Instant};
 
+/// Raw tag reads.  Repeated reads are filtered by the engine
+/// ([`crate::engine::Engine::read`]), the single place every scan passes through.
 pub struct RfidReader {
     receiver: mpsc::Receiver<String>,
-    debounce_ms: u64,
-    last_scan: Option<(String, Instant)>,
 }
 
 impl RfidReader {
-    pub fn new(input_device: &str, debounce_ms: u64) -> Self {
+    pub fn new(input_device: &str) -> Self {
         let (sender, receiver) = mpsc::channel();
         let device = input_device.to_string();
 
@@ -19,11 +18,7 @@ impl RfidReader {
             Self::read_stdin(sender);
         });
 
-        Self {
-            receiver,
-            debounce_ms,
-            last_scan: None,
-        }
+        Self { receiver }
     }
 
     fn read_stdin(sender: mpsc::Sender<String>) {
@@ -50,23 +45,6 @@ impl RfidReader {
     }
 
     pub fn poll(&mut self) -> Option<String> {
-        while let Ok(tag_id) = self.receiver.try_recv() {
-            let now = Instant::now();
-
-            let is_duplicate = self
-                .last_scan
-                .as_ref()
-                .map(|(last_id, last_time)| {
-                    last_id == &tag_id
-                        && last_time.elapsed() < Duration::from_millis(self.debounce_ms)
-                })
-                .unwrap_or(false);
-
-            if !is_duplicate {
-                self.last_scan = Some((tag_id.clone(), now));
-                return Some(tag_id);
-            }
-        }
-        None
+        self.receiver.try_recv().ok()
     }
 }
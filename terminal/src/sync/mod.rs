@@ -0,0 +1,93 @@
+use log::{info, warn};
+use std::sync::{Arc, Mutex};
+
+use crate::api::{ApiClient, ApiError};
+use crate::buffer::{EventBuffer, EventKind};
+
+/// Attempts to sync all pending buffered events with the API.
+/// Returns the number of events successfully synced, or `None` if another sync holds the
+/// buffer's sync lock.
+///
+/// Offline events are replayed in FIFO order.  Three outcomes are possible for each event:
+///
+/// * **Success** — the server accepted it; mark synced.
+/// * **Network/timeout** — backend still unreachable; stop and retry on the next tick.
+/// * **409 Conflict** — another terminal already changed this employee's status while this
+///   terminal was offline.  The event is stale; discard it so it is never retried.
+/// * **404 Not found** — the RFID tag was deregistered while offline; discard.
+/// * **Other server error** — discard to avoid blocking the queue indefinitely.
+pub async fn sync_buffered_events(api: ApiClient, buffer: Arc<Mutex<EventBuffer>>) -> Option<u32> {
+    let (_lock, events) = {
+        let buf = buffer.lock().ok()?;
+        let lock = match buf.try_sync_lock() {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                info!("Buffer sync skipped: another sync is running");
+                return None;
+            }
+            Err(e) => {
+                warn!("Buffer sync skipped: cannot take the sync lock: {}", e);
+                return None;
+            }
+        };
+        (lock, buf.get_pending().unwrap_or_default())
+    };
+
+    let mut synced = 0u32;
+    for event in &events {
+        if let Some(id) = event.id {
+            let result = match event.kind {
+                EventKind::Clock => api
+                    .clock_in_out(&event.rfid_tag_id, &event.terminal_id)
+                    .await
+                    .map(|_| ()),
+                EventKind::VerifiedClock => {
+                    api.replay_verified_scan(
+                        &event.rfid_tag_id,
+                        &event.terminal_id,
+                        event.timestamp,
+                    )
+                    .await
+                }
+                EventKind::PinClock => {
+                    api.replay_pin_clock(
+                        event.employee_number.as_deref().unwrap_or_default(),
+                        &event.terminal_id,
+                        event.timestamp,
+                    )
+                    .await
+                }
+                EventKind::ProjectSwitch => {
+                    api.switch_project(
+                        &event.rfid_tag_id,
+                        &event.terminal_id,
+                        event.project_id.as_deref().unwrap_or_default(),
+                        event.timestamp,
+                    )
+                    .await
+                }
+            };
+            match result {
+                Ok(()) => {
+                    if let Ok(buf) = buffer.lock() {
+                        let _ = buf.mark_synced(id);
+                    }
+                    synced += 1;
+                }
+                // Backend unreachable — stop and retry later.
+                Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => break,
+                // Stale or invalid events — discard to keep the queue moving.
+                Err(ApiError::Conflict) | Err(ApiError::NotFound(_)) | Err(_) => {
+                    warn!(
+                        "Discarding buffered event id={} rfid={}: already processed or invalid",
+                        id, event.rfid_tag_id
+                    );
+                    if let Ok(buf) = buffer.lock() {
+                        let _ = buf.mark_synced(id);
+                    }
+                }
+            }
+        }
+    }
+    Some(synced)
+}
@@ -0,0 +1,167 @@
+/// Screen geometry the views scale against.
+///
+/// All sizes in `screens` are written for the 1024x600 reference display (600x1024 in portrait)
+/// and multiplied by `scale`, which follows the window size and `display.font_scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub width: f32,
+    pub height: f32,
+    /// Taller than wide — summary items are stacked instead of placed side by side.
+    pub portrait: bool,
+    scale: f32,
+    min_text_size: f32,
+}
+
+const REFERENCE_LONG_SIDE: f32 = 1024.0;
+const REFERENCE_SHORT_SIDE: f32 = 600.0;
+/// Nothing on screen is rendered smaller than this, whatever the scale.
+const MIN_TEXT_SIZE: f32 = 12.0;
+/// Floor for the high-contrast themes, so countdowns and labels stay readable from a distance.
+pub const ACCESSIBLE_MIN_TEXT_SIZE: f32 = 22.0;
+
+impl Layout {
+    pub fn new(width: f32, height: f32, font_scale: f32) -> Self {
+        let portrait = height > width;
+        let (reference_width, reference_height) = if portrait {
+            (REFERENCE_SHORT_SIDE, REFERENCE_LONG_SIDE)
+        } else {
+            (REFERENCE_LONG_SIDE, REFERENCE_SHORT_SIDE)
+        };
+        let window_scale = (width / reference_width).min(height / reference_height);
+        // Guard against typos such as `font_scale = 0` or `12` in terminal.toml.
+        let font_scale = if font_scale.is_finite() {
+            font_scale.clamp(0.5, 3.0)
+        } else {
+            1.0
+        };
+
+        Self {
+            width,
+            height,
+            portrait,
+            scale: (window_scale * font_scale).max(0.1),
+            min_text_size: MIN_TEXT_SIZE,
+        }
+    }
+
+    /// Raises the smallest text size, e.g. for the high-contrast themes.
+    pub fn with_min_text_size(mut self, size: f32) -> Self {
+        self.min_text_size = size.max(MIN_TEXT_SIZE);
+        self
+    }
+
+    /// Scaled text size for a size designed on the reference display.
+    pub fn text(&self, reference_size: f32) -> f32 {
+        (reference_size * self.scale)
+            .round()
+            .max(self.min_text_size)
+    }
+
+    /// Scaled spacing, padding or widget size.
+    pub fn space(&self, reference_size: f32) -> f32 {
+        (reference_size * self.scale).round()
+    }
+}
+
+/// Window size for the configured resolution and orientation.  A landscape resolution such as
+/// `1024x600` is turned into `600x1024` for portrait mounts and vice versa, so the same
+/// resolution string works for both.
+pub fn window_size(width: u32, height: u32, orientation: &str) -> (u32, u32) {
+    let (long, short) = (width.max(height), width.min(height));
+    if orientation.eq_ignore_ascii_case("portrait") {
+        (short, long)
+    } else {
+        (long, short)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::screens::{IDLE_SPACING, IDLE_TEXT_SIZES};
+
+    fn idle_screen_height(layout: &Layout) -> f32 {
+        // Text lines are roughly 1.3x their font size high.
+        IDLE_TEXT_SIZES
+            .iter()
+            .map(|s| layout.text(*s) * 1.3)
+            .chain(IDLE_SPACING.iter().map(|s| layout.space(*s)))
+            .sum()
+    }
+
+    /// Snapshot of the scaled sizes per supported display.  Update deliberately when the
+    /// scaling rules change.
+    #[test]
+    fn test_layout_snapshots() {
+        let cases = [
+            // (width, height, font_scale, portrait, clock size, heading size, body size)
+            (1024.0, 600.0, 1.0, false, 80.0, 48.0, 24.0),
+            (800.0, 480.0, 1.0, false, 63.0, 38.0, 19.0),
+            (600.0, 1024.0, 1.0, true, 80.0, 48.0, 24.0),
+            (480.0, 800.0, 1.0, true, 63.0, 38.0, 19.0),
+            (1280.0, 720.0, 1.0, false, 96.0, 58.0, 29.0),
+            (1920.0, 1080.0, 1.0, false, 144.0, 86.0, 43.0),
+            (1024.0, 600.0, 1.5, false, 120.0, 72.0, 36.0),
+        ];
+
+        for (width, height, font_scale, portrait, clock, heading, body) in cases {
+            let layout = Layout::new(width, height, font_scale);
+            let actual = (
+                layout.portrait,
+                layout.text(80.0),
+                layout.text(48.0),
+                layout.text(24.0),
+            );
+            assert_eq!(
+                actual,
+                (portrait, clock, heading, body),
+                "layout snapshot for {}x{} @ {}",
+                width,
+                height,
+                font_scale
+            );
+        }
+    }
+
+    #[test]
+    fn test_idle_screen_fits_every_supported_display() {
+        for (width, height) in [
+            (800.0, 480.0),
+            (1024.0, 600.0),
+            (600.0, 1024.0),
+            (480.0, 800.0),
+            (1280.0, 720.0),
+            (1920.0, 1080.0),
+        ] {
+            let layout = Layout::new(width, height, 1.0);
+            assert!(
+                idle_screen_height(&layout) < height,
+                "idle screen overflows {}x{}",
+                width,
+                height
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_text_size_and_font_scale_guard() {
+        let tiny = Layout::new(320.0, 240.0, 0.5);
+        assert_eq!(tiny.text(16.0), MIN_TEXT_SIZE);
+
+        let typo = Layout::new(1024.0, 600.0, 12.0);
+        assert_eq!(typo.text(10.0), 30.0, "font_scale is clamped to 3.0");
+
+        let accessible =
+            Layout::new(800.0, 480.0, 1.0).with_min_text_size(ACCESSIBLE_MIN_TEXT_SIZE);
+        assert_eq!(accessible.text(18.0), ACCESSIBLE_MIN_TEXT_SIZE);
+        assert_eq!(accessible.text(80.0), 63.0);
+    }
+
+    #[test]
+    fn test_window_size_follows_orientation() {
+        assert_eq!(window_size(1024, 600, "landscape"), (1024, 600));
+        assert_eq!(window_size(1024, 600, "portrait"), (600, 1024));
+        assert_eq!(window_size(600, 1024, "landscape"), (1024, 600));
+        assert_eq!(window_size(600, 1024, "Portrait"), (600, 1024));
+    }
+}
@@ -1,18 +1,39 @@
+pub mod layout;
+pub mod palette;
 pub mod screens;
 
 use chrono::{DateTime, Utc};
 use iced::futures::SinkExt;
+use iced::widget::qr_code;
 use iced::{executor, Application, Command, Element, Settings, Size, Subscription, Theme};
-use log::{info, warn};
+use log::{debug, info, warn};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::api::{ApiClient, ApiError, ClockResponse};
-use crate::audio::AudioPlayer;
-use crate::buffer::EventBuffer;
-use crate::config::AppConfig;
+use crate::api::{
+    ApiError, BlockedBadge, ClockResponse, EmployeeInfo, EmployeeStatus, Project, RosterEntry,
+};
+use crate::audio::SoundEvent;
+use crate::branding::{self, Branding, BrandingStore};
+use crate::buffer::Removal;
+use crate::config::{AppConfig, DisplayConfig};
+use crate::engine::machine::{ClockOutcome, Effect, Input, Phase};
+use crate::engine::{Engine, UndoTarget};
+use crate::maintenance::{self, ScanOutcome};
+use crate::photos::{self, PhotoCache};
+use crate::pin::{self, PinRateLimiter};
+use crate::projects;
+use crate::qr;
+use crate::reload::{self, RejectedConfig};
 use crate::rfid::RfidReader;
-use screens::{ClockInData, ClockOutData, ErrorData, ErrorType};
+use layout::Layout;
+use palette::Palette;
+use screens::{
+    AbsenceItem, ClockInData, ClockOutData, ErrorData, ErrorType, InfoData, MaintenanceData,
+    PhotoState, PinEntryData, PinStep, ReaderTest,
+};
 
 // ─── Messages ────────────────────────────────────────────────────────────────
 
@@ -25,13 +46,127 @@ pub enum Message {
     /// An RFID tag was scanned.
     RfidScanned(String),
     /// API response received after a scan.
-    ScanResult(Result<ClockResponse, ApiError>),
+    ScanResult {
+        rfid: String,
+        result: Result<ClockResponse, ApiError>,
+    },
+    /// "Kontostand anzeigen" pressed on the idle screen.
+    InfoModeRequested,
+    /// The info prompt was cancelled before a badge was scanned.
+    InfoModeCancelled,
+    /// Read-only status response received after an info scan.
+    InfoResult(Result<EmployeeStatus, ApiError>),
+    /// No second read of this tag arrived within the double-scan window; clock it.
+    DoubleScanWindowElapsed(String),
+    /// "Ja, ausstempeln" (or "einstempeln") on the cooldown question.
+    RepeatConfirmed,
+    /// "Abbrechen" on the cooldown question.
+    RepeatCancelled,
+    /// "Rückgängig" on a clock-in/out confirmation.
+    UndoRequested,
+    /// Backend answer to taking back a booked clocking.
+    UndoResult(Result<(), ApiError>),
+    /// Try again to take back this buffered event, which a sync held when "Rückgängig" was
+    /// pressed.
+    UndoRetry(i64),
+    /// Periodic trigger to sync the company branding.
+    BrandingRefreshTick,
+    /// Branding sync finished.
+    BrandingLoaded(Result<Branding, ApiError>),
+    /// Background photo download finished; `path` is `None` if it failed.
+    PhotoLoaded {
+        employee_id: String,
+        path: Option<PathBuf>,
+    },
+    /// "Ausweis vergessen? PIN eingeben" pressed on the idle screen.
+    PinEntryRequested,
+    /// A digit was pressed on the PIN keypad.
+    PinDigit(u8),
+    /// "⌫" on the PIN keypad.
+    PinBackspace,
+    /// "OK" on the PIN keypad: confirms the employee number, then the PIN.
+    PinSubmit,
+    /// The PIN keypad was cancelled.
+    PinCancelled,
+    /// API response received after a PIN clocking.
+    PinResult(Result<ClockResponse, ApiError>),
+    /// API response to a badge scan with PIN in two-factor mode.  `offline_check` is the
+    /// result of checking the PIN against the cached roster, done only when the backend
+    /// was unreachable and the roster has a hash for the badge.
+    BadgePinResult {
+        result: Result<ClockResponse, ApiError>,
+        offline_check: Option<bool>,
+    },
+    /// Periodic trigger to fetch the remote configuration.
+    RemoteConfigTick,
+    /// Remote configuration document fetched from the backend.
+    RemoteConfigLoaded(Result<toml::Table, ApiError>),
+    /// The configuration file changed or SIGHUP was received; contains the re-read and
+    /// validated configuration.
+    ConfigReloaded(Box<Result<AppConfig, RejectedConfig>>),
+    /// Periodic trigger to refresh the two-factor roster.
+    RosterRefreshTick,
+    /// Two-factor roster fetched from the backend.
+    RosterLoaded(Result<Vec<RosterEntry>, ApiError>),
+    /// Periodic trigger to refresh the blocklist of revoked badges.
+    BlocklistRefreshTick,
+    /// Blocklist fetched from the backend.
+    BlocklistLoaded(Result<Vec<BlockedBadge>, ApiError>),
+    /// The backend's answer to the alert sent when the reader locked itself.
+    ScanAlertReported(Result<(), ApiError>),
+    /// IP address and backend reachability for the maintenance screen.
+    MaintenanceDiagnostics {
+        ip: Option<IpAddr>,
+        backend_reachable: bool,
+    },
+    /// "Jetzt synchronisieren" on the maintenance screen.
+    MaintenanceSyncNow,
+    /// "Ton testen" on the maintenance screen.
+    MaintenanceTestSound,
+    /// "Leser testen": the next scan is shown instead of being processed.
+    MaintenanceTestReader,
+    /// "Konfiguration neu laden" on the maintenance screen.
+    MaintenanceReloadConfig,
+    /// Leaves the maintenance screen.
+    MaintenanceExit,
+    /// The window was resized (or rotated); contains the new width and height.
+    WindowResized(u32, u32),
     /// Background sync completed; contains the number of events synced.
     SyncComplete(u32),
+    /// Periodic trigger to refresh the cached project list.
+    ProjectRefreshTick,
+    /// Project list fetched from the backend.
+    ProjectsLoaded(Result<Vec<Project>, ApiError>),
+    /// "Projekt wählen" pressed on a confirmation screen.
+    ProjectSelectOpened,
+    /// Search text on the project selection screen changed.
+    ProjectSearchChanged(String),
+    /// A project was tapped on the selection screen; contains the project ID.
+    ProjectChosen(String),
+    /// The project selection was cancelled.
+    ProjectSelectCancelled,
+    /// API response received after a project switch.
+    ProjectSwitchResult {
+        rfid: String,
+        project_id: String,
+        /// When the project was chosen; buffered with the switch if the backend is down.
+        timestamp: DateTime<Utc>,
+        result: Result<(), ApiError>,
+    },
 }
 
 // ─── Application state ───────────────────────────────────────────────────────
 
+/// The "Rückgängig" button of a confirmation, shown for `seconds_left` more ticks.
+struct UndoOffer {
+    target: UndoTarget,
+    /// `None` for PIN clockings.
+    rfid: Option<String>,
+    seconds_left: u64,
+    /// Pressed while a sync held the buffer; tried again until the button times out.
+    retrying: bool,
+}
+
 enum AppState {
     Idle {
         now: DateTime<Utc>,
@@ -39,40 +174,128 @@ enum AppState {
     Loading {
         rfid: String,
     },
+    /// First read of a badge while double-scan detection is enabled; a second read of the same
+    /// tag within the window switches to the info screen instead of clocking.
+    AwaitingSecondScan {
+        rfid: String,
+    },
+    /// The badge was clocked moments ago and read again; asks before clocking it again.
+    /// `last_entry` is the entry type of that clocking, `None` if it was buffered.
+    ConfirmRepeat {
+        rfid: String,
+        last_entry: Option<String>,
+        seconds_left: u64,
+    },
+    /// Waiting for the response to a PIN clocking.
+    PinLoading {
+        employee_number: String,
+    },
+    /// Info mode was requested on the idle screen; the next scan only looks up balances.
+    InfoPrompt {
+        seconds_left: u64,
+    },
+    /// Read-only balance overview; no time entry was created.
+    Info {
+        data: InfoData,
+        seconds_left: u64,
+    },
     ClockIn {
+        /// `None` for PIN clockings; project selection needs a badge.
+        rfid: Option<String>,
         data: ClockInData,
+        undo: Option<UndoOffer>,
         seconds_left: u64,
     },
     ClockOut {
         data: ClockOutData,
+        undo: Option<UndoOffer>,
         seconds_left: u64,
     },
     /// Event was stored offline; shown with amber colour scheme.
     OfflineConfirm {
+        rfid: Option<String>,
         data: ClockInData,
+        undo: Option<UndoOffer>,
+        seconds_left: u64,
+    },
+    /// Waiting for the backend to take back a booked clocking.
+    Undoing {
+        rfid: Option<String>,
+    },
+    /// The clocking was taken back.
+    Undone {
+        seconds_left: u64,
+    },
+    /// Too many unknown or revoked badges; every read is ignored until the countdown ends.
+    LockedOut {
+        seconds_left: u64,
+    },
+    /// Touch list of projects for the employee who just scanned.
+    ProjectSelect {
+        rfid: String,
+        query: String,
+        /// Project IDs recently used with this badge, most recent first.
+        favourites: Vec<String>,
+        seconds_left: u64,
+    },
+    /// Project switch was submitted (or buffered); shows the chosen project.
+    ProjectConfirm {
+        project_name: String,
         seconds_left: u64,
     },
     Error {
         data: ErrorData,
         seconds_left: u64,
     },
+    /// Employee number and PIN keypad for employees who forgot their badge, or the PIN for
+    /// `rfid` in two-factor mode.  The PIN is only held here until it is submitted.
+    PinEntry {
+        rfid: Option<String>,
+        data: PinEntryData,
+        pin: String,
+        seconds_left: u64,
+    },
+    /// Diagnostics opened with an admin badge; locks itself after `seconds_left` without input.
+    Maintenance {
+        data: MaintenanceData,
+        seconds_left: u64,
+    },
 }
 
 struct TerminalApp {
     state: AppState,
     config: AppConfig,
-    api_client: ApiClient,
-    event_buffer: Arc<Mutex<EventBuffer>>,
-    audio: AudioPlayer,
+    /// Backend, buffer, audio feedback and scan log; everything but the screens.
+    engine: Engine,
     rfid_reader: Arc<Mutex<RfidReader>>,
-    /// Number of buffered events waiting to be synced.
-    pending_count: u32,
-    /// Whether the last API call succeeded.
-    is_online: bool,
-    /// Identifier sent with every scan request.
-    terminal_id: String,
-    /// Debounce tracking: (last_tag, instant it was scanned).
-    last_scan_time: Option<(String, std::time::Instant)>,
+    /// Projects offered on the selection screen; backed by the buffer's cache while offline.
+    projects: Vec<Project>,
+    /// Employee photo cache; `None` when photos are disabled.
+    photo_cache: Option<Arc<Mutex<PhotoCache>>>,
+    /// Branding last synced from the backend; empty until the first sync.
+    branding: Branding,
+    /// Screen colours derived from the theme and the brand colours.
+    palette: Palette,
+    /// Text and spacing scale for the current window size and orientation.
+    layout: Layout,
+    /// File the configuration was loaded from; re-read when it changes, on SIGHUP and by
+    /// "Konfiguration neu laden".
+    config_path: PathBuf,
+    /// Wrong PIN attempts per employee number.
+    pin_limiter: PinRateLimiter,
+    /// Wrong PIN attempts per badge in two-factor mode.
+    badge_pin_limiter: PinRateLimiter,
+    /// Rotating code on the idle screen; `None` when disabled.
+    presence_qr: Option<PresenceQr>,
+    /// End of a lockout triggered while a request was in flight; its screen follows once
+    /// the request has settled.
+    pending_lockout: Option<Instant>,
+}
+
+/// The idle screen QR code of the current time window.
+struct PresenceQr {
+    window: u64,
+    data: qr_code::Data,
 }
 
 // ─── Application trait ───────────────────────────────────────────────────────
@@ -81,93 +304,214 @@ impl Application for TerminalApp {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = AppConfig;
+    type Flags = (AppConfig, PathBuf);
+
+    fn new((config, config_path): (AppConfig, PathBuf)) -> (Self, Command<Message>) {
+        let engine = Engine::new(&config);
 
-    fn new(config: AppConfig) -> (Self, Command<Message>) {
-        let api_client = ApiClient::new(&config.api);
+        let projects = engine
+            .buffer
+            .lock()
+            .map(|buf| buf.cached_projects().unwrap_or_default())
+            .unwrap_or_default();
 
-        let event_buffer = Arc::new(Mutex::new(
-            EventBuffer::new(&config.offline.buffer_path, config.offline.max_buffer_size)
-                .unwrap_or_else(|e| {
+        let photo_cache = if config.photos.enabled {
+            match PhotoCache::new(&config.photos) {
+                Ok(cache) => Some(Arc::new(Mutex::new(cache))),
+                Err(e) => {
                     warn!(
-                        "Cannot open event buffer at '{}': {}. Falling back to in-memory.",
-                        config.offline.buffer_path, e
+                        "Cannot open photo cache at '{}': {}. Photos disabled.",
+                        config.photos.cache_dir, e
                     );
-                    EventBuffer::new(":memory:", config.offline.max_buffer_size)
-                        .expect("in-memory buffer must always succeed")
-                }),
-        ));
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-        let pending_count = event_buffer
-            .lock()
-            .map(|buf| buf.pending_count().unwrap_or(0))
-            .unwrap_or(0);
+        let branding = if config.company.sync_branding {
+            BrandingStore::new(&config.company.branding_cache_dir).load()
+        } else {
+            Branding::default()
+        };
+        let palette = Palette::new(
+            &config.display.theme,
+            branding.primary_color.as_deref(),
+            branding.accent_color.as_deref(),
+        );
 
-        let audio = AudioPlayer::new(config.audio.clone());
+        let (width, height) = layout::window_size(
+            config.display.resolution_width(),
+            config.display.resolution_height(),
+            &config.display.orientation,
+        );
+        let layout = screen_layout(&config.display, width, height);
 
-        let rfid_reader = Arc::new(Mutex::new(RfidReader::new(
-            &config.rfid.input_device,
-            config.rfid.debounce_ms,
-        )));
+        let rfid_reader = Arc::new(Mutex::new(RfidReader::new(&config.rfid.input_device)));
 
-        let terminal_id = config.api.terminal_id.clone();
+        let pin_limiter = PinRateLimiter::new(&config.pin);
+        let badge_pin_limiter = PinRateLimiter::new(&config.pin);
 
-        let app = TerminalApp {
+        let mut app = TerminalApp {
             state: AppState::Idle { now: Utc::now() },
             config,
-            api_client,
-            event_buffer,
-            audio,
+            engine,
             rfid_reader,
-            pending_count,
-            is_online: true,
-            terminal_id,
-            last_scan_time: None,
+            projects,
+            photo_cache,
+            branding,
+            palette,
+            layout,
+            config_path,
+            pin_limiter,
+            badge_pin_limiter,
+            presence_qr: None,
+            pending_lockout: None,
         };
 
+        if app.config.qr.enabled && app.config.qr.secret.is_empty() {
+            warn!("qr.enabled is set but qr.secret is empty; the idle screen QR code is hidden");
+        }
+        app.update_presence_qr(Utc::now());
+
         info!("Terminal application started");
-        (app, Command::none())
+        let mut commands = Vec::new();
+        if app.config.projects.enabled {
+            commands.push(app.refresh_projects());
+        }
+        if app.config.company.sync_branding {
+            commands.push(app.refresh_branding());
+        }
+        if app.config.pin.require_with_badge {
+            commands.push(app.refresh_roster());
+        }
+        commands.push(app.refresh_blocklist());
+        if app.config.remote.enabled {
+            commands.push(app.fetch_remote_config());
+        }
+        (app, Command::batch(commands))
     }
 
     fn title(&self) -> String {
-        format!("Zeiterfassung Terminal — {}", self.config.company.name)
+        format!("Zeiterfassung Terminal — {}", self.company_name())
     }
 
     fn theme(&self) -> Theme {
-        if self.config.display.theme == "light" {
-            Theme::Light
-        } else {
-            Theme::Dark
-        }
+        self.palette.theme()
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
-        match message {
-            Message::Tick => self.handle_tick(),
-            Message::SyncTick => self.handle_sync_tick(),
-            Message::RfidScanned(tag_id) => self.handle_rfid_scanned(tag_id),
-            Message::ScanResult(result) => self.handle_scan_result(result),
-            Message::SyncComplete(count) => self.handle_sync_complete(count),
-        }
+        let command = self.handle_message(message);
+        self.show_pending_lockout();
+        let next = self.sync_machine();
+        Command::batch([command, next])
     }
 
     fn view(&self) -> Element<'_, Message, Theme, iced::Renderer> {
+        let palette = &self.palette;
+        let layout = &self.layout;
         match &self.state {
             AppState::Idle { now } => screens::idle_view(
                 now,
-                &self.config.company.name,
-                self.pending_count,
-                !self.is_online,
+                self.company_name(),
+                self.logo_path(),
+                self.engine.pending_count,
+                !self.engine.is_online,
+                self.config.info.enabled,
+                self.config.pin.enabled,
+                self.presence_qr.as_ref().map(|qr| &qr.data),
+                palette,
+                layout,
+            ),
+            AppState::Loading { .. }
+            | AppState::AwaitingSecondScan { .. }
+            | AppState::PinLoading { .. } => screens::loading_view(self.engine.queued(), layout),
+            AppState::PinEntry {
+                data, seconds_left, ..
+            } => screens::pin_entry_view(data, *seconds_left, palette, layout),
+            AppState::ConfirmRepeat {
+                last_entry,
+                seconds_left,
+                ..
+            } => {
+                screens::confirm_repeat_view(last_entry.as_deref(), *seconds_left, palette, layout)
+            }
+            AppState::InfoPrompt { seconds_left } => {
+                screens::info_prompt_view(*seconds_left, palette, layout)
+            }
+            AppState::Info { data, seconds_left } => {
+                screens::info_view(data, *seconds_left, palette, layout)
+            }
+            AppState::ClockIn {
+                rfid,
+                data,
+                undo,
+                seconds_left,
+            } => screens::clock_in_view(
+                data,
+                *seconds_left,
+                self.config.projects.enabled && rfid.is_some(),
+                undo.as_ref().map(|offer| offer.seconds_left),
+                self.engine.queued(),
+                palette,
+                layout,
+            ),
+            AppState::ClockOut {
+                data,
+                undo,
+                seconds_left,
+            } => screens::clock_out_view(
+                data,
+                *seconds_left,
+                undo.as_ref().map(|offer| offer.seconds_left),
+                self.engine.queued(),
+                palette,
+                layout,
             ),
-            AppState::Loading { .. } => screens::loading_view(),
-            AppState::ClockIn { data, seconds_left } => screens::clock_in_view(data, *seconds_left),
-            AppState::ClockOut { data, seconds_left } => {
-                screens::clock_out_view(data, *seconds_left)
+            AppState::OfflineConfirm {
+                rfid,
+                data,
+                undo,
+                seconds_left,
+            } => screens::offline_confirm_view(
+                data,
+                *seconds_left,
+                self.config.projects.enabled && rfid.is_some(),
+                undo.as_ref().map(|offer| offer.seconds_left),
+                self.engine.queued(),
+                palette,
+                layout,
+            ),
+            AppState::Undoing { .. } => screens::loading_view(0, layout),
+            AppState::Undone { seconds_left } => {
+                screens::undone_view(*seconds_left, palette, layout)
+            }
+            AppState::LockedOut { seconds_left } => {
+                screens::locked_out_view(*seconds_left, palette, layout)
+            }
+            AppState::ProjectSelect {
+                query,
+                favourites,
+                seconds_left,
+                ..
+            } => screens::project_select_view(
+                projects::select_projects(&self.projects, favourites, query),
+                query,
+                *seconds_left,
+                palette,
+                layout,
+            ),
+            AppState::ProjectConfirm {
+                project_name,
+                seconds_left,
+            } => screens::project_confirm_view(project_name, *seconds_left, palette, layout),
+            AppState::Error { data, seconds_left } => {
+                screens::error_view(data, *seconds_left, self.engine.queued(), palette, layout)
             }
-            AppState::OfflineConfirm { data, seconds_left } => {
-                screens::offline_confirm_view(data, *seconds_left)
+            AppState::Maintenance { data, seconds_left } => {
+                screens::maintenance_view(data, *seconds_left, palette, layout)
             }
-            AppState::Error { data, seconds_left } => screens::error_view(data, *seconds_left),
         }
     }
 
@@ -179,14 +523,251 @@ impl Application for TerminalApp {
 
         let rfid = rfid_subscription(Arc::clone(&self.rfid_reader));
 
-        Subscription::batch(vec![tick, sync_tick, rfid])
+        let resize = iced::event::listen_with(|event, _| match event {
+            iced::Event::Window(_, iced::window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            _ => None,
+        });
+
+        let config_watch = config_watch_subscription(self.config_path.clone());
+
+        let blocklist_interval =
+            Duration::from_secs(self.config.rfid.blocklist_refresh_minutes.max(1) * 60);
+        let blocklist_tick =
+            iced::time::every(blocklist_interval).map(|_| Message::BlocklistRefreshTick);
+
+        let mut subscriptions = vec![tick, sync_tick, rfid, resize, config_watch, blocklist_tick];
+        if self.config.company.sync_branding {
+            let branding_interval =
+                Duration::from_secs(self.config.company.branding_refresh_minutes.max(1) * 60);
+            subscriptions
+                .push(iced::time::every(branding_interval).map(|_| Message::BrandingRefreshTick));
+        }
+        if self.config.remote.enabled {
+            let remote_interval =
+                Duration::from_secs(self.config.remote.refresh_minutes.max(1) * 60);
+            subscriptions
+                .push(iced::time::every(remote_interval).map(|_| Message::RemoteConfigTick));
+        }
+        if self.config.pin.require_with_badge {
+            let roster_interval =
+                Duration::from_secs(self.config.pin.roster_refresh_minutes.max(1) * 60);
+            subscriptions
+                .push(iced::time::every(roster_interval).map(|_| Message::RosterRefreshTick));
+        }
+        if self.config.projects.enabled {
+            let refresh_interval =
+                Duration::from_secs(self.config.projects.refresh_interval_seconds.max(60));
+            subscriptions
+                .push(iced::time::every(refresh_interval).map(|_| Message::ProjectRefreshTick));
+        }
+
+        Subscription::batch(subscriptions)
     }
 }
 
 // ─── Update helpers ───────────────────────────────────────────────────────────
 
 impl TerminalApp {
+    fn handle_message(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Tick => self.handle_tick(),
+            Message::SyncTick => self.handle_sync_tick(),
+            Message::RfidScanned(tag_id) => self.handle_rfid_scanned(tag_id),
+            Message::ScanResult { rfid, result } => {
+                let effects = self.engine.handle(Input::Response { rfid, result });
+                self.apply_effects(effects)
+            }
+            Message::InfoModeRequested => {
+                if matches!(self.state, AppState::Idle { .. }) {
+                    self.state = AppState::InfoPrompt {
+                        seconds_left: self.config.display.confirmation_seconds(),
+                    };
+                }
+                Command::none()
+            }
+            Message::RepeatConfirmed => match &self.state {
+                AppState::ConfirmRepeat { rfid, .. } => {
+                    let effects = self.engine.handle(Input::Confirmed(rfid.clone()));
+                    self.apply_effects(effects)
+                }
+                _ => Command::none(),
+            },
+            Message::UndoRequested => self.handle_undo_requested(),
+            Message::UndoRetry(id) => self.handle_undo_retry(id),
+            Message::UndoResult(result) => {
+                self.handle_undo_result(result);
+                Command::none()
+            }
+            Message::RepeatCancelled => {
+                if matches!(self.state, AppState::ConfirmRepeat { .. }) {
+                    self.state = AppState::Idle { now: Utc::now() };
+                }
+                Command::none()
+            }
+            Message::InfoModeCancelled => {
+                if matches!(self.state, AppState::InfoPrompt { .. }) {
+                    self.state = AppState::Idle { now: Utc::now() };
+                }
+                Command::none()
+            }
+            Message::InfoResult(result) => self.handle_info_result(result),
+            Message::PinEntryRequested => {
+                if self.config.pin.enabled && matches!(self.state, AppState::Idle { .. }) {
+                    self.state = AppState::PinEntry {
+                        rfid: None,
+                        data: PinEntryData {
+                            step: PinStep::EmployeeNumber,
+                            employee_number: String::new(),
+                            pin_length: 0,
+                            keypad: pin::keypad(&self.config.pin),
+                            message: None,
+                        },
+                        pin: String::new(),
+                        seconds_left: self.config.pin.entry_timeout_seconds,
+                    };
+                }
+                Command::none()
+            }
+            Message::PinDigit(digit) => self.handle_pin_digit(digit),
+            Message::PinBackspace => {
+                if let AppState::PinEntry {
+                    data,
+                    pin,
+                    seconds_left,
+                    ..
+                } = &mut self.state
+                {
+                    match data.step {
+                        PinStep::EmployeeNumber => {
+                            data.employee_number.pop();
+                        }
+                        PinStep::Pin | PinStep::BadgePin => {
+                            pin.pop();
+                            data.pin_length = pin.len();
+                        }
+                    }
+                    *seconds_left = self.config.pin.entry_timeout_seconds;
+                }
+                Command::none()
+            }
+            Message::PinSubmit => self.handle_pin_submit(),
+            Message::PinCancelled => {
+                if matches!(self.state, AppState::PinEntry { .. }) {
+                    self.state = AppState::Idle { now: Utc::now() };
+                }
+                Command::none()
+            }
+            Message::PinResult(result) => self.handle_pin_result(result),
+            Message::BadgePinResult {
+                result,
+                offline_check,
+            } => self.handle_badge_pin_result(result, offline_check),
+            Message::RemoteConfigTick => self.fetch_remote_config(),
+            Message::RemoteConfigLoaded(result) => self.handle_remote_config_loaded(result),
+            Message::ConfigReloaded(result) => {
+                let _ = self.apply_reloaded_config(*result);
+                Command::none()
+            }
+            Message::RosterRefreshTick => self.refresh_roster(),
+            Message::RosterLoaded(result) => self.handle_roster_loaded(result),
+            Message::BlocklistRefreshTick => self.refresh_blocklist(),
+            Message::BlocklistLoaded(result) => self.handle_blocklist_loaded(result),
+            Message::ScanAlertReported(result) => {
+                if let Err(err) = result {
+                    warn!("Cannot report the reader lockout to the backend: {}", err);
+                }
+                Command::none()
+            }
+            Message::BrandingRefreshTick => self.refresh_branding(),
+            Message::BrandingLoaded(result) => self.handle_branding_loaded(result),
+            Message::PhotoLoaded { employee_id, path } => {
+                self.handle_photo_loaded(employee_id, path)
+            }
+            Message::DoubleScanWindowElapsed(tag_id) => {
+                let effects = self.engine.handle(Input::WindowElapsed(tag_id));
+                self.apply_effects(effects)
+            }
+            Message::MaintenanceDiagnostics {
+                ip,
+                backend_reachable,
+            } => {
+                if let AppState::Maintenance { data, .. } = &mut self.state {
+                    data.ip = ip.map(|ip| ip.to_string());
+                    data.backend_reachable = Some(backend_reachable);
+                }
+                Command::none()
+            }
+            Message::MaintenanceSyncNow => {
+                self.refresh_maintenance(Some("Synchronisierung gestartet"));
+                self.handle_sync_tick()
+            }
+            Message::MaintenanceTestSound => {
+                self.engine.audio.play_success();
+                self.refresh_maintenance(Some("Testton abgespielt"));
+                Command::none()
+            }
+            Message::MaintenanceTestReader => {
+                if let AppState::Maintenance { data, .. } = &mut self.state {
+                    data.reader_test = ReaderTest::Waiting;
+                }
+                self.refresh_maintenance(None);
+                Command::none()
+            }
+            Message::MaintenanceReloadConfig => self.reload_config(),
+            Message::MaintenanceExit => {
+                if matches!(self.state, AppState::Maintenance { .. }) {
+                    info!("Maintenance mode closed");
+                    self.state = AppState::Idle { now: Utc::now() };
+                }
+                Command::none()
+            }
+            Message::WindowResized(width, height) => {
+                self.layout = screen_layout(&self.config.display, width, height);
+                Command::none()
+            }
+            Message::SyncComplete(count) => self.handle_sync_complete(count),
+            Message::ProjectRefreshTick => self.refresh_projects(),
+            Message::ProjectsLoaded(result) => self.handle_projects_loaded(result),
+            Message::ProjectSelectOpened => self.handle_project_select_opened(),
+            Message::ProjectSearchChanged(query) => self.handle_project_search_changed(query),
+            Message::ProjectChosen(project_id) => self.handle_project_chosen(project_id),
+            Message::ProjectSelectCancelled => {
+                self.state = AppState::Idle { now: Utc::now() };
+                Command::none()
+            }
+            Message::ProjectSwitchResult {
+                rfid,
+                project_id,
+                timestamp,
+                result,
+            } => self.handle_project_switch_result(rfid, project_id, timestamp, result),
+        }
+    }
+
     fn handle_tick(&mut self) -> Command<Message> {
+        self.update_presence_qr(Utc::now());
+        let effects = self.engine.handle(Input::Tick);
+        let command = self.apply_effects(effects);
+        let mut undo_failed = false;
+        if let AppState::ClockIn { undo, .. }
+        | AppState::ClockOut { undo, .. }
+        | AppState::OfflineConfirm { undo, .. } = &mut self.state
+        {
+            if let Some(offer) = undo {
+                offer.seconds_left = offer.seconds_left.saturating_sub(1);
+                if offer.seconds_left == 0 {
+                    undo_failed = offer.retrying;
+                    *undo = None;
+                }
+            }
+        }
+        // "Rückgängig" was pressed, but the sync held the buffer until the button timed out.
+        if undo_failed {
+            warn!("Buffered clocking not taken back: the buffer stayed busy");
+            self.show_undo_error("Buchung konnte nicht zur\u{00FC}ckgenommen werden");
+        }
         let return_to_idle = match &mut self.state {
             AppState::Idle { now } => {
                 *now = Utc::now();
@@ -195,7 +776,16 @@ impl TerminalApp {
             AppState::ClockIn { seconds_left, .. }
             | AppState::ClockOut { seconds_left, .. }
             | AppState::OfflineConfirm { seconds_left, .. }
-            | AppState::Error { seconds_left, .. } => {
+            | AppState::ProjectSelect { seconds_left, .. }
+            | AppState::ProjectConfirm { seconds_left, .. }
+            | AppState::InfoPrompt { seconds_left }
+            | AppState::ConfirmRepeat { seconds_left, .. }
+            | AppState::Undone { seconds_left }
+            | AppState::LockedOut { seconds_left }
+            | AppState::Info { seconds_left, .. }
+            | AppState::Error { seconds_left, .. }
+            | AppState::PinEntry { seconds_left, .. }
+            | AppState::Maintenance { seconds_left, .. } => {
                 if *seconds_left > 0 {
                     *seconds_left -= 1;
                     false
@@ -203,275 +793,1756 @@ impl TerminalApp {
                     true
                 }
             }
-            AppState::Loading { .. } => false,
+            AppState::Loading { .. }
+            | AppState::AwaitingSecondScan { .. }
+            | AppState::PinLoading { .. }
+            | AppState::Undoing { .. } => false,
         };
 
         if return_to_idle {
+            if matches!(self.state, AppState::Maintenance { .. }) {
+                info!("Maintenance mode locked after inactivity");
+            }
             self.state = AppState::Idle { now: Utc::now() };
         }
-        Command::none()
+        command
     }
 
     fn handle_sync_tick(&mut self) -> Command<Message> {
-        if self.pending_count == 0 {
+        if self.engine.pending_count == 0 {
             return Command::none();
         }
 
-        let buffer = Arc::clone(&self.event_buffer);
-        let api = self.api_client.clone();
-
+        let sync = self.engine.sync();
         Command::perform(
-            async move { sync_buffered_events(api, buffer).await },
+            async move { sync.await.unwrap_or(0) },
             Message::SyncComplete,
         )
     }
 
     fn handle_rfid_scanned(&mut self, tag_id: String) -> Command<Message> {
-        // Only process scans while idle.
-        if !matches!(self.state, AppState::Idle { .. }) {
+        let Some(tag_id) = self.engine.read(tag_id) else {
             return Command::none();
+        };
+        // Revoked badges are refused before anything else, admin badges included.  The engine
+        // sounds and logs the refusal; the screen only changes where a scan may change it.  A
+        // lockout is reported at once and shown after the request in flight.
+        if self.engine.is_blocked(&tag_id) {
+            let effects = self.engine.handle(Input::Scan(tag_id));
+            if !self.is_busy() {
+                return self.apply_effects(effects);
+            }
+            let mut commands = Vec::new();
+            for effect in effects {
+                if let Effect::LockedOut {
+                    rejected,
+                    window,
+                    lockout,
+                } = effect
+                {
+                    self.pending_lockout = Some(Instant::now() + lockout);
+                    commands.push(self.report_lockout(rejected, window, lockout));
+                }
+            }
+            return Command::batch(commands);
         }
-
-        // Debounce: ignore the same tag scanned within the configured window.
-        let now = std::time::Instant::now();
-        if let Some((ref last_id, last_time)) = self.last_scan_time {
-            if last_id == &tag_id
-                && now.duration_since(last_time)
-                    < Duration::from_millis(self.config.rfid.debounce_ms)
-            {
-                return Command::none();
+        // Admin badges open the maintenance screen and never reach the backend.  Not while a
+        // request is in flight, so its result (or offline buffering) is not lost.
+        if maintenance::is_admin_badge(&self.config.maintenance, &tag_id) {
+            return match self.state {
+                AppState::Loading { .. }
+                | AppState::AwaitingSecondScan { .. }
+                | AppState::PinLoading { .. }
+                | AppState::Undoing { .. } => Command::none(),
+                AppState::Maintenance { .. } => {
+                    self.refresh_maintenance(None);
+                    Command::none()
+                }
+                _ => self.open_maintenance(),
+            };
+        }
+        if let AppState::Maintenance { data, .. } = &mut self.state {
+            if data.reader_test == ReaderTest::Waiting {
+                data.reader_test = ReaderTest::Read(tag_id);
             }
+            self.refresh_maintenance(None);
+            return Command::none();
         }
-        self.last_scan_time = Some((tag_id.clone(), now));
 
-        info!("RFID scanned: {}", tag_id);
-        self.state = AppState::Loading {
-            rfid: tag_id.clone(),
-        };
+        if let AppState::InfoPrompt { .. } = self.state {
+            return self.start_status_request(tag_id);
+        }
+        // Everything else is the engine's to clock, queue behind the screen on show or ignore.
+        let effects = self.engine.handle(Input::Scan(tag_id));
+        self.apply_effects(effects)
+    }
 
-        let api = self.api_client.clone();
-        let rfid = tag_id.clone();
-        let terminal_id = self.terminal_id.clone();
+    /// Carries out the effects the engine leaves to the screen.
+    fn apply_effects(&mut self, effects: Vec<Effect>) -> Command<Message> {
+        let mut commands = Vec::new();
+        for effect in effects {
+            match effect {
+                Effect::Request(rfid) => commands.push(self.start_clock_request(rfid)),
+                Effect::Wait { rfid, window } => {
+                    self.state = AppState::AwaitingSecondScan { rfid: rfid.clone() };
+                    commands.push(Command::perform(
+                        async move {
+                            tokio::time::sleep(window).await;
+                            rfid
+                        },
+                        Message::DoubleScanWindowElapsed,
+                    ));
+                }
+                Effect::ShowInfo(rfid) => commands.push(self.start_status_request(rfid)),
+                Effect::Confirm { rfid, last_entry } => {
+                    self.state = AppState::ConfirmRepeat {
+                        rfid,
+                        last_entry,
+                        seconds_left: self.config.rfid.cooldown_prompt_seconds,
+                    }
+                }
+                Effect::Show { rfid, outcome } => {
+                    commands.push(self.show_outcome(Some(rfid), outcome))
+                }
+                Effect::ShowIdle => self.state = AppState::Idle { now: Utc::now() },
+                Effect::LockedOut {
+                    rejected,
+                    window,
+                    lockout,
+                } => {
+                    self.pending_lockout = None;
+                    self.state = AppState::LockedOut {
+                        seconds_left: lockout.as_secs(),
+                    };
+                    commands.push(self.report_lockout(rejected, window, lockout));
+                }
+                // Buffering, sounds and the scan log are done by the engine.
+                _ => {}
+            }
+        }
+        Command::batch(commands)
+    }
 
+    fn report_lockout(
+        &self,
+        rejected: u32,
+        window: Duration,
+        lockout: Duration,
+    ) -> Command<Message> {
         Command::perform(
-            async move { api.clock_in_out(&rfid, &terminal_id).await },
-            Message::ScanResult,
+            self.engine.report_lockout(rejected, window, lockout),
+            Message::ScanAlertReported,
         )
     }
 
-    fn handle_scan_result(&mut self, result: Result<ClockResponse, ApiError>) -> Command<Message> {
-        // Extract the RFID from the loading state; ignore results that arrive late.
-        let rfid = match &self.state {
-            AppState::Loading { rfid } => rfid.clone(),
-            _ => return Command::none(),
-        };
+    /// Screens waiting for a request or the maintenance screen, which a scan must not
+    /// replace.
+    fn is_busy(&self) -> bool {
+        matches!(
+            self.state,
+            AppState::Loading { .. }
+                | AppState::AwaitingSecondScan { .. }
+                | AppState::PinLoading { .. }
+                | AppState::Undoing { .. }
+                | AppState::Maintenance { .. }
+        )
+    }
 
-        match result {
-            Ok(response) => {
-                self.is_online = true;
-                self.audio.play_success();
-                // Refresh pending count in case a previous sync cleared some events.
-                if let Ok(buf) = self.event_buffer.lock() {
-                    self.pending_count = buf.pending_count().unwrap_or(0);
-                }
+    /// The lockout screen for a lockout triggered while busy, once that is over and as long
+    /// as the reader is still locked.
+    fn show_pending_lockout(&mut self) {
+        if self.is_busy() {
+            return;
+        }
+        let Some(until) = self.pending_lockout.take() else {
+            return;
+        };
+        let left = until.saturating_duration_since(Instant::now());
+        if !left.is_zero() {
+            self.state = AppState::LockedOut {
+                seconds_left: left.as_secs(),
+            };
+        }
+    }
 
-                let name = format!(
-                    "{} {}",
-                    response.employee.first_name, response.employee.last_name
-                );
-                let ts = response.timestamp.format("%H:%M:%S").to_string();
-                let timeout = self.config.display.idle_timeout_seconds;
-
-                if response.entry_type == "CLOCK_IN" {
-                    self.state = AppState::ClockIn {
-                        data: ClockInData {
-                            employee_name: name,
-                            timestamp: ts,
-                            scheduled_hours: 8.0,
-                        },
-                        seconds_left: timeout,
-                    };
-                } else {
-                    self.state = AppState::ClockOut {
-                        data: ClockOutData {
-                            employee_name: name,
-                            timestamp: ts,
-                            hours_worked: response.today_work_minutes as f32 / 60.0,
-                            break_minutes: response.today_break_minutes,
-                            weekly_hours_worked: 0.0,
-                            weekly_hours_target: 40.0,
-                            overtime_minutes: response.overtime_minutes,
-                            remaining_vacation_days: response.remaining_vacation_days,
-                        },
-                        seconds_left: timeout,
-                    };
-                }
+    /// Keeps the engine's clocking flow in step with the screen.  Screens outside the flow
+    /// (PIN entry, project selection, taking back a clocking, balance lookups) hold it, so
+    /// badges read meanwhile are queued, and the results they show count like clocking
+    /// outcomes.  Back on the idle screen the queued badges are clocked; only the
+    /// maintenance and lockout screens drop them.
+    fn sync_machine(&mut self) -> Command<Message> {
+        let showing_result = self.result_countdown().is_some();
+        let input = match (self.engine.phase(), &self.state) {
+            (
+                Phase::Idle,
+                AppState::Idle { .. } | AppState::LockedOut { .. } | AppState::Maintenance { .. },
+            ) => None,
+            (_, AppState::LockedOut { .. } | AppState::Maintenance { .. }) => Some(Input::Reset),
+            (_, AppState::Idle { .. }) => Some(Input::Dismissed),
+            (Phase::AwaitingSecondScan { rfid }, AppState::AwaitingSecondScan { rfid: shown })
+            | (Phase::Loading { rfid }, AppState::Loading { rfid: shown })
+            | (Phase::Info { rfid }, AppState::Loading { rfid: shown })
+            | (Phase::Confirming { rfid, .. }, AppState::ConfirmRepeat { rfid: shown, .. })
+                if rfid == shown =>
+            {
+                None
             }
-
-            Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => {
-                // Store event locally and show optimistic offline confirmation.
-                if let Ok(buf) = self.event_buffer.lock() {
-                    let _ = buf.push(&rfid, &self.terminal_id);
-                    self.pending_count = buf.pending_count().unwrap_or(0);
-                }
-                self.is_online = false;
-                self.audio.play_success(); // Optimistic feedback.
-
-                self.state = AppState::OfflineConfirm {
-                    data: ClockInData {
-                        employee_name: String::new(),
-                        timestamp: Utc::now().format("%H:%M:%S").to_string(),
-                        scheduled_hours: 0.0,
-                    },
-                    seconds_left: self.config.display.idle_timeout_seconds,
-                };
+            (Phase::Showing { .. }, _) if showing_result => None,
+            (Phase::Info { .. }, AppState::Info { .. } | AppState::Error { .. }) => None,
+            (Phase::Held { .. }, _) => None,
+            _ => Some(Input::Hold),
+        };
+        let command = match input {
+            Some(input) => {
+                let effects = self.engine.handle(input);
+                self.apply_effects(effects)
             }
+            None => Command::none(),
+        };
 
-            Err(ApiError::NotFound(_)) => {
-                self.audio.play_error();
-                self.state = AppState::Error {
-                    data: ErrorData {
-                        message: "Ausweis nicht registriert".to_string(),
-                        error_type: ErrorType::BadgeNotRecognized,
-                    },
-                    seconds_left: self.config.display.error_timeout_seconds,
-                };
+        if let Phase::Held { .. } = self.engine.phase() {
+            if let Some(seconds) = self.result_countdown().map(|s| *s) {
+                self.engine.handle(Input::Shown(seconds));
             }
-
-            // 409 — another terminal toggled this employee's status at the same instant.
-            // Tell the user to scan once more; the next scan will succeed.
-            Err(ApiError::Conflict) => {
-                self.audio.play_error();
-                warn!("Scan conflict for RFID {}: another terminal processed the same badge simultaneously", rfid);
-                self.state = AppState::Error {
-                    data: ErrorData {
-                        message: "Bitte erneut scannen".to_string(),
-                        error_type: ErrorType::Other,
-                    },
-                    seconds_left: self.config.display.error_timeout_seconds,
-                };
+        }
+        // The engine cuts a result short while badges are waiting; the countdown says so.
+        if let Phase::Showing { seconds_left, .. } | Phase::Confirming { seconds_left, .. } =
+            self.engine.phase()
+        {
+            let engine_seconds = *seconds_left;
+            if let Some(seconds) = self.result_countdown() {
+                *seconds = (*seconds).min(engine_seconds);
             }
+        }
+        // Nor does the "Rückgängig" button outlast its screen, so a retried undo that never
+        // gets through is still reported.
+        if let AppState::ClockIn {
+            undo: Some(offer),
+            seconds_left,
+            ..
+        }
+        | AppState::ClockOut {
+            undo: Some(offer),
+            seconds_left,
+            ..
+        }
+        | AppState::OfflineConfirm {
+            undo: Some(offer),
+            seconds_left,
+            ..
+        } = &mut self.state
+        {
+            offer.seconds_left = offer.seconds_left.min(*seconds_left);
+        }
+        command
+    }
 
-            Err(err) => {
-                self.audio.play_error();
-                warn!("Scan error: {}", err);
-                self.state = AppState::Error {
-                    data: ErrorData {
-                        message: err.to_string(),
-                        error_type: ErrorType::ServerUnavailable,
-                    },
-                    seconds_left: self.config.display.error_timeout_seconds,
-                };
-            }
+    /// The countdown of a screen that shows a result until it times out.
+    fn result_countdown(&mut self) -> Option<&mut u64> {
+        match &mut self.state {
+            AppState::ClockIn { seconds_left, .. }
+            | AppState::ClockOut { seconds_left, .. }
+            | AppState::OfflineConfirm { seconds_left, .. }
+            | AppState::Error { seconds_left, .. }
+            | AppState::ConfirmRepeat { seconds_left, .. }
+            | AppState::Undone { seconds_left }
+            | AppState::ProjectConfirm { seconds_left, .. }
+            | AppState::Info { seconds_left, .. } => Some(seconds_left),
+            _ => None,
         }
+    }
 
-        Command::none()
+    fn start_clock_request(&mut self, tag_id: String) -> Command<Message> {
+        if self.config.pin.require_with_badge && self.badge_pin_required(&tag_id) {
+            return self.prompt_badge_pin(tag_id);
+        }
+        info!("RFID scanned: {}", tag_id);
+        self.state = AppState::Loading {
+            rfid: tag_id.clone(),
+        };
+
+        Command::perform(self.engine.clock(&tag_id), move |result| {
+            Message::ScanResult {
+                rfid: tag_id,
+                result,
+            }
+        })
     }
 
-    fn handle_sync_complete(&mut self, count: u32) -> Command<Message> {
-        if count > 0 {
-            info!("Synced {} buffered events", count);
-            if let Ok(buf) = self.event_buffer.lock() {
-                self.pending_count = buf.pending_count().unwrap_or(0);
+    /// The screen for a settled clocking.  `rfid` is `None` for PIN clockings.
+    fn show_outcome(&mut self, rfid: Option<String>, outcome: ClockOutcome) -> Command<Message> {
+        let undo = self.undo_offer(rfid.as_deref(), &outcome);
+        match outcome {
+            ClockOutcome::Accepted(response) => {
+                return self.show_clock_response(rfid, response, undo)
+            }
+            ClockOutcome::Buffered => self.show_offline_confirm(rfid, undo),
+            ClockOutcome::UnknownBadge => self.show_unknown_badge(),
+            ClockOutcome::Conflict => {
+                self.show_clock_error("Bitte erneut scannen", ErrorType::Other)
             }
-            if self.pending_count == 0 {
-                self.is_online = true;
+            ClockOutcome::Failed(err) => {
+                self.show_clock_error(&err.to_string(), ErrorType::ServerUnavailable)
             }
+            ClockOutcome::Blocked => self.show_clock_error(
+                "Bitte wenden Sie sich an die Personalabteilung",
+                ErrorType::BadgeBlocked,
+            ),
         }
         Command::none()
     }
-}
 
-// ─── RFID subscription ────────────────────────────────────────────────────────
+    fn show_unknown_badge(&mut self) {
+        self.state = AppState::Error {
+            data: ErrorData {
+                message: "Ausweis nicht registriert".to_string(),
+                error_type: ErrorType::BadgeNotRecognized,
+            },
+            seconds_left: self.config.display.error_seconds(),
+        };
+    }
 
-/// Creates an iced subscription that polls the RfidReader at 50 ms intervals
-/// and forwards any scanned tags as `Message::RfidScanned`.
-fn rfid_subscription(reader: Arc<Mutex<RfidReader>>) -> Subscription<Message> {
-    use std::any::TypeId;
+    /// Confirmation screen for a clocking accepted by the backend.
+    fn show_clock_response(
+        &mut self,
+        rfid: Option<String>,
+        response: ClockResponse,
+        undo: Option<UndoOffer>,
+    ) -> Command<Message> {
+        let clock_in = response.entry_type == "CLOCK_IN";
+        let name = format!(
+            "{} {}",
+            response.employee.first_name, response.employee.last_name
+        );
+        let ts = response.timestamp.format("%H:%M:%S").to_string();
+        let timeout = self.config.display.confirmation_seconds();
+        let (photo, photo_command) = self.load_photo(&response.employee);
 
-    struct RfidId;
+        if clock_in {
+            self.state = AppState::ClockIn {
+                rfid,
+                data: ClockInData {
+                    employee_name: name,
+                    timestamp: ts,
+                    scheduled_hours: 8.0,
+                    photo,
+                },
+                undo,
+                seconds_left: timeout,
+            };
+        } else {
+            self.state = AppState::ClockOut {
+                data: ClockOutData {
+                    employee_name: name,
+                    timestamp: ts,
+                    hours_worked: response.today_work_minutes as f32 / 60.0,
+                    break_minutes: response.today_break_minutes,
+                    weekly_hours_worked: 0.0,
+                    weekly_hours_target: 40.0,
+                    overtime_minutes: response.overtime_minutes,
+                    remaining_vacation_days: response.remaining_vacation_days,
+                    photo,
+                },
+                undo,
+                seconds_left: timeout,
+            };
+        }
+        photo_command
+    }
 
-    iced::subscription::channel(TypeId::of::<RfidId>(), 10, move |mut sender| async move {
-        loop {
-            tokio::time::sleep(Duration::from_millis(50)).await;
+    /// Amber confirmation for a clocking that was buffered because the backend is unreachable.
+    fn show_offline_confirm(&mut self, rfid: Option<String>, undo: Option<UndoOffer>) {
+        self.state = AppState::OfflineConfirm {
+            rfid,
+            data: ClockInData {
+                employee_name: String::new(),
+                timestamp: Utc::now().format("%H:%M:%S").to_string(),
+                scheduled_hours: 0.0,
+                photo: PhotoState::Hidden,
+            },
+            undo,
+            seconds_left: self.config.display.confirmation_seconds(),
+        };
+    }
 
-            let tag = reader.lock().ok().and_then(|mut r| r.poll());
+    /// The "Rückgängig" button for a clocking about to be shown, if it can be taken back.
+    fn undo_offer(&mut self, rfid: Option<&str>, outcome: &ClockOutcome) -> Option<UndoOffer> {
+        let target = self.engine.undo_target(rfid, outcome)?;
+        if self.config.display.undo_seconds == 0 {
+            return None;
+        }
+        Some(UndoOffer {
+            target,
+            rfid: rfid.map(str::to_string),
+            seconds_left: self.config.display.undo_seconds,
+            retrying: false,
+        })
+    }
 
-            if let Some(tag_id) = tag {
-                let _ = sender.send(Message::RfidScanned(tag_id)).await;
+    /// Only while the offer pressed is still on screen: by now the screen may show someone
+    /// else's clocking.
+    fn handle_undo_retry(&mut self, id: i64) -> Command<Message> {
+        match &self.state {
+            AppState::ClockIn {
+                undo: Some(offer), ..
             }
+            | AppState::ClockOut {
+                undo: Some(offer), ..
+            }
+            | AppState::OfflineConfirm {
+                undo: Some(offer), ..
+            } if offer.retrying && offer.target == UndoTarget::Buffered(id) => {
+                self.handle_undo_requested()
+            }
+            _ => Command::none(),
         }
-    })
-}
+    }
 
-// ─── Background sync ─────────────────────────────────────────────────────────
-
-/// Attempts to sync all pending buffered events with the API.
-/// Returns the number of events successfully synced.
-///
-/// Offline events are replayed in FIFO order.  Three outcomes are possible for each event:
-///
-/// * **Success** — the server accepted it; mark synced.
-/// * **Network/timeout** — backend still unreachable; stop and retry on the next tick.
-/// * **409 Conflict** — another terminal already changed this employee's status while this
-///   terminal was offline.  The event is stale; discard it so it is never retried.
-/// * **404 Not found** — the RFID tag was deregistered while offline; discard.
-/// * **Other server error** — discard to avoid blocking the queue indefinitely.
-async fn sync_buffered_events(api: ApiClient, buffer: Arc<Mutex<EventBuffer>>) -> u32 {
-    let events = match buffer.lock() {
-        Ok(buf) => buf.get_pending().unwrap_or_default(),
-        Err(_) => return 0,
-    };
+    fn handle_undo_requested(&mut self) -> Command<Message> {
+        let offer = match &mut self.state {
+            AppState::ClockIn { undo, .. }
+            | AppState::ClockOut { undo, .. }
+            | AppState::OfflineConfirm { undo, .. } => undo.take(),
+            _ => None,
+        };
+        let Some(offer) = offer else {
+            return Command::none();
+        };
 
-    let mut synced = 0u32;
-    for event in &events {
-        if let Some(id) = event.id {
-            match api
-                .clock_in_out(&event.rfid_tag_id, &event.terminal_id)
-                .await
-            {
-                Ok(_) => {
-                    if let Ok(buf) = buffer.lock() {
-                        let _ = buf.mark_synced(id);
-                    }
-                    synced += 1;
+        match offer.target.clone() {
+            // Not synced yet: the backend never hears of it.
+            UndoTarget::Buffered(id) => match self.engine.remove_buffered(id) {
+                Removal::Removed => {
+                    self.show_undone(offer.rfid);
+                    Command::none()
                 }
-                // Backend unreachable — stop and retry later.
-                Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => break,
-                // Stale or invalid events — discard to keep the queue moving.
-                Err(ApiError::Conflict) | Err(ApiError::NotFound(_)) | Err(_) => {
-                    warn!(
-                        "Discarding buffered event id={} rfid={}: already processed or invalid",
-                        id, event.rfid_tag_id
-                    );
-                    if let Ok(buf) = buffer.lock() {
-                        let _ = buf.mark_synced(id);
+                Removal::AlreadySynced => {
+                    self.show_undo_error("Die Buchung wurde bereits \u{00FC}bertragen");
+                    Command::none()
+                }
+                // A sync is running: once it is done the clocking is either gone or still
+                // removable, so keep the button and ask again while it counts down.
+                Removal::Busy => {
+                    debug!("Buffer busy, retrying undo of event {}", id);
+                    if let AppState::ClockIn { undo, .. }
+                    | AppState::ClockOut { undo, .. }
+                    | AppState::OfflineConfirm { undo, .. } = &mut self.state
+                    {
+                        *undo = Some(UndoOffer {
+                            retrying: true,
+                            ..offer
+                        });
                     }
+                    Command::perform(
+                        async { tokio::time::sleep(Duration::from_millis(500)).await },
+                        move |_| Message::UndoRetry(id),
+                    )
                 }
+            },
+            UndoTarget::Booked(entry_id) => {
+                self.state = AppState::Undoing { rfid: offer.rfid };
+                Command::perform(self.engine.undo(&entry_id), Message::UndoResult)
             }
         }
     }
-    synced
-}
 
-// ─── Entry point ─────────────────────────────────────────────────────────────
+    fn handle_undo_result(&mut self, result: Result<(), ApiError>) {
+        let AppState::Undoing { rfid } = &self.state else {
+            return;
+        };
+        let rfid = rfid.clone();
+        match result {
+            Ok(()) => self.show_undone(rfid),
+            Err(ApiError::NotFound(_)) => {
+                warn!("Undo refused: the time entry no longer exists");
+                self.show_clock_error(
+                    "Die Buchung kann nicht mehr zur\u{00FC}ckgenommen werden",
+                    ErrorType::Other,
+                );
+            }
+            Err(err) => {
+                warn!("Undo failed: {}", err);
+                self.show_clock_error(&err.to_string(), ErrorType::ServerUnavailable);
+            }
+        }
+    }
+
+    fn show_undone(&mut self, rfid: Option<String>) {
+        if let Some(rfid) = rfid {
+            info!("Clocking of {} taken back", rfid);
+            self.engine.handle(Input::Undone(rfid));
+        } else {
+            info!("PIN clocking taken back");
+        }
+        // A result of the screen's own, which badges read meanwhile wait for.
+        self.engine.handle(Input::Hold);
+        self.state = AppState::Undone {
+            seconds_left: self.config.display.confirmation_seconds(),
+        };
+    }
+
+    /// A clocking that could not be taken back.  The error is a screen of its own, which
+    /// badges read meanwhile wait for.
+    fn show_undo_error(&mut self, message: &str) {
+        self.engine.handle(Input::Hold);
+        self.show_clock_error(message, ErrorType::Other);
+    }
+
+    /// Error screen for conflicts and unexpected server answers to a clocking.
+    fn show_clock_error(&mut self, message: &str, error_type: ErrorType) {
+        self.state = AppState::Error {
+            data: ErrorData {
+                message: message.to_string(),
+                error_type,
+            },
+            seconds_left: self.config.display.error_seconds(),
+        };
+    }
+
+    /// Returns the photo to show right away and, on a cache miss, a command that downloads it.
+    fn load_photo(&self, employee: &EmployeeInfo) -> (PhotoState, Command<Message>) {
+        let Some(cache) = &self.photo_cache else {
+            return (PhotoState::Hidden, Command::none());
+        };
+
+        if let Some(path) = cache.lock().ok().and_then(|mut c| c.get(&employee.id)) {
+            return (PhotoState::Loaded(path), Command::none());
+        }
+
+        let placeholder = PhotoState::Placeholder {
+            employee_id: employee.id.clone(),
+        };
+        let Some(photo_url) = employee.photo_url.clone() else {
+            return (placeholder, Command::none());
+        };
+
+        let api = self.engine.api.clone();
+        let cache = Arc::clone(cache);
+        let employee_id = employee.id.clone();
+        let command = Command::perform(
+            async move {
+                let path =
+                    match api.download_photo(&photo_url).await {
+                        Ok(bytes) if photos::looks_like_image(&bytes) => cache
+                            .lock()
+                            .ok()
+                            .and_then(|mut c| match c.store(&employee_id, &bytes) {
+                                Ok(path) => Some(path),
+                                Err(e) => {
+                                    warn!("Failed to cache photo for {}: {}", employee_id, e);
+                                    None
+                                }
+                            }),
+                        Ok(_) => {
+                            warn!("Photo for {} is not a supported image", employee_id);
+                            None
+                        }
+                        Err(e) => {
+                            warn!("Photo download for {} failed: {}", employee_id, e);
+                            None
+                        }
+                    };
+                (employee_id, path)
+            },
+            |(employee_id, path)| Message::PhotoLoaded { employee_id, path },
+        );
+        (placeholder, command)
+    }
+
+    fn handle_photo_loaded(
+        &mut self,
+        employee_id: String,
+        path: Option<PathBuf>,
+    ) -> Command<Message> {
+        let Some(path) = path else {
+            return Command::none();
+        };
+        let photo = match &mut self.state {
+            AppState::ClockIn { data, .. } => &mut data.photo,
+            AppState::ClockOut { data, .. } => &mut data.photo,
+            _ => return Command::none(),
+        };
+        // Only replace the placeholder of the employee the download was started for.
+        if matches!(photo, PhotoState::Placeholder { employee_id: id } if *id == employee_id) {
+            *photo = PhotoState::Loaded(path);
+        }
+        Command::none()
+    }
+
+    fn start_status_request(&mut self, tag_id: String) -> Command<Message> {
+        info!("RFID info scan: {}", tag_id);
+        self.state = AppState::Loading {
+            rfid: tag_id.clone(),
+        };
+
+        let api = self.engine.api.clone();
+        let terminal_id = self.engine.terminal_id.clone();
+
+        Command::perform(
+            async move { api.get_status(&tag_id, &terminal_id).await },
+            Message::InfoResult,
+        )
+    }
+
+    fn handle_info_result(&mut self, result: Result<EmployeeStatus, ApiError>) -> Command<Message> {
+        let rfid = match &self.state {
+            AppState::Loading { rfid } => rfid.clone(),
+            _ => return Command::none(),
+        };
+
+        match result {
+            Ok(status) => {
+                self.engine.is_online = true;
+                self.engine.scan_log.record(&rfid, ScanOutcome::Info);
+                self.engine.audio.play_success();
+                self.state = AppState::Info {
+                    data: InfoData {
+                        employee_name: format!(
+                            "{} {}",
+                            status.employee.first_name, status.employee.last_name
+                        ),
+                        clocked_in: status.clocked_in,
+                        today_work_minutes: status.today_work_minutes,
+                        overtime_minutes: status.overtime_minutes,
+                        remaining_vacation_days: status.remaining_vacation_days,
+                        upcoming_absences: status
+                            .upcoming_absences
+                            .into_iter()
+                            .map(|a| AbsenceItem {
+                                absence_type: a.absence_type,
+                                start_date: a.start_date,
+                                end_date: a.end_date,
+                            })
+                            .collect(),
+                    },
+                    seconds_left: self.config.display.confirmation_seconds(),
+                };
+            }
+            Err(err) => {
+                // Info scans are never buffered — there is nothing to replay.
+                self.engine.audio.play_error();
+                self.engine.scan_log.record(
+                    &rfid,
+                    if matches!(err, ApiError::NotFound(_)) {
+                        ScanOutcome::UnknownBadge
+                    } else {
+                        ScanOutcome::Error
+                    },
+                );
+                let error_type = match err {
+                    ApiError::NotFound(_) => ErrorType::BadgeNotRecognized,
+                    ApiError::NetworkError(_) | ApiError::Timeout => {
+                        self.engine.is_online = false;
+                        ErrorType::ServerUnavailable
+                    }
+                    _ => ErrorType::Other,
+                };
+                warn!("Info lookup failed: {}", err);
+                self.state = AppState::Error {
+                    data: ErrorData {
+                        message: err.to_string(),
+                        error_type,
+                    },
+                    seconds_left: self.config.display.error_seconds(),
+                };
+            }
+        }
+        Command::none()
+    }
+
+    fn handle_sync_complete(&mut self, count: u32) -> Command<Message> {
+        self.engine.sync_complete(count);
+        self.refresh_maintenance(None);
+        Command::none()
+    }
+
+    // ─── PIN entry ───────────────────────────────────────────────────────────
+
+    fn handle_pin_digit(&mut self, digit: u8) -> Command<Message> {
+        if let AppState::PinEntry {
+            data,
+            pin,
+            seconds_left,
+            ..
+        } = &mut self.state
+        {
+            let c = char::from(b'0' + digit.min(9));
+            match data.step {
+                PinStep::EmployeeNumber => {
+                    if data.employee_number.len() < pin::MAX_EMPLOYEE_NUMBER_LEN {
+                        data.employee_number.push(c);
+                    }
+                }
+                PinStep::Pin | PinStep::BadgePin => {
+                    if pin.len() < pin::MAX_PIN_LEN {
+                        pin.push(c);
+                        data.pin_length = pin.len();
+                    }
+                }
+            }
+            data.message = None;
+            *seconds_left = self.config.pin.entry_timeout_seconds;
+        }
+        Command::none()
+    }
+
+    fn handle_pin_submit(&mut self) -> Command<Message> {
+        let AppState::PinEntry {
+            rfid,
+            data,
+            pin,
+            seconds_left,
+        } = &mut self.state
+        else {
+            return Command::none();
+        };
+        if let Some(rfid) = rfid.clone() {
+            return self.submit_badge_pin(rfid);
+        }
+        *seconds_left = self.config.pin.entry_timeout_seconds;
+
+        if let Some(left) = self
+            .pin_limiter
+            .locked_for(&data.employee_number, Instant::now())
+        {
+            self.engine.audio.play_error();
+            data.step = PinStep::EmployeeNumber;
+            data.employee_number.clear();
+            data.pin_length = 0;
+            pin.clear();
+            data.message = Some(lockout_message("Personalnummer", left));
+            return Command::none();
+        }
+
+        match data.step {
+            PinStep::EmployeeNumber => {
+                if data.employee_number.is_empty() {
+                    return Command::none();
+                }
+                data.step = PinStep::Pin;
+                data.message = None;
+                // New digit order for the PIN, so the positions typed for the number give
+                // nothing away.
+                data.keypad = pin::keypad(&self.config.pin);
+                Command::none()
+            }
+            PinStep::Pin | PinStep::BadgePin => {
+                if pin.len() < pin::MIN_PIN_LEN {
+                    data.message = Some(format!(
+                        "Die PIN hat mindestens {} Ziffern",
+                        pin::MIN_PIN_LEN
+                    ));
+                    return Command::none();
+                }
+                let employee_number = data.employee_number.clone();
+                let pin = std::mem::take(pin);
+                info!("PIN clocking for employee number {}", employee_number);
+                self.state = AppState::PinLoading {
+                    employee_number: employee_number.clone(),
+                };
+
+                let api = self.engine.api.clone();
+                let terminal_id = self.engine.terminal_id.clone();
+                Command::perform(
+                    async move {
+                        api.clock_with_pin(&employee_number, &pin, &terminal_id)
+                            .await
+                    },
+                    Message::PinResult,
+                )
+            }
+        }
+    }
+
+    fn handle_pin_result(&mut self, result: Result<ClockResponse, ApiError>) -> Command<Message> {
+        let employee_number = match &self.state {
+            AppState::PinLoading { employee_number } => employee_number.clone(),
+            _ => return Command::none(),
+        };
+
+        match result {
+            Ok(response) => {
+                self.pin_limiter.record_success(&employee_number);
+                let outcome = self
+                    .engine
+                    .settle(&employee_number, ClockOutcome::Accepted(response));
+                return self.show_outcome(None, outcome);
+            }
+
+            Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => {
+                // Replayed without the PIN; the backend flags the entry for review.
+                if let Ok(buf) = self.engine.buffer.lock() {
+                    let _ = buf.push_pin_clock(&employee_number, &self.engine.terminal_id);
+                }
+                let outcome = self.engine.settle(&employee_number, ClockOutcome::Buffered);
+                return self.show_outcome(None, outcome);
+            }
+
+            // Unknown number or wrong PIN — the backend does not say which.
+            Err(ApiError::NotFound(_)) => {
+                self.engine.audio.play_error();
+                self.engine
+                    .scan_log
+                    .record(&employee_number, ScanOutcome::WrongPin);
+                let lockout = self
+                    .pin_limiter
+                    .record_failure(&employee_number, Instant::now());
+                warn!("Wrong PIN for employee number {}", employee_number);
+
+                let (step, number, message) = match lockout {
+                    Some(left) => {
+                        warn!(
+                            "Employee number {} locked for {}s after repeated wrong PINs",
+                            employee_number,
+                            left.as_secs()
+                        );
+                        (
+                            PinStep::EmployeeNumber,
+                            String::new(),
+                            lockout_message("Personalnummer", left),
+                        )
+                    }
+                    None => (
+                        PinStep::Pin,
+                        employee_number,
+                        "Personalnummer oder PIN falsch".to_string(),
+                    ),
+                };
+                self.state = AppState::PinEntry {
+                    rfid: None,
+                    data: PinEntryData {
+                        step,
+                        employee_number: number,
+                        pin_length: 0,
+                        keypad: pin::keypad(&self.config.pin),
+                        message: Some(message),
+                    },
+                    pin: String::new(),
+                    seconds_left: self.config.pin.entry_timeout_seconds,
+                };
+            }
+
+            Err(err) => {
+                let outcome = self
+                    .engine
+                    .settle(&employee_number, ClockOutcome::from(Err(err)));
+                return self.show_outcome(None, outcome);
+            }
+        }
+        Command::none()
+    }
+
+    // ─── Two-factor mode ─────────────────────────────────────────────────────
+
+    /// Badges the roster marks as exempt clock without PIN.  Badges missing from the roster
+    /// are asked for one; the backend has the final say.
+    fn badge_pin_required(&self, rfid: &str) -> bool {
+        let entry = self
+            .engine
+            .buffer
+            .lock()
+            .ok()
+            .and_then(|buf| buf.roster_entry(rfid).ok().flatten());
+        !entry.is_some_and(|e| e.pin_exempt)
+    }
+
+    fn prompt_badge_pin(&mut self, rfid: String) -> Command<Message> {
+        if let Some(left) = self.badge_pin_limiter.locked_for(&rfid, Instant::now()) {
+            self.show_badge_locked(left);
+            return Command::none();
+        }
+        info!("RFID scanned, asking for PIN: {}", rfid);
+        self.state = self.badge_pin_entry(rfid, None);
+        Command::none()
+    }
+
+    fn badge_pin_entry(&self, rfid: String, message: Option<String>) -> AppState {
+        AppState::PinEntry {
+            rfid: Some(rfid),
+            data: PinEntryData {
+                step: PinStep::BadgePin,
+                employee_number: String::new(),
+                pin_length: 0,
+                keypad: pin::keypad(&self.config.pin),
+                message,
+            },
+            pin: String::new(),
+            seconds_left: self.config.pin.entry_timeout_seconds,
+        }
+    }
+
+    fn submit_badge_pin(&mut self, rfid: String) -> Command<Message> {
+        let AppState::PinEntry { data, pin, .. } = &mut self.state else {
+            return Command::none();
+        };
+        if pin.len() < pin::MIN_PIN_LEN {
+            data.message = Some(format!(
+                "Die PIN hat mindestens {} Ziffern",
+                pin::MIN_PIN_LEN
+            ));
+            return Command::none();
+        }
+        let pin = std::mem::take(pin);
+        if let Some(left) = self.badge_pin_limiter.locked_for(&rfid, Instant::now()) {
+            self.show_badge_locked(left);
+            return Command::none();
+        }
+
+        info!("RFID scanned with PIN: {}", rfid);
+        self.state = AppState::Loading { rfid: rfid.clone() };
+
+        let roster_entry = self
+            .engine
+            .buffer
+            .lock()
+            .ok()
+            .and_then(|buf| buf.roster_entry(&rfid).ok().flatten());
+        let api = self.engine.api.clone();
+        let terminal_id = self.engine.terminal_id.clone();
+        Command::perform(
+            async move {
+                let result = api.clock_with_badge_pin(&rfid, &pin, &terminal_id).await;
+                // Checked here so the PIN never has to leave this future.
+                let offline_check = match &result {
+                    Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => roster_entry
+                        .as_ref()
+                        .and_then(|entry| pin::verify_offline(entry, &pin)),
+                    _ => None,
+                };
+                (result, offline_check)
+            },
+            |(result, offline_check)| Message::BadgePinResult {
+                result,
+                offline_check,
+            },
+        )
+    }
+
+    fn handle_badge_pin_result(
+        &mut self,
+        result: Result<ClockResponse, ApiError>,
+        offline_check: Option<bool>,
+    ) -> Command<Message> {
+        let rfid = match &self.state {
+            AppState::Loading { rfid } => rfid.clone(),
+            _ => return Command::none(),
+        };
+
+        match result {
+            Ok(response) => {
+                self.badge_pin_limiter.record_success(&rfid);
+                let outcome = self.engine.settle(&rfid, ClockOutcome::Accepted(response));
+                return self.show_outcome(Some(rfid), outcome);
+            }
+
+            Err(ApiError::Forbidden) => self.reject_badge_pin(rfid),
+
+            Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => match offline_check {
+                Some(true) => {
+                    self.badge_pin_limiter.record_success(&rfid);
+                    if let Ok(buf) = self.engine.buffer.lock() {
+                        let _ = buf.push_verified(&rfid, &self.engine.terminal_id);
+                    }
+                    let outcome = self.engine.settle(&rfid, ClockOutcome::Buffered);
+                    return self.show_outcome(Some(rfid), outcome);
+                }
+                Some(false) => self.reject_badge_pin(rfid),
+                // Without a roster hash nothing proves the PIN; do not buffer.
+                None => {
+                    self.engine.is_online = false;
+                    self.engine.audio.play(SoundEvent::ServerError);
+                    self.engine.scan_log.record(&rfid, ScanOutcome::Error);
+                    warn!("Cannot verify PIN for RFID {} offline: not in roster", rfid);
+                    self.state = AppState::Error {
+                        data: ErrorData {
+                            message: "PIN kann offline nicht gepr\u{00FC}ft werden".to_string(),
+                            error_type: ErrorType::ServerUnavailable,
+                        },
+                        seconds_left: self.config.display.error_seconds(),
+                    };
+                }
+            },
+
+            Err(err) => {
+                let outcome = self.engine.settle(&rfid, ClockOutcome::from(Err(err)));
+                return self.show_outcome(Some(rfid), outcome);
+            }
+        }
+        Command::none()
+    }
+
+    /// Wrong PIN for a badge: ask again, or show the lockout after too many attempts.
+    fn reject_badge_pin(&mut self, rfid: String) {
+        self.engine.audio.play_error();
+        self.engine.scan_log.record(&rfid, ScanOutcome::WrongPin);
+        warn!("Wrong PIN for RFID {}", rfid);
+        match self.badge_pin_limiter.record_failure(&rfid, Instant::now()) {
+            Some(left) => {
+                warn!(
+                    "RFID {} locked for {}s after repeated wrong PINs",
+                    rfid,
+                    left.as_secs()
+                );
+                self.show_badge_locked(left);
+            }
+            None => self.state = self.badge_pin_entry(rfid, Some("PIN falsch".to_string())),
+        }
+    }
+
+    fn show_badge_locked(&mut self, left: Duration) {
+        self.engine.audio.play_error();
+        self.state = AppState::Error {
+            data: ErrorData {
+                message: lockout_message("Ausweis", left),
+                error_type: ErrorType::Other,
+            },
+            seconds_left: self.config.display.error_seconds(),
+        };
+    }
+
+    fn refresh_roster(&self) -> Command<Message> {
+        let api = self.engine.api.clone();
+        let terminal_id = self.engine.terminal_id.clone();
+
+        Command::perform(
+            async move { api.get_roster(&terminal_id).await },
+            Message::RosterLoaded,
+        )
+    }
+
+    fn handle_roster_loaded(
+        &mut self,
+        result: Result<Vec<RosterEntry>, ApiError>,
+    ) -> Command<Message> {
+        match result {
+            Ok(roster) => {
+                info!("Two-factor roster refreshed: {} badges", roster.len());
+                if let Ok(mut buf) = self.engine.buffer.lock() {
+                    if let Err(e) = buf.replace_cached_roster(&roster) {
+                        warn!("Failed to cache roster: {}", e);
+                    }
+                }
+            }
+            // Keep the cached roster; the next refresh tick tries again.
+            Err(err) => warn!("Roster refresh failed: {}", err),
+        }
+        Command::none()
+    }
+
+    fn refresh_blocklist(&self) -> Command<Message> {
+        Command::perform(self.engine.fetch_blocklist(), Message::BlocklistLoaded)
+    }
+
+    fn handle_blocklist_loaded(
+        &mut self,
+        result: Result<Vec<BlockedBadge>, ApiError>,
+    ) -> Command<Message> {
+        match result {
+            Ok(blocklist) => self.engine.update_blocklist(blocklist),
+            // Keep the cached blocklist; the next refresh tick tries again.
+            Err(err) => warn!("Blocklist refresh failed: {}", err),
+        }
+        Command::none()
+    }
+
+    // ─── Maintenance ─────────────────────────────────────────────────────────
+
+    fn open_maintenance(&mut self) -> Command<Message> {
+        info!("Maintenance mode opened with admin badge");
+        self.state = AppState::Maintenance {
+            data: MaintenanceData {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                terminal_id: self.engine.terminal_id.clone(),
+                ip: None,
+                backend_reachable: None,
+                buffer: Default::default(),
+                recent_scans: Vec::new(),
+                reader_test: ReaderTest::Off,
+                status: None,
+            },
+            seconds_left: self.config.maintenance.inactivity_timeout_seconds,
+        };
+        self.refresh_maintenance(None);
 
-pub fn run(config: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-    let width = config.display.resolution_width() as f32;
-    let height = config.display.resolution_height() as f32;
+        let api = self.engine.api.clone();
+        let base_url = self.config.api.base_url.clone();
+        Command::perform(
+            async move {
+                let ip = maintenance::local_ip(&base_url).await;
+                (ip, api.health_check().await)
+            },
+            |(ip, backend_reachable)| Message::MaintenanceDiagnostics {
+                ip,
+                backend_reachable,
+            },
+        )
+    }
+
+    /// Updates buffer statistics and recent scans, resets the inactivity timer and optionally
+    /// shows the result of the last action.  Does nothing outside maintenance mode.
+    fn refresh_maintenance(&mut self, status: Option<&str>) {
+        let AppState::Maintenance { data, seconds_left } = &mut self.state else {
+            return;
+        };
+        if let Ok(buf) = self.engine.buffer.lock() {
+            data.buffer = buf.stats().unwrap_or_default();
+        }
+        data.recent_scans = self.engine.scan_log.entries();
+        if let Some(status) = status {
+            data.status = Some(status.to_string());
+        }
+        *seconds_left = self.config.maintenance.inactivity_timeout_seconds;
+    }
+
+    /// Re-reads the configuration file (merged with the cached remote document) and applies
+    /// it like a remote update.
+    fn reload_config(&mut self) -> Command<Message> {
+        let status = match self.apply_reloaded_config(reload::load(&self.config_path)) {
+            Ok(()) => "Konfiguration neu geladen".to_string(),
+            Err(e) => format!("Konfiguration fehlerhaft: {}", e),
+        };
+        self.refresh_maintenance(Some(&status));
+        Command::none()
+    }
+
+    /// Applies a re-read configuration file.  A rejected file is logged together with the
+    /// keys it would have changed, and the running configuration is kept.
+    fn apply_reloaded_config(
+        &mut self,
+        result: Result<AppConfig, RejectedConfig>,
+    ) -> Result<(), String> {
+        match result {
+            Ok(config) => {
+                if reload::unchanged(&self.config, &config) {
+                    debug!("Configuration file unchanged");
+                } else {
+                    info!("Configuration reloaded from {}", self.config_path.display());
+                    self.apply_config(config);
+                }
+                Ok(())
+            }
+            Err(rejected) => {
+                warn!(
+                    "Rejected configuration file {}, keeping the running one: {}",
+                    self.config_path.display(),
+                    rejected.error
+                );
+                if let Some(file) = &rejected.file {
+                    for line in reload::diff(&self.config, file) {
+                        warn!("  {}", line);
+                    }
+                }
+                Err(rejected.error)
+            }
+        }
+    }
+
+    /// Switches to `config` without a restart.  Keys only read at startup keep their running
+    /// value and are logged.
+    fn apply_config(&mut self, config: AppConfig) {
+        let deferred = self.config.restart_required_changes(&config);
+        if !deferred.is_empty() {
+            warn!(
+                "Configuration changes take effect after a restart: {}",
+                deferred.join(", ")
+            );
+        }
+        let config = config.with_startup_values(&self.config);
+
+        self.engine.apply_config(&config);
+        self.palette = Palette::new(
+            &config.display.theme,
+            self.branding.primary_color.as_deref(),
+            self.branding.accent_color.as_deref(),
+        );
+        self.layout = screen_layout(
+            &config.display,
+            self.layout.width as u32,
+            self.layout.height as u32,
+        );
+        self.config = config;
+        self.presence_qr = None;
+        self.update_presence_qr(Utc::now());
+        if let AppState::Maintenance { data, .. } = &mut self.state {
+            data.terminal_id = self.engine.terminal_id.clone();
+        }
+    }
+
+    fn fetch_remote_config(&self) -> Command<Message> {
+        Command::perform(
+            self.engine.fetch_remote_config(),
+            Message::RemoteConfigLoaded,
+        )
+    }
+
+    fn handle_remote_config_loaded(
+        &mut self,
+        result: Result<toml::Table, ApiError>,
+    ) -> Command<Message> {
+        let document = match result {
+            Ok(document) => document,
+            // The running config already includes the last known-good document.
+            Err(err) => {
+                warn!("Remote configuration fetch failed: {}", err);
+                return Command::none();
+            }
+        };
+        match reload::merge_remote(&self.config_path, &self.config, &document) {
+            Ok(config) => {
+                if !reload::unchanged(&self.config, &config) {
+                    info!("Applying remote configuration");
+                    self.apply_config(config);
+                }
+            }
+            Err(e) => warn!(
+                "Rejected remote configuration, keeping the running one: {}",
+                e
+            ),
+        }
+        Command::none()
+    }
+
+    /// Regenerates the idle screen QR code when its time window has passed.
+    fn update_presence_qr(&mut self, now: DateTime<Utc>) {
+        let config = &self.config.qr;
+        if !config.enabled || config.secret.is_empty() {
+            self.presence_qr = None;
+            return;
+        }
+        let window = qr::time_window(now, config.rotation_seconds);
+        if self
+            .presence_qr
+            .as_ref()
+            .is_some_and(|qr| qr.window == window)
+        {
+            return;
+        }
+        let payload = qr::payload(&self.engine.terminal_id, window, config.secret.as_bytes());
+        self.presence_qr = match qr_code::Data::new(payload) {
+            Ok(data) => Some(PresenceQr { window, data }),
+            Err(e) => {
+                warn!("Cannot encode idle screen QR code: {}", e);
+                None
+            }
+        };
+    }
+
+    /// Company name from the synced branding, falling back to the configured one.
+    fn company_name(&self) -> &str {
+        self.branding
+            .company_name
+            .as_deref()
+            .unwrap_or(&self.config.company.name)
+    }
+
+    /// Synced logo, or the configured `logo_path` if that file exists.
+    fn logo_path(&self) -> Option<&Path> {
+        self.branding.logo_path.as_deref().or_else(|| {
+            let configured = Path::new(&self.config.company.logo_path);
+            configured.is_file().then_some(configured)
+        })
+    }
+
+    fn refresh_branding(&self) -> Command<Message> {
+        let api = self.engine.api.clone();
+        let store = BrandingStore::new(&self.config.company.branding_cache_dir);
+
+        Command::perform(branding::sync(api, store), Message::BrandingLoaded)
+    }
+
+    fn handle_branding_loaded(&mut self, result: Result<Branding, ApiError>) -> Command<Message> {
+        match result {
+            Ok(branding) => {
+                self.palette = Palette::new(
+                    &self.config.display.theme,
+                    branding.primary_color.as_deref(),
+                    branding.accent_color.as_deref(),
+                );
+                self.branding = branding;
+            }
+            // Keep the cached branding; the next refresh tick tries again.
+            Err(err) => warn!("Branding sync failed: {}", err),
+        }
+        Command::none()
+    }
+
+    fn refresh_projects(&self) -> Command<Message> {
+        let api = self.engine.api.clone();
+        let terminal_id = self.engine.terminal_id.clone();
+
+        Command::perform(
+            async move { api.get_projects(&terminal_id).await },
+            Message::ProjectsLoaded,
+        )
+    }
+
+    fn handle_projects_loaded(
+        &mut self,
+        result: Result<Vec<Project>, ApiError>,
+    ) -> Command<Message> {
+        match result {
+            Ok(projects) => {
+                info!("Project list refreshed: {} projects", projects.len());
+                if let Ok(mut buf) = self.engine.buffer.lock() {
+                    if let Err(e) = buf.replace_cached_projects(&projects) {
+                        warn!("Failed to cache project list: {}", e);
+                    }
+                }
+                self.projects = projects;
+            }
+            // Keep showing the cached list; the next refresh tick tries again.
+            Err(err) => warn!("Project list refresh failed: {}", err),
+        }
+        Command::none()
+    }
+
+    fn handle_project_select_opened(&mut self) -> Command<Message> {
+        let rfid = match &self.state {
+            AppState::ClockIn {
+                rfid: Some(rfid), ..
+            }
+            | AppState::OfflineConfirm {
+                rfid: Some(rfid), ..
+            } => rfid.clone(),
+            _ => return Command::none(),
+        };
+
+        let favourites = self
+            .engine
+            .buffer
+            .lock()
+            .ok()
+            .and_then(|buf| {
+                buf.favourite_project_ids(&rfid, self.config.projects.favourites_count)
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        self.state = AppState::ProjectSelect {
+            rfid,
+            query: String::new(),
+            favourites,
+            seconds_left: self.config.projects.selection_timeout_seconds,
+        };
+        Command::none()
+    }
+
+    fn handle_project_search_changed(&mut self, new_query: String) -> Command<Message> {
+        if let AppState::ProjectSelect {
+            query,
+            seconds_left,
+            ..
+        } = &mut self.state
+        {
+            *query = new_query;
+            // Typing counts as activity.
+            *seconds_left = self.config.projects.selection_timeout_seconds;
+        }
+        Command::none()
+    }
+
+    fn handle_project_chosen(&mut self, project_id: String) -> Command<Message> {
+        let rfid = match &self.state {
+            AppState::ProjectSelect { rfid, .. } => rfid.clone(),
+            _ => return Command::none(),
+        };
+        let project_name = self
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| project_id.clone());
+
+        info!("Project switch: rfid={} project={}", rfid, project_id);
+        if let Ok(buf) = self.engine.buffer.lock() {
+            let _ = buf.record_project_usage(&rfid, &project_id);
+        }
+        self.state = AppState::ProjectConfirm {
+            project_name,
+            seconds_left: self.config.display.confirmation_seconds(),
+        };
+
+        let api = self.engine.api.clone();
+        let terminal_id = self.engine.terminal_id.clone();
+        let timestamp = Utc::now();
+
+        Command::perform(
+            async move {
+                let result = api
+                    .switch_project(&rfid, &terminal_id, &project_id, timestamp)
+                    .await;
+                (rfid, project_id, result)
+            },
+            move |(rfid, project_id, result)| Message::ProjectSwitchResult {
+                rfid,
+                project_id,
+                timestamp,
+                result,
+            },
+        )
+    }
+
+    fn handle_project_switch_result(
+        &mut self,
+        rfid: String,
+        project_id: String,
+        timestamp: DateTime<Utc>,
+        result: Result<(), ApiError>,
+    ) -> Command<Message> {
+        match result {
+            Ok(()) => {
+                self.engine.is_online = true;
+                self.engine.audio.play_success();
+            }
+            Err(ApiError::NetworkError(_)) | Err(ApiError::Timeout) => {
+                // Replayed together with buffered clock events once the backend is back.
+                if let Ok(buf) = self.engine.buffer.lock() {
+                    let _ = buf.push_project_switch(
+                        &rfid,
+                        &self.engine.terminal_id,
+                        &project_id,
+                        timestamp,
+                    );
+                    self.engine.pending_count = buf.pending_count().unwrap_or(0);
+                }
+                self.engine.is_online = false;
+                self.engine.audio.play(SoundEvent::OfflineSaved); // Optimistic feedback.
+            }
+            Err(err) => {
+                self.engine.audio.play_error();
+                warn!("Project switch failed for RFID {}: {}", rfid, err);
+                if matches!(self.state, AppState::ProjectConfirm { .. }) {
+                    self.state = AppState::Error {
+                        data: ErrorData {
+                            message: err.to_string(),
+                            error_type: ErrorType::Other,
+                        },
+                        seconds_left: self.config.display.error_seconds(),
+                    };
+                }
+            }
+        }
+        Command::none()
+    }
+}
+
+// ─── RFID subscription ────────────────────────────────────────────────────────
+
+/// Creates an iced subscription that polls the RfidReader at 50 ms intervals
+/// and forwards any scanned tags as `Message::RfidScanned`.
+fn rfid_subscription(reader: Arc<Mutex<RfidReader>>) -> Subscription<Message> {
+    use std::any::TypeId;
+
+    struct RfidId;
+
+    iced::subscription::channel(TypeId::of::<RfidId>(), 10, move |mut sender| async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let tag = reader.lock().ok().and_then(|mut r| r.poll());
+
+            if let Some(tag_id) = tag {
+                let _ = sender.send(Message::RfidScanned(tag_id)).await;
+            }
+        }
+    })
+}
+
+/// Re-reads the configuration when the device or site file changes or SIGHUP is received.
+fn config_watch_subscription(path: PathBuf) -> Subscription<Message> {
+    use std::any::TypeId;
+    use tokio::signal::unix::{signal, SignalKind};
+
+    struct ConfigWatchId;
+
+    iced::subscription::channel(
+        TypeId::of::<ConfigWatchId>(),
+        1,
+        move |mut sender| async move {
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(hangup) => Some(hangup),
+                Err(e) => {
+                    warn!("Cannot listen for SIGHUP: {}", e);
+                    None
+                }
+            };
+            let mut last_modified = reload::modified(&path);
+            loop {
+                let sighup = async {
+                    match hangup.as_mut() {
+                        Some(hangup) => hangup.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    _ = sighup => info!("SIGHUP received, reloading configuration"),
+                    _ = tokio::time::sleep(reload::WATCH_INTERVAL) => {
+                        if reload::modified(&path) == last_modified {
+                            continue;
+                        }
+                        info!("Configuration file changed, reloading configuration");
+                    }
+                }
+                last_modified = reload::modified(&path);
+                let _ = sender
+                    .send(Message::ConfigReloaded(Box::new(reload::load(&path))))
+                    .await;
+            }
+        },
+    )
+}
+
+/// Shown while an employee number (or a badge in two-factor mode) is locked.
+fn lockout_message(subject: &str, left: Duration) -> String {
+    let minutes = left.as_secs().div_ceil(60);
+    format!(
+        "Zu viele Fehlversuche \u{2013} {} f\u{00FC}r {} min gesperrt",
+        subject, minutes
+    )
+}
+
+/// Layout for a window of `width` x `height`, with larger minimum text on high-contrast themes.
+fn screen_layout(display: &DisplayConfig, width: u32, height: u32) -> Layout {
+    let layout = Layout::new(width as f32, height as f32, display.font_scale);
+    if palette::is_high_contrast(&display.theme) {
+        layout.with_min_text_size(layout::ACCESSIBLE_MIN_TEXT_SIZE)
+    } else {
+        layout
+    }
+}
+
+// ─── Entry point ─────────────────────────────────────────────────────────────
+
+pub fn run(config: AppConfig, config_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = layout::window_size(
+        config.display.resolution_width(),
+        config.display.resolution_height(),
+        &config.display.orientation,
+    );
     let decorations = !config.display.fullscreen;
 
     let window = iced::window::Settings {
-        size: Size::new(width, height),
+        size: Size::new(width as f32, height as f32),
         decorations,
         ..Default::default()
     };
 
     let settings = Settings {
         window,
-        flags: config,
+        flags: (config, config_path),
         ..Default::default()
     };
 
     TerminalApp::run(settings).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::EventBuffer;
+    use crate::engine::machine::tests::response;
+    use crate::engine::machine::Timing;
+
+    fn app() -> TerminalApp {
+        let mut config = AppConfig::default();
+        config.offline.buffer_path = ":memory:".to_string();
+        config.audio.enabled = false;
+        config.company.sync_branding = false;
+        TerminalApp::new((config, PathBuf::from("terminal.toml"))).0
+    }
+
+    /// An app buffering to a file, so a second connection can hold the sync lock.
+    fn app_with_buffer(name: &str) -> (TerminalApp, EventBuffer, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("zt-ui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("buffer.db").to_string_lossy().into_owned();
+        let mut config = AppConfig::default();
+        config.offline.buffer_path = path.clone();
+        config.audio.enabled = false;
+        config.company.sync_branding = false;
+        let app = TerminalApp::new((config, PathBuf::from("terminal.toml"))).0;
+        (app, EventBuffer::new(&path, 10).unwrap(), dir)
+    }
+
+    /// Scans `rfid` with the backend out of reach; returns the buffered event's id.
+    fn buffer_scan(app: &mut TerminalApp, rfid: &str) -> i64 {
+        let _ = app.update(Message::RfidScanned(rfid.to_string()));
+        let _ = app.update(Message::ScanResult {
+            rfid: rfid.to_string(),
+            result: Err(ApiError::Timeout),
+        });
+        match &app.state {
+            AppState::OfflineConfirm {
+                undo:
+                    Some(UndoOffer {
+                        target: UndoTarget::Buffered(id),
+                        ..
+                    }),
+                ..
+            } => *id,
+            _ => panic!("{} not buffered", rfid),
+        }
+    }
+
+    fn undo_retrying(app: &TerminalApp) -> bool {
+        matches!(
+            &app.state,
+            AppState::OfflineConfirm { undo: Some(offer), .. } if offer.retrying
+        )
+    }
+
+    fn answer(app: &mut TerminalApp, rfid: &str, entry_type: &str) {
+        let _ = app.update(Message::ScanResult {
+            rfid: rfid.to_string(),
+            result: Ok(response(entry_type)),
+        });
+    }
+
+    /// Ticks until the screen moves on from the result; returns how many it took.
+    fn tick_past_confirmation(app: &mut TerminalApp) -> u64 {
+        let mut ticks = 0;
+        while matches!(
+            app.state,
+            AppState::ClockIn { .. } | AppState::ClockOut { .. } | AppState::Undone { .. }
+        ) {
+            let _ = app.update(Message::Tick);
+            ticks += 1;
+            assert!(ticks < 100, "confirmation never timed out");
+        }
+        ticks
+    }
+
+    #[test]
+    fn test_every_queued_badge_sees_its_confirmation() {
+        let mut app = app();
+        let queued_display = Timing::new(&app.config).queued_display_seconds;
+        for rfid in ["TAG-1", "TAG-2", "TAG-3"] {
+            let _ = app.update(Message::RfidScanned(rfid.to_string()));
+        }
+        assert!(matches!(&app.state, AppState::Loading { rfid } if rfid == "TAG-1"));
+        assert_eq!(app.engine.queued(), 2);
+
+        // Shown briefly while others wait, the last one in line for the full time.
+        for (rfid, next) in [("TAG-1", "TAG-2"), ("TAG-2", "TAG-3")] {
+            answer(&mut app, rfid, "CLOCK_IN");
+            assert!(
+                matches!(app.state, AppState::ClockIn { seconds_left, .. } if seconds_left == queued_display),
+                "{} not shown",
+                rfid
+            );
+            assert_eq!(tick_past_confirmation(&mut app), queued_display + 1);
+            assert!(matches!(&app.state, AppState::Loading { rfid } if rfid == next));
+        }
+
+        answer(&mut app, "TAG-3", "CLOCK_OUT");
+        assert!(matches!(app.state, AppState::ClockOut { .. }));
+        assert_eq!(
+            tick_past_confirmation(&mut app),
+            app.config.display.confirmation_seconds() + 1
+        );
+        assert!(matches!(app.state, AppState::Idle { .. }));
+        assert_eq!(app.engine.queued(), 0);
+    }
+
+    #[test]
+    fn test_badges_wait_behind_an_undo() {
+        let mut app = app();
+        for rfid in ["TAG-1", "TAG-2"] {
+            let _ = app.update(Message::RfidScanned(rfid.to_string()));
+        }
+        answer(&mut app, "TAG-1", "CLOCK_IN");
+        let queued_display = Timing::new(&app.config).queued_display_seconds;
+
+        // Neither taking the clocking back nor a badge read meanwhile drops the line.
+        let _ = app.update(Message::UndoRequested);
+        assert!(matches!(app.state, AppState::Undoing { .. }));
+        let _ = app.update(Message::RfidScanned("TAG-3".to_string()));
+        assert_eq!(app.engine.queued(), 2);
+        let _ = app.update(Message::UndoResult(Ok(())));
+        assert!(matches!(app.state, AppState::Undone { .. }));
+
+        assert_eq!(tick_past_confirmation(&mut app), queued_display + 1);
+        assert!(matches!(&app.state, AppState::Loading { rfid } if rfid == "TAG-2"));
+        assert_eq!(app.engine.queued(), 1);
+    }
+
+    #[test]
+    fn test_undo_retries_only_its_own_offer() {
+        let (mut app, cli, dir) = app_with_buffer("undo-retry");
+        let first = buffer_scan(&mut app, "TAG-1");
+
+        // A sync holds the buffer: the button stays and the undo is tried again.
+        let lock = cli.try_sync_lock().unwrap();
+        let _ = app.update(Message::UndoRequested);
+        assert!(undo_retrying(&app));
+        drop(lock);
+        let _ = app.update(Message::UndoRetry(first));
+        assert!(matches!(app.state, AppState::Undone { .. }));
+        assert_eq!(app.engine.pending_count, 0);
+
+        // Someone else's clocking came on screen meanwhile: a late retry leaves it alone.
+        tick_past_confirmation(&mut app);
+        let first = buffer_scan(&mut app, "TAG-2");
+        let lock = cli.try_sync_lock().unwrap();
+        let _ = app.update(Message::UndoRequested);
+        assert!(undo_retrying(&app));
+        let second = buffer_scan(&mut app, "TAG-3");
+        drop(lock);
+        let _ = app.update(Message::UndoRetry(first));
+        let _ = app.update(Message::UndoRetry(second));
+        assert!(matches!(
+            &app.state,
+            AppState::OfflineConfirm { undo: Some(offer), .. }
+                if !offer.retrying && offer.target == UndoTarget::Buffered(second)
+        ));
+        assert_eq!(app.engine.pending_count, 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_undo_still_busy_when_the_button_times_out_is_reported() {
+        let (mut app, cli, dir) = app_with_buffer("undo-timeout");
+        buffer_scan(&mut app, "TAG-1");
+
+        let _lock = cli.try_sync_lock().unwrap();
+        let _ = app.update(Message::UndoRequested);
+        let mut ticks = 0;
+        while undo_retrying(&app) {
+            let _ = app.update(Message::Tick);
+            ticks += 1;
+            assert!(ticks < 100, "undo offer never timed out");
+        }
+        assert!(matches!(
+            &app.state,
+            AppState::Error { data, .. } if data.message == "Buchung konnte nicht zur\u{00FC}ckgenommen werden"
+        ));
+        assert_eq!(app.engine.pending_count, 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_lockout_while_busy_follows_the_request_in_flight() {
+        let mut config = AppConfig::default();
+        config.offline.buffer_path = ":memory:".to_string();
+        config.audio.enabled = false;
+        config.company.sync_branding = false;
+        config.rfid.debounce_ms = 0;
+        config.rfid.max_rejected_scans = 2;
+        let mut app = TerminalApp::new((config, PathBuf::from("terminal.toml"))).0;
+        app.engine.set_blocklist(vec!["REVOKED".to_string()]);
+
+        let _ = app.update(Message::RfidScanned("TAG-1".to_string()));
+        let _ = app.update(Message::RfidScanned("TAG-2".to_string()));
+        for _ in 0..2 {
+            let _ = app.update(Message::RfidScanned("REVOKED".to_string()));
+        }
+        // The clocking in progress finishes; the badge queued behind it is dropped.
+        assert!(matches!(&app.state, AppState::Loading { rfid } if rfid == "TAG-1"));
+        assert_eq!(app.engine.queued(), 0);
+        answer(&mut app, "TAG-1", "CLOCK_IN");
+        assert!(matches!(app.state, AppState::LockedOut { .. }));
+        assert!(app.pending_lockout.is_none());
+    }
+}
@@ -0,0 +1,295 @@
+use iced::theme;
+use iced::{Color, Theme};
+
+/// Colours used by the screens.
+///
+/// The status colours (success, clock-out, warning) are fixed so employees can rely on them at
+/// every site.  Brand colours from the backend only tint neutral elements — headings and primary
+/// buttons — and are dropped if they could be mistaken for a status colour.
+///
+/// The high-contrast themes use a blue/orange/yellow status set that stays distinguishable with
+/// red-green colour blindness, full-contrast secondary text and no brand colours at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    /// Text colour for headings such as the company name.
+    pub brand: Color,
+    /// Background of primary buttons.
+    pub accent: Color,
+    pub success: Color,
+    pub success_background: Color,
+    pub clock_out: Color,
+    pub clock_out_background: Color,
+    /// Offline and error screens.
+    pub warning: Color,
+    pub warning_background: Color,
+    /// "Events are being synced" hint on the idle screen.
+    pub syncing: Color,
+    /// Labels and secondary information.
+    pub secondary_text: Color,
+    /// Countdown and other low-priority text.
+    pub muted_text: Color,
+    /// Photo placeholder tile.
+    pub placeholder: Color,
+    /// One of the high-contrast themes is active; screens add shape cues and borders.
+    pub high_contrast: bool,
+    base: theme::Palette,
+}
+
+pub const HIGH_CONTRAST: &str = "high-contrast";
+pub const HIGH_CONTRAST_LIGHT: &str = "high-contrast-light";
+
+/// Returns true for the theme names that select a high-contrast palette.
+pub fn is_high_contrast(theme_name: &str) -> bool {
+    theme_name == HIGH_CONTRAST || theme_name == HIGH_CONTRAST_LIGHT
+}
+
+/// Minimum RGB distance between a brand colour and any status colour.
+const MIN_STATUS_DISTANCE: f32 = 0.35;
+/// Minimum WCAG contrast ratio for brand-coloured text on the screen background.
+const MIN_TEXT_CONTRAST: f32 = 3.0;
+
+impl Palette {
+    /// Builds the palette for the configured theme (`"dark"`, `"light"`, `"high-contrast"` or
+    /// `"high-contrast-light"`) and optional brand colours in hex notation.
+    pub fn new(theme_name: &str, primary_color: Option<&str>, accent_color: Option<&str>) -> Self {
+        match theme_name {
+            HIGH_CONTRAST => return Self::high_contrast_dark(),
+            HIGH_CONTRAST_LIGHT => return Self::high_contrast_light(),
+            _ => {}
+        }
+
+        let base = if theme_name == "light" {
+            theme::Palette::LIGHT
+        } else {
+            theme::Palette::DARK
+        };
+
+        let mut palette = Self {
+            brand: base.text,
+            accent: base.primary,
+            success: Color::from_rgb(0.2, 0.9, 0.3),
+            success_background: Color::from_rgb(0.05, 0.15, 0.05),
+            clock_out: Color::from_rgb(0.95, 0.2, 0.2),
+            clock_out_background: Color::from_rgb(0.15, 0.03, 0.03),
+            warning: Color::from_rgb(1.0, 0.65, 0.0),
+            warning_background: Color::from_rgb(0.15, 0.10, 0.0),
+            syncing: Color::from_rgb(0.5, 0.8, 1.0),
+            secondary_text: Color::from_rgb(0.7, 0.7, 0.7),
+            muted_text: Color::from_rgb(0.6, 0.6, 0.6),
+            placeholder: Color::from_rgb(0.3, 0.3, 0.3),
+            high_contrast: false,
+            base,
+        };
+
+        let primary = primary_color.and_then(parse_hex_color);
+        let accent = accent_color.and_then(parse_hex_color).or(primary);
+
+        if let Some(color) = primary {
+            if palette.is_distinguishable(color)
+                && contrast_ratio(color, base.background) >= MIN_TEXT_CONTRAST
+            {
+                palette.brand = color;
+            }
+        }
+        if let Some(color) = accent {
+            if palette.is_distinguishable(color) {
+                palette.accent = color;
+            }
+        }
+        palette
+    }
+
+    /// White on black; status colours from the Okabe–Ito colour-blind safe set.
+    fn high_contrast_dark() -> Self {
+        let base = theme::Palette {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            primary: Color::from_rgb8(0xFF, 0xD6, 0x00),
+            success: Color::from_rgb8(0x56, 0xB4, 0xE9),
+            danger: Color::from_rgb8(0xE6, 0x9F, 0x00),
+        };
+        Self {
+            brand: Color::WHITE,
+            accent: base.primary,
+            success: base.success,
+            success_background: Color::BLACK,
+            clock_out: base.danger,
+            clock_out_background: Color::BLACK,
+            warning: Color::from_rgb8(0xF0, 0xE4, 0x42),
+            warning_background: Color::BLACK,
+            syncing: Color::WHITE,
+            secondary_text: Color::WHITE,
+            muted_text: Color::WHITE,
+            placeholder: Color::from_rgb8(0x40, 0x40, 0x40),
+            high_contrast: true,
+            base,
+        }
+    }
+
+    /// Black on white for brightly lit entrances.
+    fn high_contrast_light() -> Self {
+        let base = theme::Palette {
+            background: Color::WHITE,
+            text: Color::BLACK,
+            primary: Color::from_rgb8(0x00, 0x33, 0x99),
+            success: Color::from_rgb8(0x00, 0x4C, 0x99),
+            danger: Color::from_rgb8(0x99, 0x3D, 0x00),
+        };
+        Self {
+            brand: Color::BLACK,
+            accent: base.primary,
+            success: base.success,
+            success_background: Color::WHITE,
+            clock_out: base.danger,
+            clock_out_background: Color::WHITE,
+            warning: Color::from_rgb8(0x5C, 0x4A, 0x00),
+            warning_background: Color::WHITE,
+            syncing: Color::BLACK,
+            secondary_text: Color::BLACK,
+            muted_text: Color::BLACK,
+            placeholder: Color::from_rgb8(0xD0, 0xD0, 0xD0),
+            high_contrast: true,
+            base,
+        }
+    }
+
+    /// Screen background, used for borders that must stand out against it.
+    pub fn background(&self) -> Color {
+        self.base.background
+    }
+
+    /// iced theme with the brand accent as primary colour, so buttons follow the branding.
+    pub fn theme(&self) -> Theme {
+        if self.high_contrast {
+            Theme::custom("High Contrast".to_string(), self.base)
+        } else if self.accent == self.base.primary {
+            if self.base == theme::Palette::LIGHT {
+                Theme::Light
+            } else {
+                Theme::Dark
+            }
+        } else {
+            Theme::custom(
+                "Branding".to_string(),
+                theme::Palette {
+                    primary: self.accent,
+                    ..self.base
+                },
+            )
+        }
+    }
+
+    fn is_distinguishable(&self, color: Color) -> bool {
+        [self.success, self.clock_out, self.warning]
+            .iter()
+            .all(|status| distance(color, *status) >= MIN_STATUS_DISTANCE)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new("dark", None, None)
+    }
+}
+
+/// Parses `#RRGGBB`, `RRGGBB` or `#RGB`.
+pub fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16).ok();
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn distance(a: Color, b: Color) -> f32 {
+    ((a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2)).sqrt()
+}
+
+fn relative_luminance(color: Color) -> f32 {
+    let linear = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            parse_hex_color("#FF8000"),
+            Some(Color::from_rgb8(255, 128, 0))
+        );
+        assert_eq!(parse_hex_color("fff"), Some(Color::WHITE));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#GGGGGG"), None);
+    }
+
+    #[test]
+    fn test_brand_colours_are_applied() {
+        let palette = Palette::new("dark", Some("#8AB4F8"), None);
+        assert_eq!(palette.brand, Color::from_rgb8(0x8A, 0xB4, 0xF8));
+        // The accent falls back to the primary brand colour.
+        assert_eq!(palette.accent, palette.brand);
+        assert!(matches!(palette.theme(), Theme::Custom(_)));
+    }
+
+    #[test]
+    fn test_brand_colour_close_to_status_colour_is_rejected() {
+        // A green brand colour would make every heading look like a successful clock-in.
+        let default = Palette::default();
+        let palette = Palette::new("dark", Some("#40E050"), Some("#F03030"));
+        assert_eq!(palette.brand, default.brand);
+        assert_eq!(palette.accent, default.accent);
+        assert_eq!(palette.success, default.success);
+    }
+
+    #[test]
+    fn test_low_contrast_brand_text_is_rejected() {
+        // Dark navy is fine for buttons but unreadable as text on the dark background.
+        let palette = Palette::new("dark", Some("#102040"), None);
+        assert_eq!(palette.brand, Palette::default().brand);
+        assert_eq!(palette.accent, Color::from_rgb8(0x10, 0x20, 0x40));
+    }
+
+    #[test]
+    fn test_high_contrast_themes() {
+        for name in [HIGH_CONTRAST, HIGH_CONTRAST_LIGHT] {
+            let palette = Palette::new(name, Some("#8AB4F8"), Some("#8AB4F8"));
+            assert!(palette.high_contrast);
+            // Branding never weakens the accessibility palette.
+            assert_ne!(palette.brand, Color::from_rgb8(0x8A, 0xB4, 0xF8));
+
+            let background = palette.background();
+            for color in [
+                palette.brand,
+                palette.success,
+                palette.clock_out,
+                palette.warning,
+                palette.muted_text,
+            ] {
+                assert!(
+                    contrast_ratio(color, background) >= 4.5,
+                    "{:?} too faint on {} theme",
+                    color,
+                    name
+                );
+            }
+        }
+        assert!(!Palette::new("dark", None, None).high_contrast);
+    }
+}
@@ -2,20 +2,43 @@
 // Full i18n via fluent (.ftl files in terminal/locales/) is planned for a future phase.
 // All strings are candidates for extraction — do not add new hardcoded strings.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use iced::widget::container::Appearance;
-use iced::widget::{column, container, row, text, Column, Space};
-use iced::{Alignment, Color, Element, Length};
+use iced::widget::{
+    button, column, container, image, qr_code, row, scrollable, svg, text, text_input, Column, Row,
+    Space,
+};
+use iced::{theme, Alignment, Border, Color, Element, Length};
+use std::path::{Path, PathBuf};
 
+use super::layout::Layout;
+use super::palette::Palette;
 use super::Message;
+use crate::buffer::BufferStats;
+use crate::maintenance::ScanRecord;
+use crate::pin;
+use crate::projects::ProjectEntry;
 
 // ─── Data types ─────────────────────────────────────────────────────────────
 
+/// Employee photo on the clock-in/clock-out screens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhotoState {
+    /// Photos are disabled in the configuration.
+    Hidden,
+    /// No photo cached yet — downloading, offline or none on file.  Shows the initials.
+    Placeholder {
+        employee_id: String,
+    },
+    Loaded(PathBuf),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ClockInData {
     pub employee_name: String,
     pub timestamp: String,
     pub scheduled_hours: f32,
+    pub photo: PhotoState,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +51,24 @@ pub struct ClockOutData {
     pub weekly_hours_target: f32,
     pub overtime_minutes: i32,
     pub remaining_vacation_days: f32,
+    pub photo: PhotoState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoData {
+    pub employee_name: String,
+    pub clocked_in: bool,
+    pub today_work_minutes: u32,
+    pub overtime_minutes: i32,
+    pub remaining_vacation_days: f32,
+    pub upcoming_absences: Vec<AbsenceItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbsenceItem {
+    pub absence_type: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,56 +80,231 @@ pub struct ErrorData {
 #[derive(Debug, Clone, PartialEq)]
 pub enum ErrorType {
     BadgeNotRecognized,
+    /// The badge is on the blocklist.
+    BadgeBlocked,
     ServerUnavailable,
     Other,
 }
 
+/// Content of the maintenance screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceData {
+    pub version: String,
+    pub terminal_id: String,
+    /// `None` until the diagnostics finished (or when there is no route to the backend).
+    pub ip: Option<String>,
+    /// `None` while the health check is running.
+    pub backend_reachable: Option<bool>,
+    pub buffer: BufferStats,
+    pub recent_scans: Vec<ScanRecord>,
+    pub reader_test: ReaderTest,
+    /// Result of the last button, e.g. "Konfiguration neu geladen".
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReaderTest {
+    Off,
+    /// The next scan is shown instead of being processed.
+    Waiting,
+    /// Full ID of the scanned badge, e.g. for computing an admin badge hash.
+    Read(String),
+}
+
+/// Which value the PIN keypad is collecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinStep {
+    EmployeeNumber,
+    Pin,
+    /// Two-factor mode: the PIN for the badge that was just scanned.
+    BadgePin,
+}
+
+/// What the PIN keypad shows.  Holds only the length of the PIN, never its digits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinEntryData {
+    pub step: PinStep,
+    pub employee_number: String,
+    pub pin_length: usize,
+    /// Digit order of the keypad, row by row; the last digit sits between ⌫ and OK.
+    pub keypad: [u8; 10],
+    /// Wrong PIN, lockout or input hint.
+    pub message: Option<String>,
+}
+
 // ─── View helpers ────────────────────────────────────────────────────────────
 
+// Unscaled sizes of the idle screen; the layout tests check that it fits every supported
+// display.
+const IDLE_COMPANY_SIZE: f32 = 28.0;
+const IDLE_CLOCK_SIZE: f32 = 80.0;
+const IDLE_DATE_SIZE: f32 = 22.0;
+const IDLE_PROMPT_SIZE: f32 = 28.0;
+const IDLE_BUTTON_SIZE: f32 = 22.0;
+const IDLE_HINT_SIZE: f32 = 18.0;
+const IDLE_CLOCK_GAP: f32 = 30.0;
+const IDLE_PROMPT_GAP: f32 = 50.0;
+const IDLE_SECTION_GAP: f32 = 20.0;
+
+/// Text sizes of the idle screen top to bottom: company name, clock, date, prompt, info
+/// button, offline hint.
+#[cfg(test)]
+pub(super) const IDLE_TEXT_SIZES: [f32; 6] = [
+    IDLE_COMPANY_SIZE,
+    IDLE_CLOCK_SIZE,
+    IDLE_DATE_SIZE,
+    IDLE_PROMPT_SIZE,
+    IDLE_BUTTON_SIZE,
+    IDLE_HINT_SIZE,
+];
+/// Gaps of the idle screen top to bottom: above the clock and the prompt, above the buttons
+/// and the offline hint.
+#[cfg(test)]
+pub(super) const IDLE_SPACING: [f32; 4] = [
+    IDLE_CLOCK_GAP,
+    IDLE_PROMPT_GAP,
+    IDLE_SECTION_GAP,
+    IDLE_SECTION_GAP,
+];
+
 /// Idle/welcome screen: shows clock and "scan badge" prompt.
-pub fn idle_view(
+#[allow(clippy::too_many_arguments)]
+pub fn idle_view<'a>(
     now: &DateTime<Utc>,
     company_name: &str,
+    logo: Option<&Path>,
     pending_count: u32,
     is_offline: bool,
-) -> Element<'static, Message> {
+    show_info_button: bool,
+    show_pin_button: bool,
+    presence_qr: Option<&'a qr_code::Data>,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'a, Message> {
     let time_str = now.format("%H:%M:%S").to_string();
     let date_str = now.format("%A, %d. %B %Y").to_string();
 
-    let mut col: Column<Message> = column![
-        text(company_name.to_string()).size(28),
-        Space::with_height(30),
-        text(time_str).size(80),
-        text(date_str).size(22),
-        Space::with_height(50),
-        text("Bitte scannen Sie Ihren Ausweis").size(28),
-    ]
-    .spacing(8)
-    .align_items(Alignment::Center);
+    let mut col: Column<'a, Message> = column![]
+        .spacing(layout.space(8.0))
+        .align_items(Alignment::Center);
+    if let Some(logo) = logo {
+        col = col.push(logo_view(logo, layout));
+        col = col.push(Space::with_height(layout.space(10.0)));
+    }
+    col = col.extend([
+        text(company_name.to_string())
+            .size(layout.text(IDLE_COMPANY_SIZE))
+            .style(palette.brand)
+            .into(),
+        Space::with_height(layout.space(IDLE_CLOCK_GAP)).into(),
+        text(time_str).size(layout.text(IDLE_CLOCK_SIZE)).into(),
+        text(date_str).size(layout.text(IDLE_DATE_SIZE)).into(),
+        Space::with_height(layout.space(IDLE_PROMPT_GAP)).into(),
+        text("Bitte scannen Sie Ihren Ausweis")
+            .size(layout.text(IDLE_PROMPT_SIZE))
+            .into(),
+    ]);
+
+    let mut actions: Vec<Element<'static, Message>> = Vec::new();
+    if show_info_button {
+        actions.push(
+            button(text("Kontostand anzeigen").size(layout.text(IDLE_BUTTON_SIZE)))
+                .on_press(Message::InfoModeRequested)
+                .padding([layout.space(12.0), layout.space(24.0)])
+                .style(theme::Button::Secondary)
+                .into(),
+        );
+    }
+    if show_pin_button {
+        actions.push(
+            button(text("Ausweis vergessen? PIN eingeben").size(layout.text(IDLE_BUTTON_SIZE)))
+                .on_press(Message::PinEntryRequested)
+                .padding([layout.space(12.0), layout.space(24.0)])
+                .style(theme::Button::Secondary)
+                .into(),
+        );
+    }
+    if !actions.is_empty() {
+        col = col.push(Space::with_height(layout.space(IDLE_SECTION_GAP)));
+        let actions: Element<'static, Message> = if layout.portrait {
+            Column::with_children(actions)
+                .spacing(layout.space(12.0))
+                .align_items(Alignment::Center)
+                .into()
+        } else {
+            Row::with_children(actions)
+                .spacing(layout.space(20.0))
+                .into()
+        };
+        col = col.push(actions);
+    }
 
     if is_offline {
-        col = col.push(Space::with_height(20));
+        col = col.push(Space::with_height(layout.space(IDLE_SECTION_GAP)));
         col = col.push(
             text(format!(
                 "\u{26A0}  Offline  \u{2014}  {} ausstehende Ereignisse",
                 pending_count
             ))
-            .size(18)
-            .style(Color::from_rgb(1.0, 0.65, 0.0)),
+            .size(layout.text(IDLE_HINT_SIZE))
+            .style(palette.warning),
         );
     } else if pending_count > 0 {
-        col = col.push(Space::with_height(20));
+        col = col.push(Space::with_height(layout.space(IDLE_SECTION_GAP)));
         col = col.push(
             text(format!(
                 "\u{2191}  {} Ereignisse werden synchronisiert",
                 pending_count
             ))
-            .size(18)
-            .style(Color::from_rgb(0.5, 0.8, 1.0)),
+            .size(layout.text(IDLE_HINT_SIZE))
+            .style(palette.syncing),
         );
     }
 
-    container(col)
+    let Some(qr) = presence_qr else {
+        return container(col)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into();
+    };
+
+    // Dark cells on white with a quiet zone, whatever the theme: inverted codes are not
+    // read by every phone camera.
+    let code = container(
+        qr_code(qr)
+            .cell_size(layout.space(4.0).round().max(2.0) as u16)
+            .style(|_: &iced::Theme| qr_code::Appearance {
+                cell: Color::BLACK,
+                background: Color::WHITE,
+            }),
+    )
+    .padding(layout.space(12.0))
+    .style(|_: &iced::Theme| Appearance {
+        background: Some(Color::WHITE.into()),
+        ..Default::default()
+    });
+    let presence = column![
+        code,
+        text("Mit der App einstempeln")
+            .size(layout.text(16.0))
+            .style(palette.secondary_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    let content: Element<'a, Message> = if layout.portrait {
+        col.push(Space::with_height(layout.space(30.0)))
+            .push(presence)
+            .into()
+    } else {
+        row![col, presence]
+            .spacing(layout.space(60.0))
+            .align_items(Alignment::Center)
+            .into()
+    };
+    container(content)
         .width(Length::Fill)
         .height(Length::Fill)
         .center_x()
@@ -96,16 +312,23 @@ pub fn idle_view(
         .into()
 }
 
-/// Loading screen shown while waiting for API response.
-pub fn loading_view() -> Element<'static, Message> {
-    let col = column![
-        text("Verarbeitung\u{2026}").size(36),
-        Space::with_height(20),
-        text("Bitte warten").size(22),
+/// Loading screen shown while waiting for API response, with the number of badges queued
+/// behind it.
+pub fn loading_view(queued: usize, layout: &Layout) -> Element<'static, Message> {
+    let mut col = column![
+        text("Verarbeitung\u{2026}").size(layout.text(36.0)),
+        Space::with_height(layout.space(20.0)),
+        text("Bitte warten").size(layout.text(22.0)),
     ]
-    .spacing(8)
+    .spacing(layout.space(8.0))
     .align_items(Alignment::Center);
 
+    if queued > 0 {
+        col = col
+            .push(Space::with_height(layout.space(20.0)))
+            .push(queue_note(queued, layout));
+    }
+
     container(col)
         .width(Length::Fill)
         .height(Length::Fill)
@@ -115,80 +338,441 @@ pub fn loading_view() -> Element<'static, Message> {
 }
 
 /// Green clock-in confirmation screen.
-pub fn clock_in_view(data: &ClockInData, seconds_left: u64) -> Element<'static, Message> {
-    let col = column![
+pub fn clock_in_view(
+    data: &ClockInData,
+    seconds_left: u64,
+    show_project_button: bool,
+    undo_seconds: Option<u64>,
+    queued: usize,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let mut col = column![
+        status_badge(Status::Success, palette, layout),
         text("\u{2713}  Eingestempelt")
-            .size(48)
-            .style(Color::from_rgb(0.2, 0.9, 0.3)),
-        Space::with_height(30),
-        text(data.employee_name.clone()).size(36),
-        Space::with_height(10),
-        text(format!("Uhrzeit: {}", data.timestamp)).size(24),
-        Space::with_height(40),
-        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
-            .size(18)
-            .style(Color::from_rgb(0.6, 0.6, 0.6)),
+            .size(layout.text(48.0))
+            .style(palette.success),
+        Space::with_height(layout.space(30.0)),
+        photo_view(&data.photo, &data.employee_name, palette, layout),
+        text(data.employee_name.clone()).size(layout.text(36.0)),
+        Space::with_height(layout.space(10.0)),
+        text(format!("Uhrzeit: {}", data.timestamp)).size(layout.text(24.0)),
+        Space::with_height(layout.space(40.0)),
     ]
-    .spacing(8)
+    .spacing(layout.space(8.0))
     .align_items(Alignment::Center);
 
+    if show_project_button {
+        col = col.push(project_button(layout));
+        col = col.push(Space::with_height(layout.space(20.0)));
+    }
+    if let Some(undo_seconds) = undo_seconds {
+        col = col.push(undo_button(undo_seconds, layout));
+        col = col.push(Space::with_height(layout.space(20.0)));
+    }
+    if queued > 0 {
+        col = col.push(queue_note(queued, layout));
+    }
+    col = col.push(
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    );
+
     container(col)
         .width(Length::Fill)
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(|_: &iced::Theme| Appearance {
-            background: Some(Color::from_rgb(0.05, 0.15, 0.05).into()),
-            ..Appearance::default()
-        })
+        .style(status_appearance(Status::Success, palette, layout))
         .into()
 }
 
 /// Red clock-out confirmation screen with summary.
-pub fn clock_out_view(data: &ClockOutData, seconds_left: u64) -> Element<'static, Message> {
+pub fn clock_out_view(
+    data: &ClockOutData,
+    seconds_left: u64,
+    undo_seconds: Option<u64>,
+    queued: usize,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
     let hours = data.hours_worked as u32;
     let minutes = ((data.hours_worked - hours as f32) * 60.0) as u32;
-    let overtime_sign = if data.overtime_minutes >= 0 { "+" } else { "" };
-    let overtime_hours = data.overtime_minutes / 60;
-    let overtime_mins = data.overtime_minutes.abs() % 60;
 
-    let col = column![
+    let mut col = column![
+        status_badge(Status::ClockOut, palette, layout),
         text("\u{2717}  Ausgestempelt")
-            .size(48)
-            .style(Color::from_rgb(0.95, 0.2, 0.2)),
-        Space::with_height(30),
-        text(data.employee_name.clone()).size(36),
-        Space::with_height(10),
-        text(format!("Uhrzeit: {}", data.timestamp)).size(24),
-        Space::with_height(30),
+            .size(layout.text(48.0))
+            .style(palette.clock_out),
+        Space::with_height(layout.space(30.0)),
+        photo_view(&data.photo, &data.employee_name, palette, layout),
+        text(data.employee_name.clone()).size(layout.text(36.0)),
+        Space::with_height(layout.space(10.0)),
+        text(format!("Uhrzeit: {}", data.timestamp)).size(layout.text(24.0)),
+        Space::with_height(layout.space(30.0)),
+        summary_group(
+            vec![
+                summary_item(
+                    "Arbeitszeit",
+                    &format!("{}h {:02}min", hours, minutes),
+                    palette,
+                    layout
+                ),
+                summary_item(
+                    "Pause",
+                    &format!("{}min", data.break_minutes),
+                    palette,
+                    layout
+                ),
+            ],
+            layout
+        ),
+        Space::with_height(layout.space(10.0)),
+        summary_group(
+            vec![
+                summary_item(
+                    "\u{00DC}berstunden",
+                    &format_overtime(data.overtime_minutes),
+                    palette,
+                    layout
+                ),
+                summary_item(
+                    "Resturlaub",
+                    &format!("{:.1} Tage", data.remaining_vacation_days),
+                    palette,
+                    layout
+                ),
+            ],
+            layout
+        ),
+        Space::with_height(layout.space(40.0)),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    if let Some(undo_seconds) = undo_seconds {
+        col = col.push(undo_button(undo_seconds, layout));
+        col = col.push(Space::with_height(layout.space(20.0)));
+    }
+    if queued > 0 {
+        col = col.push(queue_note(queued, layout));
+    }
+    col = col.push(
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    );
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(status_appearance(Status::ClockOut, palette, layout))
+        .into()
+}
+
+/// Orange offline confirmation screen (event was buffered locally).
+pub fn offline_confirm_view(
+    data: &ClockInData,
+    seconds_left: u64,
+    show_project_button: bool,
+    undo_seconds: Option<u64>,
+    queued: usize,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let mut col = column![
+        status_badge(Status::Offline, palette, layout),
+        text("\u{2191}  Offline gespeichert")
+            .size(layout.text(42.0))
+            .style(palette.warning),
+        Space::with_height(layout.space(20.0)),
+        text("Der Scan wurde lokal gespeichert").size(layout.text(24.0)),
+        text("und beim n\u{00E4}chsten Start synchronisiert.").size(layout.text(24.0)),
+        Space::with_height(layout.space(20.0)),
+        text(format!("Uhrzeit: {}", data.timestamp)).size(layout.text(20.0)),
+        Space::with_height(layout.space(40.0)),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    if show_project_button {
+        col = col.push(project_button(layout));
+        col = col.push(Space::with_height(layout.space(20.0)));
+    }
+    if let Some(undo_seconds) = undo_seconds {
+        col = col.push(undo_button(undo_seconds, layout));
+        col = col.push(Space::with_height(layout.space(20.0)));
+    }
+    if queued > 0 {
+        col = col.push(queue_note(queued, layout));
+    }
+    col = col.push(
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    );
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(status_appearance(Status::Offline, palette, layout))
+        .into()
+}
+
+/// Shown after a clocking was taken back with "Rückgängig".
+pub fn undone_view(
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let col = column![
+        text("\u{21B6}  Buchung zur\u{00FC}ckgenommen")
+            .size(layout.text(42.0))
+            .style(palette.brand),
+        Space::with_height(layout.space(20.0)),
+        text("Die Stempelung wurde gel\u{00F6}scht.").size(layout.text(24.0)),
+        text("Bitte scannen Sie erneut, falls n\u{00F6}tig.")
+            .size(layout.text(20.0))
+            .style(palette.secondary_text),
+        Space::with_height(layout.space(40.0)),
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Shown while the reader ignores every badge after a burst of unknown or revoked ones.
+pub fn locked_out_view(
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let col = column![
+        status_badge(Status::Error, palette, layout),
+        text("\u{26D4}  Leser vor\u{00FC}bergehend gesperrt")
+            .size(layout.text(42.0))
+            .style(palette.warning),
+        Space::with_height(layout.space(20.0)),
+        text("Zu viele unbekannte Ausweise in kurzer Zeit.").size(layout.text(24.0)),
+        text("Bitte wenden Sie sich an die Personalabteilung.")
+            .size(layout.text(20.0))
+            .style(palette.secondary_text),
+        Space::with_height(layout.space(40.0)),
+        text(format!("Wieder bereit in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Prompt shown after "Kontostand anzeigen": the next scan only looks up balances.
+pub fn info_prompt_view(
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let col = column![
+        text("Kontostand anzeigen")
+            .size(layout.text(42.0))
+            .style(palette.brand),
+        Space::with_height(layout.space(20.0)),
+        text("Bitte scannen Sie Ihren Ausweis").size(layout.text(28.0)),
+        text("Es wird keine Buchung erstellt.")
+            .size(layout.text(20.0))
+            .style(palette.secondary_text),
+        Space::with_height(layout.space(30.0)),
+        button(text("Abbrechen").size(layout.text(22.0)))
+            .on_press(Message::InfoModeCancelled)
+            .padding([layout.space(12.0), layout.space(24.0)])
+            .style(theme::Button::Secondary),
+        Space::with_height(layout.space(20.0)),
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Asks before clocking a badge that was clocked moments ago.  `last_entry` is the entry
+/// type of that clocking, `None` if it was buffered offline.
+pub fn confirm_repeat_view(
+    last_entry: Option<&str>,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let (question, confirm) = match last_entry {
+        Some("CLOCK_IN") => (
+            "Sie haben sich gerade eingestempelt \u{2013} wirklich ausstempeln?",
+            "Ja, ausstempeln",
+        ),
+        Some(_) => (
+            "Sie haben sich gerade ausgestempelt \u{2013} wirklich einstempeln?",
+            "Ja, einstempeln",
+        ),
+        None => (
+            "Sie haben gerade gestempelt \u{2013} wirklich erneut stempeln?",
+            "Ja, erneut stempeln",
+        ),
+    };
+
+    let col = column![
+        text(question)
+            .size(layout.text(32.0))
+            .style(palette.warning),
+        Space::with_height(layout.space(30.0)),
         row![
-            summary_item("Arbeitszeit", &format!("{}h {:02}min", hours, minutes)),
-            Space::with_width(40),
-            summary_item("Pause", &format!("{}min", data.break_minutes)),
+            button(text("Abbrechen").size(layout.text(22.0)))
+                .on_press(Message::RepeatCancelled)
+                .padding([layout.space(12.0), layout.space(24.0)])
+                .style(theme::Button::Secondary),
+            button(text(confirm).size(layout.text(22.0)))
+                .on_press(Message::RepeatConfirmed)
+                .padding([layout.space(12.0), layout.space(24.0)])
+                .style(theme::Button::Primary),
         ]
-        .align_items(Alignment::Center),
-        Space::with_height(10),
+        .spacing(layout.space(24.0)),
+        Space::with_height(layout.space(20.0)),
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .padding(layout.space(24.0))
+        .into()
+}
+
+/// Numeric keypad for clocking with employee number and PIN.
+pub fn pin_entry_view(
+    data: &PinEntryData,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let (prompt, value, can_submit) = match data.step {
+        PinStep::EmployeeNumber => (
+            "Personalnummer eingeben".to_string(),
+            data.employee_number.clone(),
+            !data.employee_number.is_empty(),
+        ),
+        PinStep::Pin => (
+            format!("PIN f\u{00FC}r Personalnummer {}", data.employee_number),
+            "\u{25CF} ".repeat(data.pin_length).trim_end().to_string(),
+            data.pin_length >= pin::MIN_PIN_LEN,
+        ),
+        PinStep::BadgePin => (
+            "Bitte PIN eingeben".to_string(),
+            "\u{25CF} ".repeat(data.pin_length).trim_end().to_string(),
+            data.pin_length >= pin::MIN_PIN_LEN,
+        ),
+    };
+
+    let key_size = layout.space(72.0);
+    let key = |label: String, message: Option<Message>, style: theme::Button| {
+        button(
+            container(text(label).size(layout.text(30.0)))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y(),
+        )
+        .on_press_maybe(message)
+        .width(key_size)
+        .height(key_size)
+        .style(style)
+    };
+    let digit = |d: u8| {
+        key(
+            d.to_string(),
+            Some(Message::PinDigit(d)),
+            theme::Button::Secondary,
+        )
+    };
+
+    let mut keypad: Column<Message> = column![]
+        .spacing(layout.space(8.0))
+        .align_items(Alignment::Center);
+    for keys in data.keypad[..9].chunks(3) {
+        keypad = keypad.push(
+            Row::with_children(keys.iter().map(|&d| digit(d).into())).spacing(layout.space(8.0)),
+        );
+    }
+    keypad = keypad.push(
         row![
-            summary_item(
-                "\u{00DC}berstunden",
-                &format!(
-                    "{}{}h {:02}min",
-                    overtime_sign, overtime_hours, overtime_mins
-                ),
+            key(
+                "\u{232B}".to_string(),
+                Some(Message::PinBackspace),
+                theme::Button::Secondary
             ),
-            Space::with_width(40),
-            summary_item(
-                "Resturlaub",
-                &format!("{:.1} Tage", data.remaining_vacation_days),
+            digit(data.keypad[9]),
+            key(
+                "OK".to_string(),
+                can_submit.then_some(Message::PinSubmit),
+                theme::Button::Primary
             ),
         ]
-        .align_items(Alignment::Center),
-        Space::with_height(40),
+        .spacing(layout.space(8.0)),
+    );
+
+    let col = column![
+        text(prompt).size(layout.text(28.0)).style(palette.brand),
+        text(if value.is_empty() {
+            "\u{2014}".to_string()
+        } else {
+            value
+        })
+        .size(layout.text(36.0)),
+        text(data.message.clone().unwrap_or_default())
+            .size(layout.text(20.0))
+            .style(palette.warning),
+        keypad,
+        Space::with_height(layout.space(10.0)),
+        button(text("Abbrechen").size(layout.text(22.0)))
+            .on_press(Message::PinCancelled)
+            .padding([layout.space(12.0), layout.space(24.0)])
+            .style(theme::Button::Secondary),
         text(format!("Zur\u{00FC}ck in {}s", seconds_left))
-            .size(18)
-            .style(Color::from_rgb(0.6, 0.6, 0.6)),
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
     ]
-    .spacing(8)
+    .spacing(layout.space(10.0))
     .align_items(Alignment::Center);
 
     container(col)
@@ -196,30 +780,85 @@ pub fn clock_out_view(data: &ClockOutData, seconds_left: u64) -> Element<'static
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(|_: &iced::Theme| Appearance {
-            background: Some(Color::from_rgb(0.15, 0.03, 0.03).into()),
-            ..Appearance::default()
-        })
         .into()
 }
 
-/// Orange offline confirmation screen (event was buffered locally).
-pub fn offline_confirm_view(data: &ClockInData, seconds_left: u64) -> Element<'static, Message> {
+/// Read-only balance overview for an info scan.
+pub fn info_view(
+    data: &InfoData,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let today_hours = data.today_work_minutes / 60;
+    let today_minutes = data.today_work_minutes % 60;
+    let clock_state = if data.clocked_in {
+        "Aktuell eingestempelt"
+    } else {
+        "Aktuell nicht eingestempelt"
+    };
+
+    let mut absences: Column<Message> = column![text("Geplante Abwesenheiten")
+        .size(layout.text(16.0))
+        .style(palette.secondary_text)]
+    .spacing(layout.space(4.0))
+    .align_items(Alignment::Center);
+    if data.upcoming_absences.is_empty() {
+        absences = absences.push(text("Keine").size(layout.text(20.0)));
+    }
+    for absence in data.upcoming_absences.iter().take(3) {
+        absences = absences.push(
+            text(format!(
+                "{}  {} \u{2013} {}",
+                absence_label(&absence.absence_type),
+                absence.start_date.format("%d.%m."),
+                absence.end_date.format("%d.%m.%Y"),
+            ))
+            .size(layout.text(20.0)),
+        );
+    }
+
     let col = column![
-        text("\u{2191}  Offline gespeichert")
-            .size(42)
-            .style(Color::from_rgb(1.0, 0.65, 0.0)),
-        Space::with_height(20),
-        text("Der Scan wurde lokal gespeichert").size(24),
-        text("und beim n\u{00E4}chsten Start synchronisiert.").size(24),
-        Space::with_height(20),
-        text(format!("Uhrzeit: {}", data.timestamp)).size(20),
-        Space::with_height(40),
+        text("Kontostand")
+            .size(layout.text(48.0))
+            .style(palette.brand),
+        Space::with_height(layout.space(20.0)),
+        text(data.employee_name.clone()).size(layout.text(36.0)),
+        text(clock_state)
+            .size(layout.text(20.0))
+            .style(palette.secondary_text),
+        Space::with_height(layout.space(30.0)),
+        summary_group(
+            vec![
+                summary_item(
+                    "Heute",
+                    &format!("{}h {:02}min", today_hours, today_minutes),
+                    palette,
+                    layout
+                ),
+                summary_item(
+                    "\u{00DC}berstunden",
+                    &format_overtime(data.overtime_minutes),
+                    palette,
+                    layout
+                ),
+                summary_item(
+                    "Resturlaub",
+                    &format!("{:.1} Tage", data.remaining_vacation_days),
+                    palette,
+                    layout
+                ),
+            ],
+            layout
+        ),
+        Space::with_height(layout.space(20.0)),
+        absences,
+        Space::with_height(layout.space(40.0)),
         text(format!("Zur\u{00FC}ck in {}s", seconds_left))
-            .size(18)
-            .style(Color::from_rgb(0.6, 0.6, 0.6)),
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
     ]
-    .spacing(8)
+    .spacing(layout.space(8.0))
     .align_items(Alignment::Center);
 
     container(col)
@@ -227,20 +866,123 @@ pub fn offline_confirm_view(data: &ClockInData, seconds_left: u64) -> Element<'s
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(|_: &iced::Theme| Appearance {
-            background: Some(Color::from_rgb(0.15, 0.10, 0.0).into()),
-            ..Appearance::default()
-        })
+        .into()
+}
+
+/// Touch list of projects (favourites first) with a search field.
+pub fn project_select_view(
+    entries: Vec<ProjectEntry>,
+    query: &str,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let search = text_input("Projekt suchen\u{2026}", query)
+        .on_input(Message::ProjectSearchChanged)
+        .size(layout.text(24.0))
+        .padding(layout.space(12.0));
+
+    let mut list: Column<Message> = column![].spacing(layout.space(8.0)).width(Length::Fill);
+    if entries.is_empty() {
+        list = list.push(
+            text("Keine Projekte gefunden")
+                .size(layout.text(22.0))
+                .style(palette.secondary_text),
+        );
+    }
+    for entry in entries {
+        let marker = if entry.favourite { "\u{2605}  " } else { "" };
+        let label = column![
+            text(format!("{}{}", marker, entry.project.name)).size(layout.text(26.0)),
+            text(entry.project.code.clone())
+                .size(layout.text(16.0))
+                .style(palette.secondary_text),
+        ]
+        .spacing(layout.space(2.0));
+        list = list.push(
+            button(label)
+                .on_press(Message::ProjectChosen(entry.project.id))
+                .width(Length::Fill)
+                .padding(layout.space(16.0))
+                .style(theme::Button::Secondary),
+        );
+    }
+
+    let col = column![
+        text("Projekt w\u{00E4}hlen")
+            .size(layout.text(36.0))
+            .style(palette.brand),
+        search,
+        scrollable(list).height(Length::Fill),
+        row![
+            button(text("Abbrechen").size(layout.text(22.0)))
+                .on_press(Message::ProjectSelectCancelled)
+                .padding(layout.space(14.0))
+                .style(theme::Button::Destructive),
+            Space::with_width(Length::Fill),
+            text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+                .size(layout.text(18.0))
+                .style(palette.muted_text),
+        ]
+        .align_items(Alignment::Center),
+    ]
+    .spacing(layout.space(12.0))
+    .padding(layout.space(24.0));
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+/// Confirmation that working time is now booked on `project_name`.
+pub fn project_confirm_view(
+    project_name: &str,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let col = column![
+        status_badge(Status::Success, palette, layout),
+        text("\u{2713}  Projekt gebucht")
+            .size(layout.text(48.0))
+            .style(palette.success),
+        Space::with_height(layout.space(30.0)),
+        text(project_name.to_string()).size(layout.text(36.0)),
+        Space::with_height(layout.space(40.0)),
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(8.0))
+    .align_items(Alignment::Center);
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(status_appearance(Status::Success, palette, layout))
         .into()
 }
 
 /// Yellow/orange error screen.
-pub fn error_view(data: &ErrorData, seconds_left: u64) -> Element<'static, Message> {
+pub fn error_view(
+    data: &ErrorData,
+    seconds_left: u64,
+    queued: usize,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
     let (icon, description) = match data.error_type {
         ErrorType::BadgeNotRecognized => (
             "\u{26A0}  Ausweis nicht erkannt",
             "Dieser Ausweis ist nicht registriert.",
         ),
+        ErrorType::BadgeBlocked => (
+            "\u{26D4}  Ausweis gesperrt",
+            "Dieser Ausweis wurde gesperrt.",
+        ),
         ErrorType::ServerUnavailable => (
             "\u{26A0}  Server nicht erreichbar",
             "Bitte versuchen Sie es sp\u{00E4}ter erneut.",
@@ -251,44 +993,410 @@ pub fn error_view(data: &ErrorData, seconds_left: u64) -> Element<'static, Messa
         ),
     };
 
-    let col = column![
-        text(icon).size(42).style(Color::from_rgb(1.0, 0.65, 0.0)),
-        Space::with_height(20),
-        text(description).size(24),
-        Space::with_height(10),
+    let mut col = column![
+        status_badge(Status::Error, palette, layout),
+        text(icon).size(layout.text(42.0)).style(palette.warning),
+        Space::with_height(layout.space(20.0)),
+        text(description).size(layout.text(24.0)),
+        Space::with_height(layout.space(10.0)),
         text(data.message.clone())
-            .size(18)
-            .style(Color::from_rgb(0.7, 0.7, 0.7)),
-        Space::with_height(40),
-        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
-            .size(18)
-            .style(Color::from_rgb(0.6, 0.6, 0.6)),
+            .size(layout.text(18.0))
+            .style(palette.secondary_text),
+        Space::with_height(layout.space(40.0)),
     ]
-    .spacing(8)
+    .spacing(layout.space(8.0))
     .align_items(Alignment::Center);
 
+    if queued > 0 {
+        col = col.push(queue_note(queued, layout));
+    }
+    col = col.push(
+        text(format!("Zur\u{00FC}ck in {}s", seconds_left))
+            .size(layout.text(18.0))
+            .style(palette.muted_text),
+    );
+
     container(col)
         .width(Length::Fill)
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(|_: &iced::Theme| Appearance {
-            background: Some(Color::from_rgb(0.15, 0.10, 0.0).into()),
-            ..Appearance::default()
+        .style(status_appearance(Status::Error, palette, layout))
+        .into()
+}
+
+/// Diagnostics and service actions, opened with an admin badge.
+pub fn maintenance_view(
+    data: &MaintenanceData,
+    seconds_left: u64,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'static, Message> {
+    let connectivity = match data.backend_reachable {
+        None => (
+            "Wird gepr\u{00FC}ft\u{2026}".to_string(),
+            palette.secondary_text,
+        ),
+        Some(true) => ("\u{2713}  Backend erreichbar".to_string(), palette.success),
+        Some(false) => (
+            "\u{26A0}  Backend nicht erreichbar".to_string(),
+            palette.warning,
+        ),
+    };
+    let oldest = data
+        .buffer
+        .oldest_pending
+        .map(|ts| ts.format("%d.%m.%Y %H:%M").to_string())
+        .unwrap_or_else(|| "\u{2014}".to_string());
+
+    let status: Column<Message> = column![
+        summary_line("Version", &data.version, palette, layout),
+        summary_line("Terminal-ID", &data.terminal_id, palette, layout),
+        summary_line(
+            "IP-Adresse",
+            data.ip.as_deref().unwrap_or("\u{2014}"),
+            palette,
+            layout
+        ),
+        text(connectivity.0)
+            .size(layout.text(20.0))
+            .style(connectivity.1),
+        Space::with_height(layout.space(10.0)),
+        summary_line(
+            "Ausstehend",
+            &data.buffer.pending.to_string(),
+            palette,
+            layout
+        ),
+        summary_line(
+            "Synchronisiert",
+            &data.buffer.synced.to_string(),
+            palette,
+            layout
+        ),
+        summary_line("\u{00C4}ltestes Ereignis", &oldest, palette, layout),
+        Space::with_height(layout.space(10.0)),
+        text(match &data.reader_test {
+            ReaderTest::Off => String::new(),
+            ReaderTest::Waiting => "Bitte Ausweis auflegen\u{2026}".to_string(),
+            ReaderTest::Read(tag) => format!("Gelesen: {}", tag),
         })
+        .size(layout.text(20.0))
+        .style(palette.brand),
+        text(data.status.clone().unwrap_or_default())
+            .size(layout.text(18.0))
+            .style(palette.secondary_text),
+    ]
+    .spacing(layout.space(6.0));
+
+    let mut scans: Column<Message> = column![text("Letzte Scans")
+        .size(layout.text(16.0))
+        .style(palette.secondary_text)]
+    .spacing(layout.space(4.0));
+    if data.recent_scans.is_empty() {
+        scans = scans.push(text("Keine").size(layout.text(18.0)));
+    }
+    for scan in &data.recent_scans {
+        scans = scans.push(
+            text(format!(
+                "{}  {}  {}",
+                scan.time.format("%H:%M:%S"),
+                scan.tag,
+                scan.outcome.label()
+            ))
+            .size(layout.text(18.0)),
+        );
+    }
+
+    let details: Element<'static, Message> = if layout.portrait {
+        column![status, scans].spacing(layout.space(20.0)).into()
+    } else {
+        row![
+            container(status).width(Length::FillPortion(1)),
+            container(scans).width(Length::FillPortion(1)),
+        ]
+        .spacing(layout.space(30.0))
+        .into()
+    };
+
+    let action = |label: &str, message: Message| {
+        button(text(label.to_string()).size(layout.text(18.0)))
+            .on_press(message)
+            .padding([layout.space(10.0), layout.space(16.0)])
+            .style(theme::Button::Secondary)
+    };
+    let actions = row![
+        action("Jetzt synchronisieren", Message::MaintenanceSyncNow),
+        action("Ton testen", Message::MaintenanceTestSound),
+        action("Leser testen", Message::MaintenanceTestReader),
+        action("Konfiguration neu laden", Message::MaintenanceReloadConfig),
+        Space::with_width(Length::Fill),
+        button(text("Beenden").size(layout.text(18.0)))
+            .on_press(Message::MaintenanceExit)
+            .padding([layout.space(10.0), layout.space(16.0)])
+            .style(theme::Button::Destructive),
+    ]
+    .spacing(layout.space(10.0))
+    .align_items(Alignment::Center);
+    let actions: Element<'static, Message> = if layout.portrait {
+        // Too narrow for one row of five buttons.
+        scrollable(actions)
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default(),
+            ))
+            .into()
+    } else {
+        actions.into()
+    };
+
+    let col = column![
+        text("Wartungsmodus")
+            .size(layout.text(36.0))
+            .style(palette.brand),
+        scrollable(details).height(Length::Fill),
+        actions,
+        text(format!("Sperrt in {}s", seconds_left))
+            .size(layout.text(16.0))
+            .style(palette.muted_text),
+    ]
+    .spacing(layout.space(12.0))
+    .padding(layout.space(24.0));
+
+    container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
         .into()
 }
 
 // ─── Private helpers ─────────────────────────────────────────────────────────
 
-fn summary_item<'a>(label: &str, value: &str) -> Element<'a, Message> {
+const LOGO_WIDTH: f32 = 240.0;
+const LOGO_HEIGHT: f32 = 90.0;
+
+/// Company logo; SVG logos are rendered as vectors, everything else as raster image.
+fn logo_view<'a>(path: &Path, layout: &Layout) -> Element<'a, Message> {
+    let is_svg = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        svg(svg::Handle::from_path(path))
+            .width(layout.space(LOGO_WIDTH))
+            .height(layout.space(LOGO_HEIGHT))
+            .into()
+    } else {
+        image(image::Handle::from_path(path))
+            .width(layout.space(LOGO_WIDTH))
+            .height(layout.space(LOGO_HEIGHT))
+            .into()
+    }
+}
+
+const PHOTO_SIZE: f32 = 220.0;
+
+/// Large employee photo, or a grey tile with the initials while none is available.
+fn photo_view<'a>(
+    photo: &PhotoState,
+    employee_name: &str,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'a, Message> {
+    match photo {
+        PhotoState::Hidden => Space::with_height(0).into(),
+        PhotoState::Loaded(path) => image(image::Handle::from_path(path))
+            .width(layout.space(PHOTO_SIZE))
+            .height(layout.space(PHOTO_SIZE))
+            .into(),
+        PhotoState::Placeholder { .. } => {
+            let initials: String = employee_name
+                .split_whitespace()
+                .filter_map(|part| part.chars().next())
+                .take(2)
+                .collect();
+            container(text(initials).size(layout.text(72.0)))
+                .width(layout.space(PHOTO_SIZE))
+                .height(layout.space(PHOTO_SIZE))
+                .center_x()
+                .center_y()
+                .style(tinted(palette.placeholder))
+                .into()
+        }
+    }
+}
+
+/// Outcome shown on a confirmation or error screen.
+#[derive(Debug, Clone, Copy)]
+enum Status {
+    Success,
+    ClockOut,
+    Offline,
+    Error,
+}
+
+impl Status {
+    fn color(self, palette: &Palette) -> Color {
+        match self {
+            Status::Success => palette.success,
+            Status::ClockOut => palette.clock_out,
+            Status::Offline | Status::Error => palette.warning,
+        }
+    }
+
+    fn background(self, palette: &Palette) -> Color {
+        match self {
+            Status::Success => palette.success_background,
+            Status::ClockOut => palette.clock_out_background,
+            Status::Offline | Status::Error => palette.warning_background,
+        }
+    }
+}
+
+const BADGE_SIZE: f32 = 110.0;
+
+/// Large symbol whose shape alone tells the outcome on high-contrast themes: a circle for
+/// success, a square for clock-out and a wide bar for offline and error screens.  Nothing is
+/// shown on the regular themes.
+fn status_badge<'a>(status: Status, palette: &Palette, layout: &Layout) -> Element<'a, Message> {
+    if !palette.high_contrast {
+        return Space::with_height(0).into();
+    }
+
+    let size = layout.space(BADGE_SIZE);
+    let (glyph, width, radius) = match status {
+        Status::Success => ("\u{2713}", size, size / 2.0),
+        Status::ClockOut => ("\u{2717}", size, 0.0),
+        Status::Offline => ("\u{2191}", size * 1.8, size / 6.0),
+        Status::Error => ("!", size * 1.8, size / 6.0),
+    };
+    container(
+        text(glyph)
+            .size(layout.text(BADGE_SIZE * 0.6))
+            .style(palette.background()),
+    )
+    .width(width)
+    .height(size)
+    .center_x()
+    .center_y()
+    .style(Appearance {
+        background: Some(status.color(palette).into()),
+        border: Border::with_radius(radius),
+        ..Appearance::default()
+    })
+    .into()
+}
+
+/// Status background; high-contrast themes frame the whole screen in the status colour instead
+/// of relying on a faint tint.
+fn status_appearance(status: Status, palette: &Palette, layout: &Layout) -> Appearance {
+    if palette.high_contrast {
+        Appearance {
+            background: Some(status.background(palette).into()),
+            border: Border {
+                color: status.color(palette),
+                width: layout.space(14.0),
+                radius: 0.0.into(),
+            },
+            ..Appearance::default()
+        }
+    } else {
+        tinted(status.background(palette))
+    }
+}
+
+fn tinted(color: Color) -> Appearance {
+    Appearance {
+        background: Some(color.into()),
+        ..Appearance::default()
+    }
+}
+
+fn format_overtime(overtime_minutes: i32) -> String {
+    let sign = if overtime_minutes >= 0 { "+" } else { "-" };
+    let minutes = overtime_minutes.unsigned_abs();
+    format!("{}{}h {:02}min", sign, minutes / 60, minutes % 60)
+}
+
+fn absence_label(absence_type: &str) -> &'static str {
+    match absence_type {
+        "VACATION" => "Urlaub",
+        "SICK_LEAVE" => "Krankheit",
+        "BUSINESS_TRIP" => "Dienstreise",
+        _ => "Abwesenheit",
+    }
+}
+
+fn project_button<'a>(layout: &Layout) -> Element<'a, Message> {
+    button(text("Projekt w\u{00E4}hlen").size(layout.text(24.0)))
+        .on_press(Message::ProjectSelectOpened)
+        .padding([layout.space(14.0), layout.space(28.0)])
+        .style(theme::Button::Primary)
+        .into()
+}
+
+/// How many badges wait behind the screen on show.
+fn queue_note<'a>(queued: usize, layout: &Layout) -> Element<'a, Message> {
+    let waiting = if queued == 1 {
+        "1 weiterer Ausweis wartet".to_string()
+    } else {
+        format!("{} weitere Ausweise warten", queued)
+    };
+    text(waiting).size(layout.text(22.0)).into()
+}
+
+fn undo_button<'a>(seconds_left: u64, layout: &Layout) -> Element<'a, Message> {
+    button(text(format!("R\u{00FC}ckg\u{00E4}ngig ({}s)", seconds_left)).size(layout.text(22.0)))
+        .on_press(Message::UndoRequested)
+        .padding([layout.space(12.0), layout.space(24.0)])
+        .style(theme::Button::Secondary)
+        .into()
+}
+
+fn summary_item<'a>(
+    label: &str,
+    value: &str,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'a, Message> {
     column![
         text(label.to_string())
-            .size(16)
-            .style(Color::from_rgb(0.7, 0.7, 0.7)),
-        text(value.to_string()).size(22),
+            .size(layout.text(16.0))
+            .style(palette.secondary_text),
+        text(value.to_string()).size(layout.text(22.0)),
+    ]
+    .spacing(layout.space(4.0))
+    .align_items(Alignment::Center)
+    .into()
+}
+
+/// Summary values side by side in landscape, stacked in portrait where the width is too narrow.
+fn summary_group<'a>(items: Vec<Element<'a, Message>>, layout: &Layout) -> Element<'a, Message> {
+    if layout.portrait {
+        Column::with_children(items)
+            .spacing(layout.space(12.0))
+            .align_items(Alignment::Center)
+            .into()
+    } else {
+        Row::with_children(items)
+            .spacing(layout.space(40.0))
+            .align_items(Alignment::Center)
+            .into()
+    }
+}
+
+/// "Label: value" on a single line, for the maintenance screen.
+fn summary_line<'a>(
+    label: &str,
+    value: &str,
+    palette: &Palette,
+    layout: &Layout,
+) -> Element<'a, Message> {
+    row![
+        text(format!("{}:", label))
+            .size(layout.text(18.0))
+            .style(palette.secondary_text),
+        text(value.to_string()).size(layout.text(20.0)),
     ]
-    .spacing(4)
+    .spacing(layout.space(8.0))
     .align_items(Alignment::Center)
     .into()
 }